@@ -55,6 +55,7 @@ impl ActivationFunc {
 
 pub type ActivationFn = fn(Vec<f32>) -> Vec<f32>;
 
+#[derive(Clone)]
 pub struct Activation {
     f: ActivationFn,
     df: ActivationFn,
@@ -119,7 +120,7 @@ pub fn relu_df(v: Vec<f32>) -> Vec<f32> {
 }
 
 pub fn softmax(mut v: Vec<f32>) -> Vec<f32> {
-    let max = v.iter().max_by(|x, y| x.total_cmp(y)).unwrap().clone();
+    let max = *v.iter().max_by(|x, y| x.total_cmp(y)).unwrap();
     v = v.into_iter().map(|x| x - max).collect();
     let sum = v.iter().map(|x| x.exp()).sum::<f32>();
     if sum == 0.0 {
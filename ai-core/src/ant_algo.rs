@@ -0,0 +1,855 @@
+use std::sync::Arc;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use weighted_rand::builder::NewBuilder;
+
+use crate::local_search::{self, LocalSearch};
+use crate::run_control::RunControl;
+use crate::tsp::DistanceMatrix;
+
+#[derive(Debug, Clone)]
+pub struct Ant {
+    pub tabu: Vec<usize>,
+    pub edges: Vec<EdgeAnt>,
+    current_node: usize,
+    visited: Vec<bool>,
+    pub ant_index: i64,
+    pub iteration_index: i64,
+    pub distance: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EdgeInfo {
+    pub distance: f32,
+    pub pheromones: f32,
+    pub probability_parameters: f32,
+}
+
+impl EdgeInfo {
+    /// `pheromones^alpha * (1 / distance)^beta`
+    pub fn recalculate(&mut self, alpha: f32, beta: f32) {
+        self.probability_parameters =
+            self.pheromones.powf(alpha) * (1.0 / self.distance).powf(beta);
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EdgeAnt {
+    pub source: usize,
+    pub target: usize,
+    pub probability: f32,
+    pub edge_info: EdgeInfo,
+}
+
+/// How the colony lays and evaporates pheromones.
+///
+/// Every deposit on a tour of length `L` is `q / L`, `p` is the evaporation rate.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PheromoneStrategy {
+    /// Ant System: every ant deposits on its tour.
+    #[default]
+    AntSystem,
+    /// Ant System plus `elite_weight` extra deposits on the best-so-far tour.
+    Elitist { elite_weight: f32 },
+    /// The `w - 1` best ants of the iteration deposit with weight `w - rank`,
+    /// the best-so-far tour with weight `w`.
+    RankBased { w: usize },
+    /// MAX-MIN Ant System: only the iteration best ant deposits, pheromones stay
+    /// in `[tau_min, tau_max]` and are reset to `tau_max` after `stagnation`
+    /// iterations without a better tour.
+    MaxMin { p_best: f32, stagnation: usize },
+    /// Ant Colony System: the next node is the best one with probability `q0`,
+    /// every step decays the edge towards `tau0` by `xi`, and only the
+    /// best-so-far tour gets the global update.
+    AntColonySystem { q0: f32, xi: f32 },
+}
+
+impl std::fmt::Display for PheromoneStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::AntSystem => "Ant System",
+            Self::Elitist { .. } => "Elitist AS",
+            Self::RankBased { .. } => "Rank-based AS",
+            Self::MaxMin { .. } => "MAX-MIN AS",
+            Self::AntColonySystem { .. } => "Ant Colony System",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Edges of the graph traveled by the colony, stored as a dense matrix.
+///
+/// Nodes are addressed by their position in the [`DistanceMatrix`] the
+/// graph was built from.
+#[derive(Debug, Clone)]
+pub struct AntGraph {
+    size: usize,
+    edges: Vec<EdgeInfo>,
+    alpha: f32,
+    beta: f32,
+    // nearest neighbours the ants try first, see `with_candidates`
+    candidates: Option<Arc<Vec<Vec<usize>>>>,
+}
+
+impl AntGraph {
+    pub fn new(distances: &DistanceMatrix, alpha: f32, beta: f32) -> Self {
+        Self::with_pheromones(distances, alpha, beta, 0.0)
+    }
+    pub fn with_pheromones(
+        distances: &DistanceMatrix,
+        alpha: f32,
+        beta: f32,
+        pheromones: f32,
+    ) -> Self {
+        let size = distances.len();
+        let mut edges = Vec::with_capacity(size * size);
+        for a in 0..size {
+            for b in 0..size {
+                let mut edge = EdgeInfo {
+                    distance: distances.get(a, b),
+                    pheromones,
+                    probability_parameters: 0.0,
+                };
+                edge.recalculate(alpha, beta);
+                edges.push(edge);
+            }
+        }
+        Self {
+            size,
+            edges,
+            alpha,
+            beta,
+            candidates: None,
+        }
+    }
+    /// Restricts the ants to the `k` nearest unvisited neighbours of the
+    /// current node, the other nodes are only tried when all of them are visited.
+    pub fn with_candidates(mut self, distances: &DistanceMatrix, k: usize) -> Self {
+        self.candidates = Some(Arc::new(distances.neighbour_lists(k)));
+        self
+    }
+    pub fn node_count(&self) -> usize {
+        self.size
+    }
+    pub fn edge(&self, a: usize, b: usize) -> &EdgeInfo {
+        &self.edges[a * self.size + b]
+    }
+    pub fn has_edge(&self, a: usize, b: usize) -> bool {
+        a != b && self.edge(a, b).distance.is_finite()
+    }
+    fn set_pheromones(&mut self, a: usize, b: usize, pheromones: f32) {
+        let mut edge = *self.edge(a, b);
+        edge.pheromones = pheromones;
+        edge.recalculate(self.alpha, self.beta);
+        self.edges[a * self.size + b] = edge;
+        self.edges[b * self.size + a] = edge;
+    }
+    fn deposit(&mut self, tour: &[usize], pheromones: f32) {
+        for i in 0..tour.len() {
+            let (a, b) = (tour[i], tour[(i + 1) % tour.len()]);
+            self.set_pheromones(a, b, self.edge(a, b).pheromones + pheromones);
+        }
+    }
+    fn evaporate(&mut self, p: f32) {
+        for edge in self.edges.iter_mut() {
+            edge.pheromones *= 1.0 - p;
+            edge.recalculate(self.alpha, self.beta);
+        }
+    }
+    fn clamp_pheromones(&mut self, min: f32, max: f32) {
+        for edge in self.edges.iter_mut() {
+            edge.pheromones = edge.pheromones.clamp(min, max);
+            edge.recalculate(self.alpha, self.beta);
+        }
+    }
+    fn edge_ant(&self, source: usize, target: usize) -> EdgeAnt {
+        EdgeAnt {
+            source,
+            target,
+            probability: 1.0,
+            edge_info: *self.edge(source, target),
+        }
+    }
+}
+
+impl Ant {
+    fn new(start_index: usize, ant_index: i64, iteration_index: i64, graph_size: usize) -> Self {
+        Self {
+            tabu: vec![],
+            current_node: start_index,
+            visited: vec![false; graph_size],
+            edges: vec![],
+            ant_index,
+            iteration_index,
+            distance: 0.0,
+        }
+    }
+    fn add_tabu_node(&mut self, node_index: usize) {
+        self.tabu.push(node_index);
+        self.visited[node_index] = true;
+    }
+    fn add_edge(&mut self, edge: EdgeAnt) {
+        self.edges.push(edge)
+    }
+    fn get_edge_ant(&mut self, g: &AntGraph) -> Vec<EdgeAnt> {
+        let is_free = |x: &usize| !self.visited[*x] && *x != self.current_node;
+        let mut edges = match &g.candidates {
+            Some(candidates) => candidates[self.current_node]
+                .iter()
+                .copied()
+                .filter(is_free)
+                .map(|x| g.edge_ant(self.current_node, x))
+                .collect::<Vec<_>>(),
+            None => vec![],
+        };
+        if edges.is_empty() {
+            edges = (0..g.node_count())
+                .filter(|x| g.has_edge(self.current_node, *x) && is_free(x))
+                .map(|x| g.edge_ant(self.current_node, x))
+                .collect::<Vec<_>>();
+        }
+        let sum: f32 = edges
+            .iter()
+            .map(|x| x.edge_info.probability_parameters)
+            .sum();
+        if sum == 0.0 {
+            let edges_amount = edges.len() as f32;
+            edges
+                .iter_mut()
+                .for_each(|x| x.probability = 1.0 / edges_amount);
+        } else {
+            edges
+                .iter_mut()
+                .for_each(|x| x.probability = x.edge_info.probability_parameters / sum);
+        }
+        edges
+    }
+    fn choose_edge(edges: &[EdgeAnt], strategy: &PheromoneStrategy, rng: &mut impl Rng) -> EdgeAnt {
+        if let PheromoneStrategy::AntColonySystem { q0, .. } = strategy {
+            if rng.gen::<f32>() < *q0 {
+                return *edges
+                    .iter()
+                    .max_by(|x, y| x.probability.total_cmp(&y.probability))
+                    .unwrap();
+            }
+        }
+        let indexes_weights = edges
+            .iter()
+            .map(|x| {
+                if x.probability.is_nan() {
+                    0.01
+                } else {
+                    x.probability
+                }
+            })
+            .collect::<Vec<_>>();
+        let wa_table = weighted_rand::builder::WalkerTableBuilder::new(&indexes_weights).build();
+        edges[wa_table.next_rng(rng)]
+    }
+    fn is_finished(&self, g: &AntGraph) -> bool {
+        self.tabu.len() == g.node_count()
+    }
+    // Moves the ant by one edge, the last step returns to the start node
+    fn step(
+        &mut self,
+        g: &AntGraph,
+        strategy: &PheromoneStrategy,
+        rng: &mut impl Rng,
+    ) -> (usize, usize) {
+        let from = self.current_node;
+        if self.tabu.len() == g.node_count() - 1 {
+            self.add_tabu_node(from);
+            self.add_edge(g.edge_ant(from, self.tabu[0]));
+            return (from, self.tabu[0]);
+        }
+        let edges = self.get_edge_ant(g);
+        let next_edge = Self::choose_edge(&edges, strategy, rng);
+        self.add_tabu_node(from);
+        self.add_edge(next_edge);
+        self.current_node = next_edge.target;
+        (from, next_edge.target)
+    }
+    fn travel_graph(&mut self, g: &AntGraph, strategy: &PheromoneStrategy, rng: &mut impl Rng) {
+        while !self.is_finished(g) {
+            self.step(g, strategy, rng);
+        }
+    }
+    // Shortens the traveled tour, the edges are rebuilt from the new tour
+    fn improve(&mut self, g: &AntGraph, search: &LocalSearch, neighbours: Option<&[Vec<usize>]>) {
+        let distance = |a: usize, b: usize| g.edge(a, b).distance;
+        if local_search::improve(&mut self.tabu, &distance, search, neighbours) {
+            let n = self.tabu.len();
+            self.edges = (0..n)
+                .map(|i| g.edge_ant(self.tabu[i], self.tabu[(i + 1) % n]))
+                .collect();
+        }
+    }
+}
+
+// ACS decays every traveled edge towards tau0 right away
+fn local_update(g: &mut AntGraph, strategy: &PheromoneStrategy, tau0: f32, a: usize, b: usize) {
+    if let PheromoneStrategy::AntColonySystem { xi, .. } = strategy {
+        g.set_pheromones(a, b, (1.0 - xi) * g.edge(a, b).pheromones + xi * tau0);
+    }
+}
+
+// Runs `f` on every item, the items are split between `threads` threads or
+// the available ones
+fn par_for_each<T: Send>(items: &mut [T], threads: Option<usize>, f: impl Fn(&mut T) + Sync) {
+    let threads = threads
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |x| x.get()))
+        .max(1);
+    let chunk_size = items.len().div_ceil(threads).max(1);
+    if chunk_size >= items.len() {
+        items.iter_mut().for_each(f);
+        return;
+    }
+    let f = &f;
+    std::thread::scope(|scope| {
+        for chunk in items.chunks_mut(chunk_size) {
+            scope.spawn(move || chunk.iter_mut().for_each(f));
+        }
+    });
+}
+
+fn random_node_idx(g: &AntGraph, rng: &mut impl Rng) -> Option<usize> {
+    let nodes_cnt = g.node_count();
+    if nodes_cnt == 0 {
+        return None;
+    }
+
+    Some(rng.gen_range(0..nodes_cnt))
+}
+
+// Initial pheromones of every edge, `nn_len` is the length of a nearest neighbour tour
+fn initial_pheromones(
+    strategy: &PheromoneStrategy,
+    n: usize,
+    ant_amount: usize,
+    q: f32,
+    p: f32,
+    nn_len: f32,
+) -> f32 {
+    match strategy {
+        PheromoneStrategy::MaxMin { .. } => q / (p * nn_len),
+        PheromoneStrategy::AntColonySystem { .. } => q / (n as f32 * nn_len),
+        _ => q * ant_amount as f32 / nn_len,
+    }
+}
+
+// Pheromone bounds of MAX-MIN Ant System for the best-so-far tour length
+fn max_min_bounds(p_best: f32, n: usize, q: f32, p: f32, best_len: f32) -> (f32, f32) {
+    let tau_max = q / (p * best_len);
+    let root = p_best.powf(1.0 / n as f32);
+    let avg = (n as f32 / 2.0 - 1.0).max(1.0);
+    let tau_min = (tau_max * (1.0 - root) / (avg * root)).min(tau_max);
+    (tau_min, tau_max)
+}
+
+// Best-so-far tour of the run
+struct BestTour {
+    tour: Vec<usize>,
+    length: f32,
+    // iterations since the last improvement
+    stagnation: usize,
+}
+
+fn update_edges(
+    ants: &[Ant],
+    g: &mut AntGraph,
+    best: &BestTour,
+    strategy: &PheromoneStrategy,
+    q: f32,
+    p: f32,
+) {
+    let mut ranked = ants.iter().collect::<Vec<_>>();
+    ranked.sort_by(|x, y| x.distance.total_cmp(&y.distance));
+    match strategy {
+        PheromoneStrategy::AntSystem => {
+            g.evaporate(p);
+            for ant in ants {
+                g.deposit(&ant.tabu, q / ant.distance);
+            }
+        }
+        PheromoneStrategy::Elitist { elite_weight } => {
+            g.evaporate(p);
+            for ant in ants {
+                g.deposit(&ant.tabu, q / ant.distance);
+            }
+            g.deposit(&best.tour, elite_weight * q / best.length);
+        }
+        PheromoneStrategy::RankBased { w } => {
+            g.evaporate(p);
+            for (rank, ant) in ranked.iter().take(w.saturating_sub(1)).enumerate() {
+                g.deposit(&ant.tabu, (w - rank - 1) as f32 * q / ant.distance);
+            }
+            g.deposit(&best.tour, *w as f32 * q / best.length);
+        }
+        PheromoneStrategy::MaxMin { p_best, stagnation } => {
+            let (tau_min, tau_max) = max_min_bounds(*p_best, g.node_count(), q, p, best.length);
+            if best.stagnation >= *stagnation {
+                g.clamp_pheromones(tau_max, tau_max);
+            } else {
+                g.evaporate(p);
+                g.deposit(&ranked[0].tabu, q / ranked[0].distance);
+                g.clamp_pheromones(tau_min, tau_max);
+            }
+        }
+        PheromoneStrategy::AntColonySystem { .. } => {
+            for i in 0..best.tour.len() {
+                let (a, b) = (best.tour[i], best.tour[(i + 1) % best.tour.len()]);
+                let pheromones = (1.0 - p) * g.edge(a, b).pheromones + p * q / best.length;
+                g.set_pheromones(a, b, pheromones);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IterationInfo {
+    pub index: usize,
+    /// The graph before the iteration, only for the iterations chosen by the
+    /// `snapshots` of [`ant_algo`].
+    pub old_edges: Option<AntGraph>,
+    pub ants: Vec<Ant>,
+    pub best_ant_i: i64,
+    pub best_path_len: f32,
+    /// Whether the iteration found a new best-so-far tour.
+    pub new_best: bool,
+}
+
+/// Iterations kept in the result of [`ant_algo`], every iteration is still
+/// passed to the callback.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum History {
+    None,
+    /// Iterations that found a new best-so-far tour.
+    BestOnly,
+    /// Every `k`-th iteration, starting with the first one.
+    EveryNth(usize),
+    #[default]
+    Full,
+}
+
+impl History {
+    pub fn keeps(&self, iteration: &IterationInfo) -> bool {
+        match self {
+            Self::None => false,
+            Self::BestOnly => iteration.new_best,
+            Self::EveryNth(k) => iteration.index.is_multiple_of((*k).max(1)),
+            Self::Full => true,
+        }
+    }
+    // Whether the iteration with `index` may be kept, known before it is done
+    fn may_keep(&self, index: usize) -> bool {
+        match self {
+            Self::None => false,
+            Self::EveryNth(k) => index.is_multiple_of((*k).max(1)),
+            Self::BestOnly | Self::Full => true,
+        }
+    }
+}
+
+impl std::fmt::Display for History {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => write!(f, "None"),
+            Self::BestOnly => write!(f, "Best only"),
+            Self::EveryNth(k) => write!(f, "Every {}th", k),
+            Self::Full => write!(f, "Full"),
+        }
+    }
+}
+
+/// Parameters of [`ant_algo`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AntOptions {
+    pub iterations_amount: i64,
+    pub ant_amount: i64,
+    /// Weight of the pheromones in the edge choice.
+    pub alpha: f32,
+    /// Weight of the inverse distance in the edge choice.
+    pub beta: f32,
+    pub q: f32,
+    /// Evaporation rate.
+    pub p: f32,
+    pub strategy: PheromoneStrategy,
+    /// Size of the candidate lists, see [`AntGraph::with_candidates`].
+    pub candidates: Option<usize>,
+    pub local_search: LocalSearch,
+    /// Iterations returned by [`ant_algo`].
+    pub history: History,
+    /// Iterations that get `old_edges` without being returned.
+    pub snapshots: History,
+    /// Threads the ants travel on, `None` uses every available core. The
+    /// result is the same for any number of threads.
+    pub threads: Option<usize>,
+}
+
+impl Default for AntOptions {
+    fn default() -> Self {
+        Self {
+            iterations_amount: 100,
+            ant_amount: 10,
+            alpha: 1.0,
+            beta: 2.0,
+            q: 1.0,
+            p: 0.5,
+            strategy: PheromoneStrategy::default(),
+            candidates: None,
+            local_search: LocalSearch::default(),
+            history: History::default(),
+            snapshots: History::None,
+            threads: None,
+        }
+    }
+}
+
+/// Runs the colony, `on_iteration` gets every iteration as soon as it is
+/// done and `options.history` chooses the iterations that are returned. The
+/// graph before an iteration is copied to `old_edges` only when `history` or
+/// `snapshots` may keep it, e.g. `snapshots` of the iterations a callback
+/// stores with `History::None`.
+///
+/// Stops early when `control` says so, the result then holds the iterations
+/// done so far.
+pub fn ant_algo(
+    distances: &DistanceMatrix,
+    options: &AntOptions,
+    mut on_iteration: impl FnMut(&IterationInfo),
+    control: &RunControl,
+    rng: &mut impl Rng,
+) -> Vec<IterationInfo> {
+    let AntOptions {
+        iterations_amount,
+        ant_amount,
+        alpha,
+        beta,
+        q,
+        p,
+        strategy,
+        candidates,
+        local_search,
+        history,
+        snapshots,
+        threads,
+    } = *options;
+    let mut iterations: Vec<IterationInfo> = vec![];
+    let n = distances.len();
+    let nn_len = distances.tour_length(&distances.nearest_neighbour_tour(0));
+    let tau0 = initial_pheromones(&strategy, n, ant_amount as usize, q, p, nn_len);
+    let mut g = AntGraph::with_pheromones(distances, alpha, beta, tau0);
+    if let Some(k) = candidates {
+        g = g.with_candidates(distances, k);
+    }
+    let neighbour_lists = local_search
+        .neighbours
+        .map(|k| distances.neighbour_lists(k));
+    let mut best = BestTour {
+        tour: vec![],
+        length: f32::INFINITY,
+        stagnation: 0,
+    };
+    let one_ant_per_node = ant_amount as usize == g.node_count();
+    // `best.stagnation` is reset by MAX-MIN, this one is not
+    let mut since_best = 0;
+    for iteration_i in 0..iterations_amount {
+        if control.should_stop(since_best) {
+            break;
+        }
+        let index = iteration_i as usize;
+        let old_edges = (history.may_keep(index) || snapshots.may_keep(index)).then(|| g.clone());
+        // every ant gets its own generator, so the result doesn't depend on
+        // how the ants are split between threads
+        let mut colony = (0..ant_amount)
+            .map(|ant_i| {
+                let start = if one_ant_per_node {
+                    ant_i as usize
+                } else {
+                    random_node_idx(&g, rng).unwrap()
+                };
+                let ant = Ant::new(start, ant_i, iteration_i, n);
+                (ant, StdRng::seed_from_u64(rng.gen()))
+            })
+            .collect::<Vec<_>>();
+        if let PheromoneStrategy::AntColonySystem { .. } = strategy {
+            // the local update changes the graph after every step, so the
+            // ants of ACS travel one after another
+            for (ant, ant_rng) in colony.iter_mut() {
+                while !ant.is_finished(&g) {
+                    let (a, b) = ant.step(&g, &strategy, ant_rng);
+                    local_update(&mut g, &strategy, tau0, a, b);
+                }
+            }
+        } else {
+            par_for_each(&mut colony, threads, |(ant, ant_rng)| {
+                ant.travel_graph(&g, &strategy, ant_rng)
+            });
+        }
+        par_for_each(&mut colony, threads, |(ant, _)| {
+            if local_search.is_enabled() {
+                ant.improve(&g, &local_search, neighbour_lists.as_deref());
+            }
+            ant.distance = ant.edges.iter().map(|x| x.edge_info.distance).sum::<f32>();
+        });
+        let ants = colony.into_iter().map(|(ant, _)| ant).collect::<Vec<_>>();
+        let best_ant_i = ants
+            .iter()
+            .enumerate()
+            .min_by(|x, y| x.1.distance.total_cmp(&y.1.distance))
+            .unwrap()
+            .0;
+        let best_path_len = ants[best_ant_i].distance;
+        let new_best = best_path_len < best.length;
+        since_best = if new_best { 0 } else { since_best + 1 };
+        if new_best {
+            best.tour = ants[best_ant_i].tabu.clone();
+            best.length = best_path_len;
+            best.stagnation = 0;
+        } else {
+            best.stagnation += 1;
+        }
+        update_edges(&ants, &mut g, &best, &strategy, q, p);
+        if let PheromoneStrategy::MaxMin { stagnation, .. } = strategy {
+            if best.stagnation >= stagnation {
+                best.stagnation = 0;
+            }
+        }
+        let iteration = IterationInfo {
+            index,
+            old_edges,
+            ants,
+            best_ant_i: best_ant_i as i64,
+            best_path_len,
+            new_best,
+        };
+        on_iteration(&iteration);
+        if history.keeps(&iteration) {
+            iterations.push(iteration);
+        }
+    }
+    iterations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRATEGIES: [PheromoneStrategy; 5] = [
+        PheromoneStrategy::AntSystem,
+        PheromoneStrategy::Elitist { elite_weight: 2.0 },
+        PheromoneStrategy::RankBased { w: 4 },
+        PheromoneStrategy::MaxMin {
+            p_best: 0.05,
+            stagnation: 20,
+        },
+        PheromoneStrategy::AntColonySystem { q0: 0.9, xi: 0.1 },
+    ];
+
+    fn instance() -> DistanceMatrix {
+        let points = [
+            (0.0, 0.0),
+            (3.0, 1.0),
+            (6.0, 0.0),
+            (7.0, 4.0),
+            (4.0, 6.0),
+            (1.0, 5.0),
+            (3.0, 3.0),
+        ];
+        DistanceMatrix::from_fn(points.len(), |a, b| {
+            let (x, y): (f32, f32) = (points[a].0 - points[b].0, points[a].1 - points[b].1);
+            (x * x + y * y).sqrt()
+        })
+    }
+
+    // Length of the shortest tour by trying every tour starting at node 0
+    fn optimal_length(distances: &DistanceMatrix) -> f32 {
+        fn search(tour: &mut Vec<usize>, distances: &DistanceMatrix, best: &mut f32) {
+            if tour.len() == distances.len() {
+                *best = best.min(distances.tour_length(tour));
+                return;
+            }
+            for node in 1..distances.len() {
+                if !tour.contains(&node) {
+                    tour.push(node);
+                    search(tour, distances, best);
+                    tour.pop();
+                }
+            }
+        }
+        let mut best = f32::INFINITY;
+        search(&mut vec![0], distances, &mut best);
+        best
+    }
+
+    #[test]
+    fn every_strategy_solves_a_tiny_instance() {
+        let distances = instance();
+        let optimum = optimal_length(&distances);
+        for strategy in STRATEGIES {
+            let options = AntOptions {
+                iterations_amount: 100,
+                ant_amount: 7,
+                p: 0.1,
+                strategy,
+                ..AntOptions::default()
+            };
+            let iterations = ant_algo(
+                &distances,
+                &options,
+                |_| (),
+                &RunControl::new(),
+                &mut StdRng::seed_from_u64(0),
+            );
+            assert_eq!(iterations.len(), 100, "{}", strategy);
+            let best = iterations
+                .iter()
+                .map(|x| x.best_path_len)
+                .fold(f32::INFINITY, f32::min);
+            assert!(
+                (best - optimum).abs() < 1e-4,
+                "{}: {} > {}",
+                strategy,
+                best,
+                optimum
+            );
+            let ant = &iterations[0].ants[0];
+            let mut tour = ant.tabu.clone();
+            tour.sort();
+            assert_eq!(
+                tour,
+                (0..distances.len()).collect::<Vec<_>>(),
+                "{}",
+                strategy
+            );
+            assert!((distances.tour_length(&ant.tabu) - ant.distance).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn max_min_trails_stay_within_bounds() {
+        let distances = instance();
+        let (p_best, q, p) = (0.05, 1.0, 0.2);
+        let options = AntOptions {
+            iterations_amount: 60,
+            ant_amount: 4,
+            q,
+            p,
+            strategy: PheromoneStrategy::MaxMin {
+                p_best,
+                stagnation: 15,
+            },
+            ..AntOptions::default()
+        };
+        let iterations = ant_algo(
+            &distances,
+            &options,
+            |_| (),
+            &RunControl::new(),
+            &mut StdRng::seed_from_u64(0),
+        );
+        let n = distances.len();
+        let mut best_len = f32::INFINITY;
+        for pair in iterations.windows(2) {
+            best_len = best_len.min(pair[0].best_path_len);
+            // the graph before the next iteration is the one updated by this one
+            let g = pair[1].old_edges.as_ref().unwrap();
+            let (tau_min, tau_max) = max_min_bounds(p_best, n, q, p, best_len);
+            for a in 0..n {
+                for b in (0..n).filter(|b| *b != a) {
+                    let pheromones = g.edge(a, b).pheromones;
+                    assert!(
+                        pheromones >= tau_min * 0.9999 && pheromones <= tau_max * 1.0001,
+                        "iteration {}: {} not in [{}, {}]",
+                        pair[1].index,
+                        pheromones,
+                        tau_min,
+                        tau_max
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn thread_count_does_not_change_the_result() {
+        let distances = DistanceMatrix::from_fn(30, |a, b| ((a * 7 + b * 7) % 31 + 1) as f32);
+        for strategy in [
+            PheromoneStrategy::AntSystem,
+            PheromoneStrategy::RankBased { w: 4 },
+        ] {
+            let run = |threads| {
+                let options = AntOptions {
+                    iterations_amount: 20,
+                    ant_amount: 12,
+                    strategy,
+                    candidates: Some(8),
+                    local_search: LocalSearch {
+                        two_opt: true,
+                        or_opt: true,
+                        three_opt: false,
+                        neighbours: Some(8),
+                    },
+                    threads: Some(threads),
+                    ..AntOptions::default()
+                };
+                ant_algo(
+                    &distances,
+                    &options,
+                    |_| (),
+                    &RunControl::new(),
+                    &mut StdRng::seed_from_u64(7),
+                )
+                .into_iter()
+                .map(|x| (x.ants[x.best_ant_i as usize].tabu.clone(), x.best_path_len))
+                .collect::<Vec<_>>()
+            };
+            let single = run(1);
+            for threads in [2, 3, 8] {
+                assert_eq!(run(threads), single, "{} on {} threads", strategy, threads);
+            }
+        }
+    }
+
+    #[test]
+    fn history_keeps_the_chosen_iterations() {
+        let iteration = |index: usize| IterationInfo {
+            index,
+            old_edges: None,
+            ants: vec![],
+            best_ant_i: 0,
+            best_path_len: 0.0,
+            new_best: [0, 3, 4].contains(&index),
+        };
+        let kept = |history: History| {
+            (0..8)
+                .filter(|x| history.keeps(&iteration(*x)))
+                .collect::<Vec<_>>()
+        };
+        let may_keep =
+            |history: History| (0..8).filter(|x| history.may_keep(*x)).collect::<Vec<_>>();
+        assert!(kept(History::None).is_empty());
+        assert_eq!(kept(History::BestOnly), [0, 3, 4]);
+        assert_eq!(kept(History::EveryNth(3)), [0, 3, 6]);
+        assert_eq!(kept(History::EveryNth(0)), (0..8).collect::<Vec<_>>());
+        assert_eq!(kept(History::Full), (0..8).collect::<Vec<_>>());
+        // the iterations kept later must have their graph copied beforehand
+        for history in [
+            History::None,
+            History::BestOnly,
+            History::EveryNth(3),
+            History::EveryNth(0),
+            History::Full,
+        ] {
+            let may_keep = may_keep(history);
+            assert!(
+                kept(history).iter().all(|x| may_keep.contains(x)),
+                "{}",
+                history
+            );
+        }
+        assert!(may_keep(History::None).is_empty());
+        assert_eq!(may_keep(History::EveryNth(3)), [0, 3, 6]);
+        assert_eq!(may_keep(History::BestOnly), (0..8).collect::<Vec<_>>());
+    }
+}
@@ -1,4 +1,5 @@
 use rand::distributions::{Alphanumeric, DistString};
+use rand::Rng;
 
 use bit_vec;
 
@@ -10,11 +11,11 @@ pub struct Claster {
 }
 
 impl Claster {
-    pub fn new() -> Self {
+    pub fn new(rng: &mut impl Rng) -> Self {
         Self {
             v: bit_vec::BitVec::default(),
             indexes: Vec::default(),
-            id: Alphanumeric.sample_string(&mut rand::thread_rng(), 16),
+            id: Alphanumeric.sample_string(rng, 16),
         }
     }
     fn add(&mut self, entity: &mut ClasterEntity) {
@@ -26,11 +27,10 @@ impl Claster {
         self.indexes.push(entity.data_index);
         entity.claster_id = self.id.clone();
     }
-    pub fn recalculate(&mut self, data: &Vec<bit_vec::BitVec>) {
+    pub fn recalculate(&mut self, data: &[bit_vec::BitVec]) {
         self.v.clear();
-        match self.indexes.first() {
-            Some(i) => self.v = data[*i].clone(),
-            None => (),
+        if let Some(i) = self.indexes.first() {
+            self.v = data[*i].clone()
         }
         for i in &self.indexes {
             self.v.and(&data[*i]);
@@ -39,7 +39,7 @@ impl Claster {
     pub fn contains(&self, index: &usize) -> bool {
         self.indexes.contains(index)
     }
-    pub fn remove(&mut self, data: &Vec<bit_vec::BitVec>, entity: &mut ClasterEntity) {
+    fn remove(&mut self, data: &[bit_vec::BitVec], entity: &mut ClasterEntity) {
         self.indexes.remove(
             self.indexes
                 .iter()
@@ -55,17 +55,17 @@ fn get_energy(v: &bit_vec::BitVec) -> u32 {
     v.blocks().map(|x| x.count_ones()).sum()
 }
 
-fn similar(P: &bit_vec::BitVec, E: &bit_vec::BitVec, b: f64) -> bool {
-    let mut intersect = P.clone();
-    intersect.and(E);
-    get_energy(&intersect) as f64 / (b + get_energy(P) as f64)
-        > get_energy(E) as f64 / (b + P.len() as f64)
+fn similar(prototype: &bit_vec::BitVec, v: &bit_vec::BitVec, b: f64) -> bool {
+    let mut intersect = prototype.clone();
+    intersect.and(v);
+    get_energy(&intersect) as f64 / (b + get_energy(prototype) as f64)
+        > get_energy(v) as f64 / (b + prototype.len() as f64)
 }
 
-fn pass(P: &bit_vec::BitVec, E: &bit_vec::BitVec, p: f64) -> bool {
-    let mut intersect = P.clone();
-    intersect.and(E);
-    (get_energy(&intersect) as f64 / get_energy(E) as f64) >= p
+fn pass(prototype: &bit_vec::BitVec, v: &bit_vec::BitVec, p: f64) -> bool {
+    let mut intersect = prototype.clone();
+    intersect.and(v);
+    (get_energy(&intersect) as f64 / get_energy(v) as f64) >= p
 }
 
 #[derive(Debug)]
@@ -86,25 +86,19 @@ impl ClastersManager {
     fn add_claster(&mut self, claster: Claster) {
         self.clasters.push(claster);
     }
-    fn remove_from_claster(&mut self, data: &Vec<bit_vec::BitVec>, entity: &mut ClasterEntity) {
-        match self.clasters.iter().position(|x| x.id == entity.claster_id) {
-            Some(index) => self.clasters[index].remove(data, entity),
-            None => (),
+    fn remove_from_claster(&mut self, data: &[bit_vec::BitVec], entity: &mut ClasterEntity) {
+        if let Some(index) = self.clasters.iter().position(|x| x.id == entity.claster_id) {
+            self.clasters[index].remove(data, entity)
         }
     }
     fn find_claster(&mut self, entity: &mut ClasterEntity) -> Option<usize> {
-        for i in 0..self.clasters.len() {
-            if entity.claster_id != self.clasters[i].id
-                && similar(&self.clasters[i].v, &entity.v, self.b)
-            {
-                if pass(&self.clasters[i].v, &entity.v, self.p) {
-                    return Some(i);
-                }
-            }
-        }
-        None
+        self.clasters.iter().position(|claster| {
+            entity.claster_id != claster.id
+                && similar(&claster.v, &entity.v, self.b)
+                && pass(&claster.v, &entity.v, self.p)
+        })
     }
-    fn dispatch_entity(&mut self, data: &Vec<bit_vec::BitVec>, entity: &mut ClasterEntity) -> bool {
+    fn dispatch_entity(&mut self, data: &[bit_vec::BitVec], entity: &mut ClasterEntity) -> bool {
         match self.find_claster(entity) {
             Some(index) => {
                 self.remove_from_claster(data, entity);
@@ -118,16 +112,17 @@ impl ClastersManager {
         self.clasters = self
             .clasters
             .drain(..)
-            .filter(|x| x.indexes.len() != 0)
+            .filter(|x| !x.indexes.is_empty())
             .collect();
     }
 }
 
 pub fn art1(
-    data: &Vec<bit_vec::BitVec>,
+    data: &[bit_vec::BitVec],
     amount_clasters: &usize,
     p: &f64,
     b: &f64,
+    rng: &mut impl Rng,
 ) -> (Vec<Claster>, Vec<usize>) {
     let mut entities: Vec<_> = data
         .iter()
@@ -138,7 +133,7 @@ pub fn art1(
             claster_id: "-".into(),
         })
         .collect();
-    let mut first_claster = Claster::new();
+    let mut first_claster = Claster::new(rng);
     first_claster.add(&mut entities[0]);
     let mut clasters = ClastersManager {
         clasters: vec![first_claster],
@@ -154,7 +149,7 @@ pub fn art1(
         for entity in &mut entities {
             changed = clasters.dispatch_entity(data, entity);
             if &clasters.clasters.len() < amount_clasters && entity.claster_id == "-" {
-                let mut claster = Claster::new();
+                let mut claster = Claster::new(rng);
                 claster.add(entity);
                 clasters.add_claster(claster);
                 changed = true;
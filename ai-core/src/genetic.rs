@@ -0,0 +1,519 @@
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+use petgraph::stable_graph::NodeIndex;
+use rand::{seq::SliceRandom, Rng};
+
+use weighted_rand::builder::NewBuilder;
+use weighted_rand::table::WalkerTable;
+
+use crate::local_search::{self, LocalSearch};
+use crate::run_control::RunControl;
+use crate::tsp::DistanceMatrix;
+
+pub mod crossover;
+pub mod encoding;
+pub mod genealogy;
+pub mod island;
+pub mod mutation;
+pub mod nsga2;
+pub mod replacement;
+pub mod selection;
+pub mod stats;
+
+use crossover::PermutationCrossover;
+use mutation::{PermutationMutation, SharedMutationMix};
+use replacement::Replacement;
+use selection::Selection;
+use stats::GenerationStats;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TSPParentInfo {
+    pub chromosome_index: usize,
+    pub population_index: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TSPChromosomeType {
+    Crossover(TSPParentInfo, TSPParentInfo),
+    Mutation(TSPParentInfo, PermutationMutation),
+    /// Copy of a chromosome of the island with the given index.
+    Migration(usize, TSPParentInfo),
+    /// The chromosome itself, carried over unchanged from the previous population.
+    Survivor(TSPParentInfo),
+    NoHistory,
+}
+
+/// Chromosome of the travel salesman problem, `path_length` is its cached
+/// `health`.
+///
+/// Mutations update the length from the changed edges only, the sum is kept
+/// in `exact_length` so the rounding doesn't add up over the generations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TSPChromosome {
+    pub index: usize,
+    pub population: usize,
+    pub travel_list: Vec<NodeIndex>,
+    pub path_length: f32,
+    pub exact_length: f64,
+    /// Distances between the nodes, indexed by `NodeIndex::index` and shared
+    /// by every chromosome of the problem.
+    pub distances: Arc<DistanceMatrix>,
+    pub chromosome_type: TSPChromosomeType,
+    pub crossover: PermutationCrossover,
+    pub mutation: SharedMutationMix,
+}
+
+fn path_length(travel_list: &[NodeIndex], distances: &DistanceMatrix) -> f64 {
+    let n = travel_list.len();
+    (0..n)
+        .map(|i| distances.get(travel_list[i].index(), travel_list[(i + 1) % n].index()) as f64)
+        .sum()
+}
+
+// Length of the edges that lead to the positions `changed` and out of the last one
+fn edges_length(
+    travel_list: &[NodeIndex],
+    changed: &RangeInclusive<usize>,
+    distances: &DistanceMatrix,
+) -> f64 {
+    let n = travel_list.len();
+    (*changed.start()..=*changed.end() + 1)
+        .map(|i| {
+            distances.get(
+                travel_list[(i + n - 1) % n].index(),
+                travel_list[i % n].index(),
+            ) as f64
+        })
+        .sum()
+}
+
+impl TSPChromosome {
+    pub fn new(
+        index: usize,
+        population: usize,
+        travel_list: Vec<NodeIndex>,
+        distances: Arc<DistanceMatrix>,
+    ) -> Self {
+        let exact_length = path_length(&travel_list, &distances);
+        Self {
+            index,
+            population,
+            travel_list,
+            path_length: exact_length as f32,
+            exact_length,
+            distances,
+            chromosome_type: TSPChromosomeType::NoHistory,
+            crossover: PermutationCrossover::default(),
+            mutation: SharedMutationMix::default(),
+        }
+    }
+    /// Crossover of the chromosome and its offspring.
+    pub fn with_crossover(mut self, crossover: PermutationCrossover) -> Self {
+        self.crossover = crossover;
+        self
+    }
+    /// Mutations of the chromosome and its offspring, share one mix across
+    /// the population to adapt it and to count the successes. With islands
+    /// give every island its own mix.
+    pub fn with_mutation(mut self, mutation: SharedMutationMix) -> Self {
+        self.mutation = mutation;
+        self
+    }
+    pub fn generate_random_population(
+        indices: Vec<NodeIndex>,
+        population_size: usize,
+        distances: Arc<DistanceMatrix>,
+        rng: &mut impl Rng,
+    ) -> Vec<Self> {
+        let mut travel_list = indices;
+        (0..population_size)
+            .map(|index| {
+                travel_list.shuffle(rng);
+                TSPChromosome::new(index, 0, travel_list.clone(), distances.clone())
+            })
+            .collect::<Vec<_>>()
+    }
+    /// Recomputes `path_length` after `travel_list` was changed directly.
+    pub fn update_path_length(&mut self) {
+        self.exact_length = path_length(&self.travel_list, &self.distances);
+        self.path_length = self.exact_length as f32;
+    }
+    /// Memetic step: shortens the travel list with local search.
+    pub fn improve(&mut self, search: &LocalSearch, neighbours: Option<&[Vec<usize>]>) {
+        let distance = |a: usize, b: usize| self.distances.get(a, b);
+        let mut tour = self.travel_list.iter().map(|x| x.index()).collect();
+        if local_search::improve(&mut tour, &distance, search, neighbours) {
+            self.travel_list = tour.into_iter().map(NodeIndex::new).collect();
+            self.update_path_length();
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TSPIteration {
+    pub old: Vec<TSPChromosome>,
+    pub new: Vec<TSPChromosome>,
+    pub best_chromosome_i: usize,
+    pub stats: GenerationStats,
+}
+
+impl GeneticIteration<TSPChromosome> for TSPIteration {
+    fn new_iter(old: Vec<TSPChromosome>, new: Vec<TSPChromosome>) -> Self {
+        Self {
+            stats: GenerationStats::new(&old, &new),
+            old: old.clone(),
+            new,
+            best_chromosome_i: old
+                .into_iter()
+                .map(|x| x.path_length)
+                .enumerate()
+                .min_by(|x, y| x.1.total_cmp(&y.1))
+                .unwrap()
+                .0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TSPSolution {
+    pub iterations: Vec<TSPIteration>,
+    pub best_population_i: usize,
+    pub best_chromosome_i: usize,
+}
+
+impl Solution<TSPChromosome, TSPIteration> for TSPSolution {
+    fn new_solution() -> Self {
+        Self {
+            iterations: vec![],
+            best_population_i: 0,
+            best_chromosome_i: 0,
+        }
+    }
+    fn add_iteration(&mut self, iteration: TSPIteration) {
+        let dist = iteration.old[iteration.best_chromosome_i].path_length;
+        let best = iteration.best_chromosome_i;
+        self.iterations.push(iteration);
+        if self.iterations[self.best_population_i].old[self.best_chromosome_i].path_length > dist {
+            self.best_population_i = self.iterations.len() - 1;
+            self.best_chromosome_i = best;
+        }
+    }
+}
+
+pub trait GeneticIteration<T: Chromosome> {
+    fn new_iter(old: Vec<T>, new: Vec<T>) -> Self;
+}
+
+pub trait Solution<ChromosomeType: Chromosome, T: GeneticIteration<ChromosomeType>>: Clone {
+    fn new_solution() -> Self;
+    fn add_iteration(&mut self, iteration: T);
+}
+
+pub trait Chromosome: Clone + PartialEq {
+    fn mutate(&self, rng: &mut impl Rng) -> Self;
+    fn crossover(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self);
+    fn health(&self) -> f32;
+    fn set_index(&mut self, index: usize);
+    fn get_index(&self) -> usize;
+    fn set_population(&mut self, population: usize);
+    fn get_population(&mut self) -> usize;
+    /// Copy of the chromosome moving from `island` to another island.
+    fn migrate(&self, _island: usize) -> Self {
+        self.clone()
+    }
+    /// Takes over the island state of `native`, the chromosome the migrant
+    /// replaces on its new island.
+    fn settle(&mut self, _native: &Self) {}
+    /// Copy of the chromosome carried over into the next population.
+    fn survive(&self) -> Self {
+        self.clone()
+    }
+}
+
+impl Chromosome for TSPChromosome {
+    fn mutate(&self, rng: &mut impl Rng) -> Self {
+        let mut mutant = self.clone();
+        let mut mix = self.mutation.lock();
+        let (operator, changed) = mix.mutate(&mut mutant.travel_list, rng);
+        mutant.chromosome_type = TSPChromosomeType::Mutation(
+            TSPParentInfo {
+                chromosome_index: self.index,
+                population_index: self.population,
+            },
+            mix.operators()[operator],
+        );
+        // only the edges around the changed positions are summed again
+        if changed.end() - changed.start() + 1 < self.travel_list.len() {
+            mutant.exact_length = self.exact_length
+                - edges_length(&self.travel_list, &changed, &self.distances)
+                + edges_length(&mutant.travel_list, &changed, &self.distances);
+            mutant.path_length = mutant.exact_length as f32;
+        } else {
+            mutant.update_path_length();
+        }
+        mix.record(operator, mutant.path_length < self.path_length);
+        mutant
+    }
+    fn crossover(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+        let (travel_list_1, travel_list_2) =
+            self.crossover
+                .apply(&self.travel_list, &other.travel_list, rng);
+        let offspring = |travel_list| {
+            let mut offspring = self.clone();
+            offspring.travel_list = travel_list;
+            offspring.chromosome_type = TSPChromosomeType::Crossover(
+                TSPParentInfo {
+                    chromosome_index: self.index,
+                    population_index: self.population,
+                },
+                TSPParentInfo {
+                    chromosome_index: other.index,
+                    population_index: other.population,
+                },
+            );
+            offspring.update_path_length();
+            offspring
+        };
+        (offspring(travel_list_1), offspring(travel_list_2))
+    }
+    fn health(&self) -> f32 {
+        self.path_length
+    }
+    fn set_index(&mut self, index: usize) {
+        self.index = index;
+    }
+    fn get_index(&self) -> usize {
+        self.index
+    }
+    fn set_population(&mut self, population: usize) {
+        self.population = population;
+    }
+    fn get_population(&mut self) -> usize {
+        self.population
+    }
+    fn migrate(&self, island: usize) -> Self {
+        let mut migrant = self.clone();
+        migrant.chromosome_type = TSPChromosomeType::Migration(
+            island,
+            TSPParentInfo {
+                chromosome_index: self.index,
+                population_index: self.population,
+            },
+        );
+        migrant
+    }
+    fn settle(&mut self, native: &Self) {
+        self.mutation = native.mutation.clone();
+    }
+    fn survive(&self) -> Self {
+        let mut survivor = self.clone();
+        survivor.chromosome_type = TSPChromosomeType::Survivor(TSPParentInfo {
+            chromosome_index: self.index,
+            population_index: self.population,
+        });
+        survivor
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EdgeInfo {
+    pub distance: f32,
+}
+
+/// Next two parents of the mating `pool`, different ones if the rest of the pool has them.
+pub fn get_two_indexs(pool: &mut Vec<usize>) -> (usize, usize) {
+    let first = pool.pop().unwrap();
+    if let Some(i) = pool.iter().rposition(|x| *x != first) {
+        let last = pool.len() - 1;
+        pool.swap(i, last);
+    }
+    (first, pool.pop().unwrap())
+}
+
+// `amount` offspring of `old`, the parents are taken from the end of `pool`
+fn breed<ChromosomeType: Chromosome>(
+    old: &[ChromosomeType],
+    pool: &mut Vec<usize>,
+    amount: usize,
+    wa_table: &WalkerTable,
+    rng: &mut impl Rng,
+) -> Vec<ChromosomeType> {
+    let mut new = vec![];
+    while new.len() < amount {
+        let v = wa_table.next_rng(rng);
+        match v {
+            0 if amount - new.len() >= 2 => {
+                let (first, second) = get_two_indexs(pool);
+                let (offspring_1, offspring_2) = old[first].crossover(&old[second], rng);
+                new.push(offspring_1);
+                new.push(offspring_2);
+            }
+            _ => {
+                new.push(old[pool.pop().unwrap()].mutate(rng));
+            }
+        }
+    }
+    new
+}
+
+/// `selection` picks the parents, e.g. [`SelectionMethod`](selection::SelectionMethod),
+/// `replacement` makes the next population of the parents and their
+/// offspring. The healthiest chromosome found so far is never lost.
+///
+/// `memetic` runs on every offspring before selection, e.g. [`TSPChromosome::improve`];
+/// pass `|_| ()` for a plain genetic algorithm.
+///
+/// Stops early when `control` says so (stagnation counts populations without
+/// a healthier offspring), the solution then holds the populations done so far.
+#[allow(clippy::too_many_arguments)]
+pub fn solve<
+    ChromosomeType: Chromosome,
+    IterationType: GeneticIteration<ChromosomeType>,
+    SolutionType: Solution<ChromosomeType, IterationType>,
+>(
+    population_amount: usize,
+    population: Vec<ChromosomeType>,
+    crossover_p: f32,
+    mutation_p: f32,
+    selection: &impl Selection,
+    replacement: Replacement,
+    memetic: impl Fn(&mut ChromosomeType),
+    control: &RunControl,
+    rng: &mut impl Rng,
+) -> SolutionType {
+    let mut solution = SolutionType::new_solution();
+    let wa_table =
+        weighted_rand::builder::WalkerTableBuilder::new(&[crossover_p, mutation_p]).build();
+
+    let mut evolution = Evolution::new(population);
+    for _ in 0..population_amount {
+        if control.should_stop(evolution.since_best) {
+            break;
+        }
+        let (old, new) = evolution.step(&wa_table, selection, replacement, &memetic, rng);
+        solution.add_iteration(IterationType::new_iter(old, new));
+    }
+    solution
+}
+
+// A population of `solve` between two generations
+struct Evolution<ChromosomeType> {
+    population: Vec<ChromosomeType>,
+    population_i: usize,
+    best: Option<(f32, ChromosomeType)>,
+    best_health: f32,
+    // populations without a healthier offspring
+    since_best: usize,
+}
+
+impl<ChromosomeType: Chromosome> Evolution<ChromosomeType> {
+    fn new(population: Vec<ChromosomeType>) -> Self {
+        Self {
+            population,
+            population_i: 0,
+            best: None,
+            best_health: f32::INFINITY,
+            since_best: 0,
+        }
+    }
+    // Indexes the population as the old one of the next generation
+    fn renumber(&mut self) {
+        for (i, chromosome) in self.population.iter_mut().enumerate() {
+            chromosome.set_population(self.population_i);
+            chromosome.set_index(i);
+        }
+    }
+    // Breeds the next population, returns the old one and its offspring
+    fn step(
+        &mut self,
+        wa_table: &WalkerTable,
+        selection: &impl Selection,
+        replacement: Replacement,
+        memetic: &impl Fn(&mut ChromosomeType),
+        rng: &mut impl Rng,
+    ) -> (Vec<ChromosomeType>, Vec<ChromosomeType>) {
+        self.renumber();
+        let old = std::mem::take(&mut self.population);
+        let offspring_amount = replacement.offspring_amount(old.len());
+        let old_health = old.iter().map(|x| x.health()).collect::<Vec<_>>();
+        // every offspring takes at most two parents
+        let mut pool = selection.select(&old_health, offspring_amount * 2, rng);
+        let mut new = breed(&old, &mut pool, offspring_amount, wa_table, rng);
+        for (i, chromosome) in new.iter_mut().enumerate() {
+            memetic(chromosome);
+            chromosome.set_index(i);
+            chromosome.set_population(self.population_i);
+        }
+        let new_health = new.iter().map(|x| x.health()).collect::<Vec<_>>();
+        for (chromosome, health) in old
+            .iter()
+            .zip(&old_health)
+            .chain(new.iter().zip(&new_health))
+        {
+            if self.best.as_ref().is_none_or(|x| *health < x.0) {
+                self.best = Some((*health, chromosome.clone()));
+            }
+        }
+        let health = new_health.iter().copied().fold(f32::INFINITY, f32::min);
+        if health < self.best_health {
+            self.best_health = health;
+            self.since_best = 0;
+        } else {
+            self.since_best += 1;
+        }
+        self.population = replacement.replace(&old, &new);
+        keep_best(&mut self.population, self.best.as_ref());
+        self.population_i += 1;
+        (old, new)
+    }
+}
+
+// Puts `best` in place of the worst chromosome if the population lost it
+fn keep_best<ChromosomeType: Chromosome>(
+    population: &mut [ChromosomeType],
+    best: Option<&(f32, ChromosomeType)>,
+) {
+    let Some((best_health, best)) = best else {
+        return;
+    };
+    let health = population.iter().map(|x| x.health()).collect::<Vec<_>>();
+    if health.iter().all(|x| x > best_health) {
+        let worst = (0..health.len())
+            .max_by(|x, y| health[*x].total_cmp(&health[*y]))
+            .unwrap();
+        population[worst] = best.survive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::mutation::MutationMix;
+    use super::*;
+
+    #[test]
+    fn incremental_length_matches_full_length() {
+        let n = 60;
+        let distances = Arc::new(DistanceMatrix::from_fn(n, |a, b| {
+            ((a * 31 + b * 17) as f32).sqrt() * 1.37 + (a as f32 - b as f32).abs().ln_1p()
+        }));
+        let mix = SharedMutationMix::new(MutationMix::new(&[
+            (PermutationMutation::Swap, 1.0),
+            (PermutationMutation::Insertion, 1.0),
+            (PermutationMutation::Inversion, 1.0),
+            (PermutationMutation::Displacement, 1.0),
+        ]));
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut chromosome =
+            TSPChromosome::new(0, 0, (0..n).map(NodeIndex::new).collect(), distances)
+                .with_mutation(mix);
+        for _ in 0..20_000 {
+            chromosome = chromosome.mutate(&mut rng);
+        }
+        let full = path_length(&chromosome.travel_list, &chromosome.distances);
+        assert!((chromosome.exact_length - full).abs() < full * 1e-12);
+        assert_eq!(chromosome.path_length, full as f32);
+    }
+}
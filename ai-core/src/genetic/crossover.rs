@@ -0,0 +1,368 @@
+//! Crossovers of permutation-encoded chromosomes.
+//!
+//! Every operator takes two parents holding the same genes in different
+//! order and returns two offspring that are permutations of them as well.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use rand::{seq::SliceRandom, Rng};
+
+/// Crossover used by [`TSPChromosome`](super::TSPChromosome).
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize)]
+pub enum PermutationCrossover {
+    /// A segment of the first parent is removed from the second one and
+    /// appended in random order.
+    ShuffledSegment,
+    /// Partially mapped crossover.
+    Pmx,
+    /// Order crossover.
+    #[default]
+    Ox1,
+    /// Cycle crossover.
+    Cx,
+    /// Edge recombination crossover.
+    Erx,
+}
+
+impl PermutationCrossover {
+    pub fn apply<T: Copy + Eq + Hash>(
+        &self,
+        a: &[T],
+        b: &[T],
+        rng: &mut impl Rng,
+    ) -> (Vec<T>, Vec<T>) {
+        match self {
+            Self::ShuffledSegment => shuffled_segment(a, b, rng),
+            Self::Pmx => pmx(a, b, rng),
+            Self::Ox1 => ox1(a, b, rng),
+            Self::Cx => cx(a, b),
+            Self::Erx => (erx(a, b, rng), erx(b, a, rng)),
+        }
+    }
+}
+
+impl std::fmt::Display for PermutationCrossover {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::ShuffledSegment => "Shuffled segment",
+            Self::Pmx => "PMX",
+            Self::Ox1 => "OX1",
+            Self::Cx => "CX",
+            Self::Erx => "ERX",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Bounds `first <= second` of a random segment
+fn cut_points(len: usize, rng: &mut impl Rng) -> (usize, usize) {
+    let first = rng.gen_range(0..len - 1);
+    let second = rng.gen_range(first..len);
+    (first, second)
+}
+
+fn positions<T: Copy + Eq + Hash>(genes: &[T]) -> HashMap<T, usize> {
+    genes.iter().enumerate().map(|(i, x)| (*x, i)).collect()
+}
+
+pub fn shuffled_segment<T: Copy + Eq + Hash>(
+    a: &[T],
+    b: &[T],
+    rng: &mut impl Rng,
+) -> (Vec<T>, Vec<T>) {
+    if a.len() < 2 {
+        return (b.to_vec(), a.to_vec());
+    }
+    let (first, second) = cut_points(a.len(), rng);
+    let genes = shuffled_segment_at(a, b, first, second);
+    let rest = genes.len() - (second - first + 1);
+    let mut offspring = || {
+        let mut genes = genes.clone();
+        genes[rest..].shuffle(rng);
+        genes
+    };
+    (offspring(), offspring())
+}
+
+/// [`shuffled_segment`] with the segment `first..=second` of `a`, before
+/// the shuffle: the genes of `b` outside the segment and then the segment.
+pub fn shuffled_segment_at<T: Copy + Eq + Hash>(
+    a: &[T],
+    b: &[T],
+    first: usize,
+    second: usize,
+) -> Vec<T> {
+    let segment = &a[first..=second];
+    let rest = b.iter().copied().filter(|x| !segment.contains(x));
+    rest.chain(segment.iter().copied()).collect()
+}
+
+/// Partially mapped crossover: the offspring take a segment of one parent,
+/// the other genes of the other parent are moved out of the segment through
+/// the mapping between the two segments.
+pub fn pmx<T: Copy + Eq + Hash>(a: &[T], b: &[T], rng: &mut impl Rng) -> (Vec<T>, Vec<T>) {
+    if a.len() < 2 {
+        return (a.to_vec(), b.to_vec());
+    }
+    let (first, second) = cut_points(a.len(), rng);
+    pmx_at(a, b, first, second)
+}
+
+/// [`pmx`] with the segment `first..=second`.
+pub fn pmx_at<T: Copy + Eq + Hash>(
+    a: &[T],
+    b: &[T],
+    first: usize,
+    second: usize,
+) -> (Vec<T>, Vec<T>) {
+    let offspring = |a: &[T], b: &[T]| {
+        let pos_a = positions(a);
+        let mut genes = b.to_vec();
+        genes[first..=second].copy_from_slice(&a[first..=second]);
+        for k in (0..first).chain(second + 1..a.len()) {
+            let mut x = b[k];
+            while (first..=second).contains(&pos_a[&x]) {
+                x = b[pos_a[&x]];
+            }
+            genes[k] = x;
+        }
+        genes
+    };
+    (offspring(a, b), offspring(b, a))
+}
+
+/// Order crossover: the offspring take a segment of one parent, the other
+/// genes follow in the order of the other parent, starting after the segment.
+pub fn ox1<T: Copy + Eq + Hash>(a: &[T], b: &[T], rng: &mut impl Rng) -> (Vec<T>, Vec<T>) {
+    if a.len() < 2 {
+        return (a.to_vec(), b.to_vec());
+    }
+    let (first, second) = cut_points(a.len(), rng);
+    ox1_at(a, b, first, second)
+}
+
+/// [`ox1`] with the segment `first..=second`.
+pub fn ox1_at<T: Copy + Eq + Hash>(
+    a: &[T],
+    b: &[T],
+    first: usize,
+    second: usize,
+) -> (Vec<T>, Vec<T>) {
+    let n = a.len();
+    let offspring = |a: &[T], b: &[T]| {
+        let segment = &a[first..=second];
+        let mut genes = a.to_vec();
+        let mut rest = (1..=n)
+            .map(|i| b[(second + i) % n])
+            .filter(|x| !segment.contains(x));
+        for i in 1..=n - segment.len() {
+            genes[(second + i) % n] = rest.next().unwrap();
+        }
+        genes
+    };
+    (offspring(a, b), offspring(b, a))
+}
+
+/// Cycle crossover: every gene keeps the position it has in one of the
+/// parents, the cycles of positions are taken from the parents in turn.
+pub fn cx<T: Copy + Eq + Hash>(a: &[T], b: &[T]) -> (Vec<T>, Vec<T>) {
+    let pos_a = positions(a);
+    let mut offspring_1 = b.to_vec();
+    let mut offspring_2 = a.to_vec();
+    let mut done = vec![false; a.len()];
+    let mut from_a = true;
+    for start in 0..a.len() {
+        if done[start] {
+            continue;
+        }
+        let mut i = start;
+        while !done[i] {
+            done[i] = true;
+            if from_a {
+                offspring_1[i] = a[i];
+                offspring_2[i] = b[i];
+            }
+            i = pos_a[&b[i]];
+        }
+        from_a = !from_a;
+    }
+    (offspring_1, offspring_2)
+}
+
+/// Edge recombination crossover: starting from the first gene of `a`, the
+/// next gene is the neighbour (in either parent) with the fewest neighbours
+/// left, or a random gene when there is none.
+pub fn erx<T: Copy + Eq + Hash>(a: &[T], b: &[T], rng: &mut impl Rng) -> Vec<T> {
+    let n = a.len();
+    if n == 0 {
+        return vec![];
+    }
+    let mut neighbours: HashMap<T, Vec<T>> = HashMap::new();
+    for parent in [a, b] {
+        for i in 0..n {
+            let list = neighbours.entry(parent[i]).or_default();
+            for x in [parent[(i + n - 1) % n], parent[(i + 1) % n]] {
+                if x != parent[i] && !list.contains(&x) {
+                    list.push(x);
+                }
+            }
+        }
+    }
+    // genes not in the offspring yet, `remaining_pos` allows O(1) removal
+    let mut remaining = a.to_vec();
+    let mut remaining_pos = positions(a);
+    let mut genes = Vec::with_capacity(n);
+    let mut current = a[0];
+    loop {
+        genes.push(current);
+        let i = remaining_pos.remove(&current).unwrap();
+        remaining.swap_remove(i);
+        if let Some(x) = remaining.get(i) {
+            remaining_pos.insert(*x, i);
+        }
+        if remaining.is_empty() {
+            return genes;
+        }
+        let next = neighbours.remove(&current).unwrap();
+        for x in &next {
+            neighbours.get_mut(x).unwrap().retain(|y| *y != current);
+        }
+        let fewest = next.iter().map(|x| neighbours[x].len()).min();
+        let candidates = next
+            .iter()
+            .filter(|x| Some(neighbours[*x].len()) == fewest)
+            .collect::<Vec<_>>();
+        current = match candidates.choose(rng) {
+            Some(x) => **x,
+            None => *remaining.choose(rng).unwrap(),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    const OPERATORS: [PermutationCrossover; 5] = [
+        PermutationCrossover::ShuffledSegment,
+        PermutationCrossover::Pmx,
+        PermutationCrossover::Ox1,
+        PermutationCrossover::Cx,
+        PermutationCrossover::Erx,
+    ];
+
+    fn is_permutation(genes: &[usize], n: usize) -> bool {
+        let mut sorted = genes.to_vec();
+        sorted.sort();
+        sorted == (0..n).collect::<Vec<_>>()
+    }
+
+    const A: [usize; 9] = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+    const B: [usize; 9] = [9, 3, 7, 8, 2, 6, 5, 1, 4];
+
+    #[test]
+    fn offspring_are_permutations() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for n in [1, 2, 3, 5, 8, 20] {
+            for _ in 0..50 {
+                let mut a = (0..n).collect::<Vec<_>>();
+                let mut b = a.clone();
+                a.shuffle(&mut rng);
+                b.shuffle(&mut rng);
+                for operator in OPERATORS {
+                    let (x, y) = operator.apply(&a, &b, &mut rng);
+                    assert!(is_permutation(&x, n), "{} {:?}", operator, x);
+                    assert!(is_permutation(&y, n), "{} {:?}", operator, y);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn every_segment_gives_permutations() {
+        let a = (0..6).collect::<Vec<_>>();
+        let b = [3, 5, 0, 4, 1, 2];
+        for first in 0..6 {
+            for second in first..6 {
+                let (x, y) = pmx_at(&a, &b, first, second);
+                assert!(is_permutation(&x, 6) && is_permutation(&y, 6));
+                assert_eq!(x[first..=second], a[first..=second]);
+                assert_eq!(y[first..=second], b[first..=second]);
+                let (x, y) = ox1_at(&a, &b, first, second);
+                assert!(is_permutation(&x, 6) && is_permutation(&y, 6));
+                assert_eq!(x[first..=second], a[first..=second]);
+                assert_eq!(y[first..=second], b[first..=second]);
+                let x = shuffled_segment_at(&a, &b, first, second);
+                assert!(is_permutation(&x, 6));
+                assert_eq!(x[6 - (second - first + 1)..], a[first..=second]);
+            }
+        }
+    }
+
+    #[test]
+    fn shuffled_segment_known_answer() {
+        assert_eq!(
+            shuffled_segment_at(&A, &B, 3, 6),
+            [9, 3, 8, 2, 1, 4, 5, 6, 7]
+        );
+        // the random offspring are the core one with the segment shuffled
+        let sorted = |genes: &[usize]| {
+            let mut genes = genes.to_vec();
+            genes.sort();
+            genes
+        };
+        let shuffled = |offspring: &[usize]| {
+            (0..9).any(|first| {
+                (first..9).any(|second| {
+                    let core = shuffled_segment_at(&A, &B, first, second);
+                    let rest = 9 - (second - first + 1);
+                    core[..rest] == offspring[..rest]
+                        && sorted(&core[rest..]) == sorted(&offspring[rest..])
+                })
+            })
+        };
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..20 {
+            let (x, y) = shuffled_segment(&A, &B, &mut rng);
+            assert!(shuffled(&x) && shuffled(&y), "{:?} {:?}", x, y);
+        }
+    }
+
+    #[test]
+    fn pmx_known_answer() {
+        let (x, y) = pmx_at(&A, &B, 3, 6);
+        assert_eq!(x, [9, 3, 2, 4, 5, 6, 7, 1, 8]);
+        assert_eq!(y, [1, 7, 3, 8, 2, 6, 5, 4, 9]);
+        // the whole parent as the segment
+        assert_eq!(pmx_at(&A, &B, 0, 8), (A.to_vec(), B.to_vec()));
+    }
+
+    #[test]
+    fn ox1_known_answer() {
+        let (x, y) = ox1_at(&A, &B, 3, 6);
+        assert_eq!(x, [3, 8, 2, 4, 5, 6, 7, 1, 9]);
+        assert_eq!(y, [3, 4, 7, 8, 2, 6, 5, 9, 1]);
+        assert_eq!(ox1_at(&A, &B, 0, 8), (A.to_vec(), B.to_vec()));
+    }
+
+    #[test]
+    fn cx_known_answer() {
+        let (x, y) = cx(&[1, 2, 3, 4, 5, 6, 7, 8], &[8, 5, 2, 1, 3, 6, 4, 7]);
+        assert_eq!(x, [1, 5, 2, 4, 3, 6, 7, 8]);
+        assert_eq!(y, [8, 2, 3, 1, 5, 6, 4, 7]);
+    }
+
+    #[test]
+    fn erx_known_answer() {
+        // every node has only two neighbours, so the offspring follows the
+        // parents' tour in one direction or the other
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..20 {
+            let x = erx(&[1, 2, 3, 4, 5], &[3, 4, 5, 1, 2], &mut rng);
+            assert!(x == [1, 2, 3, 4, 5] || x == [1, 5, 4, 3, 2], "{:?}", x);
+        }
+    }
+}
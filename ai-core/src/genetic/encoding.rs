@@ -0,0 +1,640 @@
+//! Chromosomes for problems other than the travel salesman problem.
+//!
+//! An [`EncodedChromosome`] holds a vector of genes, its [`Encoding`] (how
+//! genes are generated, crossed and mutated) and a [`Fitness`] closure.
+//! Populations of them run with [`solve`](super::solve) into an
+//! [`EncodedSolution`].
+
+use std::sync::Arc;
+
+use rand::Rng;
+
+use super::{Chromosome, GeneticIteration, Solution};
+
+type FitnessFn<T> = dyn Fn(&[T]) -> f32 + Send + Sync;
+
+/// Health of a chromosome from its genes, lower is better.
+pub struct Fitness<T>(Arc<FitnessFn<T>>);
+
+impl<T> Clone for Fitness<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Fitness<T> {
+    /// `f` is a cost, the lower the better.
+    pub fn minimize(f: impl Fn(&[T]) -> f32 + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+    /// `f` is a score, the higher the better, its health is `-f`.
+    pub fn maximize(f: impl Fn(&[T]) -> f32 + Send + Sync + 'static) -> Self {
+        Self(Arc::new(move |x| -f(x)))
+    }
+    pub fn health(&self, genes: &[T]) -> f32 {
+        (self.0)(genes)
+    }
+}
+
+impl<T> std::fmt::Debug for Fitness<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Fitness")
+    }
+}
+
+impl<T> PartialEq for Fitness<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// How the genes of an [`EncodedChromosome`] are generated, crossed and mutated.
+pub trait Encoding: Clone + PartialEq + std::fmt::Debug {
+    type Gene: Clone + PartialEq + std::fmt::Debug;
+    fn random(&self, rng: &mut impl Rng) -> Vec<Self::Gene>;
+    fn crossover(
+        &self,
+        a: &[Self::Gene],
+        b: &[Self::Gene],
+        rng: &mut impl Rng,
+    ) -> (Vec<Self::Gene>, Vec<Self::Gene>);
+    fn mutate(&self, genes: &mut [Self::Gene], rng: &mut impl Rng);
+}
+
+// Standard normal sample (Box-Muller)
+fn normal(rng: &mut impl Rng) -> f64 {
+    let u = 1.0 - rng.gen::<f64>();
+    (-2.0 * u.ln()).sqrt() * (std::f64::consts::TAU * rng.gen::<f64>()).cos()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BitCrossover {
+    OnePoint,
+    TwoPoint,
+    /// Every bit comes from either parent with equal probability.
+    Uniform,
+}
+
+/// Bit string of `len` bits, every bit flips with probability `mutation_rate`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitString {
+    pub len: usize,
+    pub crossover: BitCrossover,
+    pub mutation_rate: f64,
+}
+
+impl BitString {
+    /// Two-point crossover, one flipped bit per mutation on average.
+    pub fn new(len: usize) -> Self {
+        Self {
+            len,
+            crossover: BitCrossover::TwoPoint,
+            mutation_rate: 1.0 / len.max(1) as f64,
+        }
+    }
+}
+
+impl Encoding for BitString {
+    type Gene = bool;
+    fn random(&self, rng: &mut impl Rng) -> Vec<bool> {
+        (0..self.len).map(|_| rng.gen()).collect()
+    }
+    fn crossover(&self, a: &[bool], b: &[bool], rng: &mut impl Rng) -> (Vec<bool>, Vec<bool>) {
+        let n = a.len();
+        let from_a: Vec<bool> = match self.crossover {
+            BitCrossover::OnePoint => {
+                let cut = rng.gen_range(0..=n);
+                (0..n).map(|i| i < cut).collect()
+            }
+            BitCrossover::TwoPoint => {
+                let (mut first, mut second) = (rng.gen_range(0..=n), rng.gen_range(0..=n));
+                if first > second {
+                    std::mem::swap(&mut first, &mut second);
+                }
+                (0..n).map(|i| i < first || i >= second).collect()
+            }
+            BitCrossover::Uniform => (0..n).map(|_| rng.gen()).collect(),
+        };
+        uniform_mask(a, b, &from_a)
+    }
+    fn mutate(&self, genes: &mut [bool], rng: &mut impl Rng) {
+        for gene in genes.iter_mut() {
+            if rng.gen::<f64>() < self.mutation_rate {
+                *gene = !*gene;
+            }
+        }
+    }
+}
+
+// Offspring taking the genes marked in `from_a` from `a` and the rest from `b`, and the other way round
+fn uniform_mask<T: Clone>(a: &[T], b: &[T], from_a: &[bool]) -> (Vec<T>, Vec<T>) {
+    let pick = |a: &[T], b: &[T]| {
+        (0..a.len())
+            .map(|i| {
+                if from_a[i] {
+                    a[i].clone()
+                } else {
+                    b[i].clone()
+                }
+            })
+            .collect()
+    };
+    (pick(a, b), pick(b, a))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RealMutation {
+    /// Polynomial mutation with distribution index `eta`, larger keeps the
+    /// mutant closer to its parent.
+    Polynomial { eta: f64 },
+    /// Normal noise with `sigma` relative to the range of the gene.
+    Gaussian { sigma: f64 },
+}
+
+/// Vector of reals in `bounds`, crossed with simulated binary crossover
+/// (distribution index `eta_c`), every gene mutates with probability `mutation_rate`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RealVector {
+    pub bounds: Vec<(f64, f64)>,
+    pub eta_c: f64,
+    pub mutation: RealMutation,
+    pub mutation_rate: f64,
+}
+
+impl RealVector {
+    /// `eta_c = 15`, polynomial mutation with `eta = 20`, one mutated gene
+    /// per mutation on average.
+    pub fn new(bounds: Vec<(f64, f64)>) -> Self {
+        Self {
+            mutation_rate: 1.0 / bounds.len().max(1) as f64,
+            bounds,
+            eta_c: 15.0,
+            mutation: RealMutation::Polynomial { eta: 20.0 },
+        }
+    }
+}
+
+// Spread factor of SBX for a child that has to stay `beta` from the bound
+fn sbx_beta_q(u: f64, beta: f64, eta: f64) -> f64 {
+    let alpha = 2.0 - beta.powf(-(eta + 1.0));
+    if u <= 1.0 / alpha {
+        (u * alpha).powf(1.0 / (eta + 1.0))
+    } else {
+        (1.0 / (2.0 - u * alpha)).powf(1.0 / (eta + 1.0))
+    }
+}
+
+impl Encoding for RealVector {
+    type Gene = f64;
+    fn random(&self, rng: &mut impl Rng) -> Vec<f64> {
+        self.bounds
+            .iter()
+            .map(|(low, high)| low + rng.gen::<f64>() * (high - low))
+            .collect()
+    }
+    fn crossover(&self, a: &[f64], b: &[f64], rng: &mut impl Rng) -> (Vec<f64>, Vec<f64>) {
+        let mut offspring_1 = a.to_vec();
+        let mut offspring_2 = b.to_vec();
+        for (i, (low, high)) in self.bounds.iter().enumerate() {
+            if rng.gen::<f64>() >= 0.5 || (a[i] - b[i]).abs() < 1e-14 {
+                continue;
+            }
+            let (y1, y2) = (a[i].min(b[i]), a[i].max(b[i]));
+            let u = rng.gen::<f64>();
+            let beta_q = sbx_beta_q(u, 1.0 + 2.0 * (y1 - low) / (y2 - y1), self.eta_c);
+            let c1 = (0.5 * (y1 + y2 - beta_q * (y2 - y1))).clamp(*low, *high);
+            let beta_q = sbx_beta_q(u, 1.0 + 2.0 * (high - y2) / (y2 - y1), self.eta_c);
+            let c2 = (0.5 * (y1 + y2 + beta_q * (y2 - y1))).clamp(*low, *high);
+            if rng.gen::<bool>() {
+                (offspring_1[i], offspring_2[i]) = (c2, c1);
+            } else {
+                (offspring_1[i], offspring_2[i]) = (c1, c2);
+            }
+        }
+        (offspring_1, offspring_2)
+    }
+    fn mutate(&self, genes: &mut [f64], rng: &mut impl Rng) {
+        for (gene, (low, high)) in genes.iter_mut().zip(&self.bounds) {
+            if rng.gen::<f64>() >= self.mutation_rate || high <= low {
+                continue;
+            }
+            let range = high - low;
+            *gene = match self.mutation {
+                RealMutation::Polynomial { eta } => {
+                    let u = rng.gen::<f64>();
+                    let power = 1.0 / (eta + 1.0);
+                    let delta_q = if u < 0.5 {
+                        let xy = 1.0 - (*gene - low) / range;
+                        (2.0 * u + (1.0 - 2.0 * u) * xy.powf(eta + 1.0)).powf(power) - 1.0
+                    } else {
+                        let xy = 1.0 - (high - *gene) / range;
+                        1.0 - (2.0 * (1.0 - u) + 2.0 * (u - 0.5) * xy.powf(eta + 1.0)).powf(power)
+                    };
+                    *gene + delta_q * range
+                }
+                RealMutation::Gaussian { sigma } => *gene + normal(rng) * sigma * range,
+            }
+            .clamp(*low, *high);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntegerMutation {
+    /// A new random value in the bounds.
+    RandomReset,
+    /// A step of at most `step` up or down.
+    Creep { step: i64 },
+}
+
+/// Vector of integers in inclusive `bounds`, crossed uniformly, every gene
+/// mutates with probability `mutation_rate`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoundedInteger {
+    pub bounds: Vec<(i64, i64)>,
+    pub mutation: IntegerMutation,
+    pub mutation_rate: f64,
+}
+
+impl BoundedInteger {
+    /// Random reset, one mutated gene per mutation on average.
+    pub fn new(bounds: Vec<(i64, i64)>) -> Self {
+        Self {
+            mutation_rate: 1.0 / bounds.len().max(1) as f64,
+            bounds,
+            mutation: IntegerMutation::RandomReset,
+        }
+    }
+}
+
+impl Encoding for BoundedInteger {
+    type Gene = i64;
+    fn random(&self, rng: &mut impl Rng) -> Vec<i64> {
+        self.bounds
+            .iter()
+            .map(|(low, high)| rng.gen_range(*low..=*high))
+            .collect()
+    }
+    fn crossover(&self, a: &[i64], b: &[i64], rng: &mut impl Rng) -> (Vec<i64>, Vec<i64>) {
+        let from_a = (0..a.len()).map(|_| rng.gen()).collect::<Vec<_>>();
+        uniform_mask(a, b, &from_a)
+    }
+    fn mutate(&self, genes: &mut [i64], rng: &mut impl Rng) {
+        for (gene, (low, high)) in genes.iter_mut().zip(&self.bounds) {
+            if rng.gen::<f64>() >= self.mutation_rate {
+                continue;
+            }
+            *gene = match self.mutation {
+                IntegerMutation::RandomReset => rng.gen_range(*low..=*high),
+                IntegerMutation::Creep { step } => {
+                    let step = step.max(1);
+                    (*gene + rng.gen_range(-step..=step)).clamp(*low, *high)
+                }
+            };
+        }
+    }
+}
+
+/// Chromosome of any [`Encoding`], its health is cached.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncodedChromosome<E: Encoding> {
+    pub index: usize,
+    pub population: usize,
+    pub genes: Vec<E::Gene>,
+    health: f32,
+    pub encoding: Arc<E>,
+    pub fitness: Fitness<E::Gene>,
+}
+
+pub type BitChromosome = EncodedChromosome<BitString>;
+pub type RealChromosome = EncodedChromosome<RealVector>;
+pub type IntegerChromosome = EncodedChromosome<BoundedInteger>;
+
+impl<E: Encoding> EncodedChromosome<E> {
+    pub fn new(genes: Vec<E::Gene>, encoding: Arc<E>, fitness: Fitness<E::Gene>) -> Self {
+        Self {
+            index: 0,
+            population: 0,
+            health: fitness.health(&genes),
+            genes,
+            encoding,
+            fitness,
+        }
+    }
+    pub fn random_population(
+        encoding: E,
+        fitness: Fitness<E::Gene>,
+        population_size: usize,
+        rng: &mut impl Rng,
+    ) -> Vec<Self> {
+        let encoding = Arc::new(encoding);
+        (0..population_size)
+            .map(|index| {
+                let genes = encoding.random(rng);
+                let mut chromosome = Self::new(genes, encoding.clone(), fitness.clone());
+                chromosome.index = index;
+                chromosome
+            })
+            .collect()
+    }
+    fn with_genes(&self, genes: Vec<E::Gene>) -> Self {
+        Self::new(genes, self.encoding.clone(), self.fitness.clone())
+    }
+}
+
+impl<E: Encoding> Chromosome for EncodedChromosome<E> {
+    fn mutate(&self, rng: &mut impl Rng) -> Self {
+        let mut genes = self.genes.clone();
+        self.encoding.mutate(&mut genes, rng);
+        self.with_genes(genes)
+    }
+    fn crossover(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+        let (genes_1, genes_2) = self.encoding.crossover(&self.genes, &other.genes, rng);
+        (self.with_genes(genes_1), self.with_genes(genes_2))
+    }
+    fn health(&self) -> f32 {
+        self.health
+    }
+    fn set_index(&mut self, index: usize) {
+        self.index = index;
+    }
+    fn get_index(&self) -> usize {
+        self.index
+    }
+    fn set_population(&mut self, population: usize) {
+        self.population = population;
+    }
+    fn get_population(&mut self) -> usize {
+        self.population
+    }
+}
+
+/// Population of [`solve`](super::solve) for any chromosome.
+#[derive(Debug, Clone)]
+pub struct EncodedIteration<C: Chromosome> {
+    pub old: Vec<C>,
+    pub new: Vec<C>,
+    pub best_chromosome_i: usize,
+}
+
+impl<C: Chromosome> GeneticIteration<C> for EncodedIteration<C> {
+    fn new_iter(old: Vec<C>, new: Vec<C>) -> Self {
+        let best_chromosome_i = old
+            .iter()
+            .enumerate()
+            .min_by(|x, y| x.1.health().total_cmp(&y.1.health()))
+            .map_or(0, |x| x.0);
+        Self {
+            old,
+            new,
+            best_chromosome_i,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EncodedSolution<C: Chromosome> {
+    pub iterations: Vec<EncodedIteration<C>>,
+    pub best_population_i: usize,
+    pub best_chromosome_i: usize,
+}
+
+impl<C: Chromosome> EncodedSolution<C> {
+    /// Healthiest chromosome of all populations, `None` before the first
+    /// non-empty population.
+    pub fn best(&self) -> Option<&C> {
+        self.iterations
+            .get(self.best_population_i)?
+            .old
+            .get(self.best_chromosome_i)
+    }
+}
+
+impl<C: Chromosome> Solution<C, EncodedIteration<C>> for EncodedSolution<C> {
+    fn new_solution() -> Self {
+        Self {
+            iterations: vec![],
+            best_population_i: 0,
+            best_chromosome_i: 0,
+        }
+    }
+    fn add_iteration(&mut self, iteration: EncodedIteration<C>) {
+        let health = iteration
+            .old
+            .get(iteration.best_chromosome_i)
+            .map(|x| x.health());
+        let best = iteration.best_chromosome_i;
+        self.iterations.push(iteration);
+        let Some(health) = health else {
+            return;
+        };
+        if self.best().is_none_or(|x| x.health() > health) {
+            self.best_population_i = self.iterations.len() - 1;
+            self.best_chromosome_i = best;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Value(f32);
+
+    impl Chromosome for Value {
+        fn mutate(&self, _rng: &mut impl rand::Rng) -> Self {
+            self.clone()
+        }
+        fn crossover(&self, other: &Self, _rng: &mut impl rand::Rng) -> (Self, Self) {
+            (self.clone(), other.clone())
+        }
+        fn health(&self) -> f32 {
+            self.0
+        }
+        fn set_index(&mut self, _index: usize) {}
+        fn get_index(&self) -> usize {
+            0
+        }
+        fn set_population(&mut self, _population: usize) {}
+        fn get_population(&mut self) -> usize {
+            0
+        }
+    }
+
+    #[test]
+    fn empty_populations_have_no_best() {
+        let mut solution = EncodedSolution::new_solution();
+        assert_eq!(solution.best(), None);
+        solution.add_iteration(EncodedIteration::new_iter(vec![], vec![]));
+        assert_eq!(solution.best(), None);
+        solution.add_iteration(EncodedIteration::new_iter(
+            vec![Value(3.0), Value(1.0)],
+            vec![],
+        ));
+        solution.add_iteration(EncodedIteration::new_iter(vec![], vec![]));
+        solution.add_iteration(EncodedIteration::new_iter(vec![Value(2.0)], vec![]));
+        assert_eq!(solution.best(), Some(&Value(1.0)));
+        assert_eq!(solution.best_population_i, 1);
+    }
+
+    // Every gene of the offspring comes from the same position of a parent,
+    // the second offspring takes it from the other one
+    fn genes_are_swapped<T: PartialEq + std::fmt::Debug>(a: &[T], b: &[T], x: &[T], y: &[T]) {
+        assert_eq!((x.len(), y.len()), (a.len(), a.len()));
+        for i in 0..a.len() {
+            assert!(
+                (x[i] == a[i] && y[i] == b[i]) || (x[i] == b[i] && y[i] == a[i]),
+                "{:?} {:?} of {:?} {:?}",
+                x,
+                y,
+                a,
+                b
+            );
+        }
+    }
+
+    #[test]
+    fn bit_crossovers() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let (a, b) = (vec![true; 10], vec![false; 10]);
+        for crossover in [
+            BitCrossover::OnePoint,
+            BitCrossover::TwoPoint,
+            BitCrossover::Uniform,
+        ] {
+            let encoding = BitString {
+                crossover,
+                ..BitString::new(10)
+            };
+            for _ in 0..100 {
+                let (x, y) = encoding.crossover(&a, &b, &mut rng);
+                genes_are_swapped(&a, &b, &x, &y);
+                // the first offspring starts with `a` and switches parents at the cut points
+                let changes = x.windows(2).filter(|x| x[0] != x[1]).count();
+                match crossover {
+                    BitCrossover::OnePoint => assert!(changes == 0 || (changes == 1 && x[0])),
+                    BitCrossover::TwoPoint => assert!(changes < 2 || (changes == 2 && x[0])),
+                    BitCrossover::Uniform => (),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bit_mutation_rate() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut genes = vec![true, false, true];
+        let encoding = |mutation_rate| BitString {
+            mutation_rate,
+            ..BitString::new(3)
+        };
+        encoding(0.0).mutate(&mut genes, &mut rng);
+        assert_eq!(genes, [true, false, true]);
+        encoding(1.0).mutate(&mut genes, &mut rng);
+        assert_eq!(genes, [false, true, false]);
+    }
+
+    #[test]
+    fn sbx_offspring_stay_in_bounds() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let encoding = RealVector {
+            eta_c: 2.0,
+            ..RealVector::new(vec![(-1.0, 1.0), (0.0, 10.0), (5.0, 5.5)])
+        };
+        for _ in 0..1000 {
+            let (a, b) = (encoding.random(&mut rng), encoding.random(&mut rng));
+            let (x, y) = encoding.crossover(&a, &b, &mut rng);
+            for (i, (low, high)) in encoding.bounds.iter().enumerate() {
+                assert!((*low..=*high).contains(&x[i]), "{} of {:?}", x[i], a);
+                assert!((*low..=*high).contains(&y[i]), "{} of {:?}", y[i], b);
+            }
+        }
+    }
+
+    #[test]
+    fn sbx_offspring_are_symmetric() {
+        // Parents as far from the bounds as each other spread the same way
+        // to both sides of their mean
+        let mut rng = StdRng::seed_from_u64(0);
+        for eta_c in [0.5, 2.0, 15.0] {
+            let encoding = RealVector {
+                eta_c,
+                ..RealVector::new(vec![(0.0, 10.0), (-4.0, 4.0)])
+            };
+            let (a, b) = ([3.0, -1.0], [7.0, 1.0]);
+            for _ in 0..200 {
+                let (x, y) = encoding.crossover(&a, &b, &mut rng);
+                for i in 0..2 {
+                    assert!(
+                        (x[i] + y[i] - (a[i] + b[i])).abs() < 1e-9,
+                        "{:?} {:?}",
+                        x,
+                        y
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn real_mutants_stay_in_bounds() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let bounds = vec![(-1.0, 1.0), (0.0, 10.0), (2.0, 2.0)];
+        for mutation in [
+            RealMutation::Polynomial { eta: 0.5 },
+            RealMutation::Polynomial { eta: 20.0 },
+            RealMutation::Gaussian { sigma: 0.1 },
+            RealMutation::Gaussian { sigma: 10.0 },
+        ] {
+            let encoding = RealVector {
+                mutation,
+                mutation_rate: 1.0,
+                ..RealVector::new(bounds.clone())
+            };
+            let mut changed = 0;
+            for _ in 0..1000 {
+                let parent = encoding.random(&mut rng);
+                let mut genes = parent.clone();
+                encoding.mutate(&mut genes, &mut rng);
+                for (i, (low, high)) in bounds.iter().enumerate() {
+                    assert!((*low..=*high).contains(&genes[i]), "{:?}", mutation);
+                }
+                changed += (genes[..2] != parent[..2]) as usize;
+            }
+            assert!(changed > 900, "{:?}", mutation);
+        }
+    }
+
+    #[test]
+    fn integer_operators_stay_in_bounds() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let bounds = vec![(-3, 3), (0, 0), (10, 20)];
+        for mutation in [
+            IntegerMutation::RandomReset,
+            IntegerMutation::Creep { step: 2 },
+            IntegerMutation::Creep { step: 0 },
+        ] {
+            let encoding = BoundedInteger {
+                mutation,
+                mutation_rate: 1.0,
+                ..BoundedInteger::new(bounds.clone())
+            };
+            for _ in 0..1000 {
+                let (a, b) = (encoding.random(&mut rng), encoding.random(&mut rng));
+                let (x, y) = encoding.crossover(&a, &b, &mut rng);
+                genes_are_swapped(&a, &b, &x, &y);
+                let mut genes = a.clone();
+                encoding.mutate(&mut genes, &mut rng);
+                for (i, (low, high)) in bounds.iter().enumerate() {
+                    assert!((*low..=*high).contains(&genes[i]), "{:?}", mutation);
+                    if let IntegerMutation::Creep { step } = mutation {
+                        assert!((genes[i] - a[i]).abs() <= step.max(1), "{:?}", mutation);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,340 @@
+//! Genealogy of a genetic run: every chromosome of every population with the
+//! parents and the operator it came from.
+
+use std::collections::HashSet;
+
+use super::crossover::PermutationCrossover;
+use super::mutation::PermutationMutation;
+use super::{TSPChromosomeType, TSPParentInfo, TSPSolution};
+
+/// How a chromosome came to be.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub enum Origin {
+    Initial,
+    Crossover(PermutationCrossover),
+    Mutation(PermutationMutation),
+    Migration,
+    Survivor,
+}
+
+impl std::fmt::Display for Origin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Initial => write!(f, "Initial"),
+            Self::Crossover(x) => write!(f, "Crossover {}", x),
+            Self::Mutation(x) => write!(f, "Mutation {}", x),
+            Self::Migration => write!(f, "Migration"),
+            Self::Survivor => write!(f, "Survivor"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct GenealogyNode {
+    pub island: usize,
+    pub population: usize,
+    pub chromosome: usize,
+    pub path_length: f32,
+    pub origin: Origin,
+    /// Positions of the parents in [`Genealogy::nodes`].
+    pub parents: Vec<usize>,
+}
+
+impl GenealogyNode {
+    // Node id in the DOT graph
+    fn id(&self) -> String {
+        format!("I{}P{}C{}", self.island, self.population, self.chromosome)
+    }
+}
+
+/// Graph of the chromosomes of the old populations of every island, the
+/// ones that [`TSPParentInfo`] points to.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Genealogy {
+    pub nodes: Vec<GenealogyNode>,
+    // position of the first node and the size of every population of every island
+    #[serde(skip)]
+    populations: Vec<Vec<(usize, usize)>>,
+}
+
+impl Genealogy {
+    /// Genealogy of the solutions of the islands of a run, pass a single
+    /// solution for a run without islands.
+    pub fn new(islands: &[TSPSolution]) -> Self {
+        let mut genealogy = Self {
+            nodes: vec![],
+            populations: vec![],
+        };
+        for (island, solution) in islands.iter().enumerate() {
+            let mut populations = vec![];
+            for (population, iteration) in solution.iterations.iter().enumerate() {
+                populations.push((genealogy.nodes.len(), iteration.old.len()));
+                for (chromosome, x) in iteration.old.iter().enumerate() {
+                    let origin = match &x.chromosome_type {
+                        TSPChromosomeType::Crossover(..) => Origin::Crossover(x.crossover),
+                        TSPChromosomeType::Mutation(_, operator) => Origin::Mutation(*operator),
+                        TSPChromosomeType::Migration(..) => Origin::Migration,
+                        TSPChromosomeType::Survivor(_) => Origin::Survivor,
+                        TSPChromosomeType::NoHistory => Origin::Initial,
+                    };
+                    genealogy.nodes.push(GenealogyNode {
+                        island,
+                        population,
+                        chromosome,
+                        path_length: x.path_length,
+                        origin,
+                        parents: vec![],
+                    });
+                }
+            }
+            genealogy.populations.push(populations);
+        }
+        let mut i = 0;
+        for (island, solution) in islands.iter().enumerate() {
+            for iteration in &solution.iterations {
+                for x in &iteration.old {
+                    let parents = match &x.chromosome_type {
+                        TSPChromosomeType::Crossover(a, b) => {
+                            vec![(island, a.clone()), (island, b.clone())]
+                        }
+                        TSPChromosomeType::Mutation(a, _) => vec![(island, a.clone())],
+                        TSPChromosomeType::Migration(from, a) => vec![(*from, a.clone())],
+                        TSPChromosomeType::Survivor(a) => vec![(island, a.clone())],
+                        TSPChromosomeType::NoHistory => vec![],
+                    };
+                    // the source island of a migrant can stop before its population
+                    genealogy.nodes[i].parents = parents
+                        .into_iter()
+                        .filter_map(|(island, x)| genealogy.position(island, &x))
+                        .collect();
+                    i += 1;
+                }
+            }
+        }
+        genealogy
+    }
+    /// Position in `nodes` of a chromosome of an island.
+    pub fn position(&self, island: usize, chromosome: &TSPParentInfo) -> Option<usize> {
+        let (first, size) = *self
+            .populations
+            .get(island)?
+            .get(chromosome.population_index)?;
+        (chromosome.chromosome_index < size).then_some(first + chromosome.chromosome_index)
+    }
+    /// The shortest chromosome, the first one found if there are several.
+    pub fn best(&self) -> Option<usize> {
+        (0..self.nodes.len()).min_by(|x, y| {
+            self.nodes[*x]
+                .path_length
+                .total_cmp(&self.nodes[*y].path_length)
+        })
+    }
+    /// Every ancestor of `node` once, the nearest first.
+    pub fn ancestry(&self, node: usize) -> Vec<usize> {
+        let mut seen = HashSet::from([node]);
+        let mut ancestry = vec![node];
+        let mut i = 0;
+        while i < ancestry.len() {
+            for parent in &self.nodes[ancestry[i]].parents {
+                if seen.insert(*parent) {
+                    ancestry.push(*parent);
+                }
+            }
+            i += 1;
+        }
+        ancestry.remove(0);
+        ancestry
+    }
+    /// How many of `nodes` came from every origin, the most frequent first.
+    pub fn origins(&self, nodes: &[usize]) -> Vec<(Origin, usize)> {
+        let mut counts: Vec<(Origin, usize)> = vec![];
+        for node in nodes {
+            let origin = self.nodes[*node].origin;
+            match counts.iter_mut().find(|x| x.0 == origin) {
+                Some(x) => x.1 += 1,
+                None => counts.push((origin, 1)),
+            }
+        }
+        counts.sort_by_key(|x| std::cmp::Reverse(x.1));
+        counts
+    }
+    /// Graphviz DOT graph with an edge from every parent to its child,
+    /// `highlight` nodes are filled.
+    pub fn to_dot(&self, highlight: &[usize]) -> String {
+        let highlight = highlight.iter().collect::<HashSet<_>>();
+        let mut dot = String::from("digraph genealogy {\n    node [shape=box];\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            dot += &format!(
+                "    {} [label=\"I#{} P#{} C#{}\\n{}\\n{}\"{}];\n",
+                node.id(),
+                node.island,
+                node.population,
+                node.chromosome,
+                node.path_length,
+                node.origin,
+                if highlight.contains(&i) {
+                    ", style=filled, fillcolor=gold"
+                } else {
+                    ""
+                }
+            );
+        }
+        for node in &self.nodes {
+            for parent in &node.parents {
+                dot += &format!("    {} -> {};\n", self.nodes[*parent].id(), node.id());
+            }
+        }
+        dot + "}\n"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use petgraph::graph::NodeIndex;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::genetic::{
+        replacement::Replacement, selection::SelectionMethod, GeneticIteration, TSPChromosome,
+        TSPIteration,
+    };
+    use crate::run_control::RunControl;
+    use crate::tsp::DistanceMatrix;
+
+    fn parent(population_index: usize, chromosome_index: usize) -> TSPParentInfo {
+        TSPParentInfo {
+            chromosome_index,
+            population_index,
+        }
+    }
+
+    fn population(
+        population: usize,
+        types: Vec<TSPChromosomeType>,
+        distances: &Arc<DistanceMatrix>,
+    ) -> Vec<TSPChromosome> {
+        types
+            .into_iter()
+            .enumerate()
+            .map(|(i, chromosome_type)| {
+                let travel_list = (0..4).map(|x| NodeIndex::new((x + i) % 4)).collect();
+                let mut x = TSPChromosome::new(i, population, travel_list, distances.clone());
+                x.chromosome_type = chromosome_type;
+                x
+            })
+            .collect()
+    }
+
+    #[test]
+    fn two_generations() {
+        let distances = Arc::new(DistanceMatrix::from_fn(4, |a, b| (a + b) as f32));
+        let first = population(0, vec![TSPChromosomeType::NoHistory; 4], &distances);
+        let second = population(
+            1,
+            vec![
+                TSPChromosomeType::Survivor(parent(0, 2)),
+                TSPChromosomeType::Crossover(parent(0, 0), parent(0, 1)),
+                TSPChromosomeType::Crossover(parent(0, 0), parent(0, 1)),
+                TSPChromosomeType::Mutation(parent(0, 3), PermutationMutation::Swap),
+            ],
+            &distances,
+        );
+        let solution = TSPSolution {
+            iterations: vec![
+                TSPIteration::new_iter(first, second.clone()),
+                TSPIteration::new_iter(second.clone(), second),
+            ],
+            best_population_i: 0,
+            best_chromosome_i: 0,
+        };
+        let genealogy = Genealogy::new(&[solution]);
+
+        assert_eq!(genealogy.nodes.len(), 8);
+        let parents = genealogy
+            .nodes
+            .iter()
+            .map(|x| x.parents.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parents,
+            [
+                vec![],
+                vec![],
+                vec![],
+                vec![],
+                vec![2],
+                vec![0, 1],
+                vec![0, 1],
+                vec![3]
+            ]
+        );
+        assert_eq!(genealogy.nodes[4].origin, Origin::Survivor);
+        assert_eq!(genealogy.ancestry(4), [2]);
+
+        let dot = genealogy.to_dot(&[4]);
+        assert!(dot.starts_with("digraph genealogy {\n") && dot.ends_with("}\n"));
+        assert_eq!(dot.matches(" -> ").count(), 6);
+        assert_eq!(dot.matches("[label=").count(), 8);
+        assert_eq!(dot.matches("fillcolor=gold").count(), 1);
+        assert!(dot.contains("    I0P0C2 -> I0P1C0;\n"));
+        assert!(dot.contains("\\nSurvivor\""));
+
+        let json = serde_json::to_value(&genealogy).unwrap();
+        let nodes = json["nodes"].as_array().unwrap();
+        assert_eq!(json.as_object().unwrap().len(), 1);
+        assert_eq!(nodes.len(), 8);
+        assert_eq!(nodes[4]["origin"], "Survivor");
+        assert_eq!(nodes[5]["parents"], serde_json::json!([0, 1]));
+        assert_eq!(nodes[7]["population"], 1);
+        assert_eq!(nodes[7]["chromosome"], 3);
+    }
+
+    #[test]
+    fn carried_over_chromosomes_are_survivors() {
+        let n = 8;
+        let distances = Arc::new(DistanceMatrix::from_fn(n, |a, b| (a * b % 7 + 1) as f32));
+        let mut rng = StdRng::seed_from_u64(0);
+        let population = TSPChromosome::generate_random_population(
+            (0..n).map(NodeIndex::new).collect(),
+            6,
+            distances,
+            &mut rng,
+        );
+        let solution: TSPSolution = crate::genetic::solve(
+            4,
+            population,
+            0.5,
+            0.5,
+            &SelectionMethod::default(),
+            Replacement::MuPlusLambda { offspring: 3 },
+            |_| (),
+            &RunControl::new(),
+            &mut rng,
+        );
+        let genealogy = Genealogy::new(std::slice::from_ref(&solution));
+        let mut survivors = 0;
+        for (population, iteration) in solution.iterations.iter().enumerate().skip(1) {
+            let previous = &solution.iterations[population - 1].old;
+            for x in &iteration.old {
+                // an offspring is never in the old population, a survivor is
+                // the chromosome it points to
+                match &x.chromosome_type {
+                    TSPChromosomeType::Survivor(a) => {
+                        assert_eq!(a.population_index, population - 1);
+                        assert_eq!(previous[a.chromosome_index].travel_list, x.travel_list);
+                        survivors += 1;
+                    }
+                    TSPChromosomeType::Crossover(a, _) | TSPChromosomeType::Mutation(a, _) => {
+                        assert_eq!(a.population_index, population - 1);
+                    }
+                    _ => panic!("{:?} in population {}", x.chromosome_type, population),
+                }
+            }
+        }
+        assert!(survivors > 0);
+        let origins = genealogy.origins(&(0..genealogy.nodes.len()).collect::<Vec<_>>());
+        assert!(origins.iter().any(|x| x.0 == Origin::Survivor));
+    }
+}
@@ -0,0 +1,303 @@
+//! Island model: several populations evolve on their own threads and
+//! exchange their best chromosomes every few populations.
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use weighted_rand::builder::NewBuilder;
+
+use super::replacement::Replacement;
+use super::selection::Selection;
+use super::{Chromosome, Evolution, GeneticIteration, Solution};
+use crate::run_control::RunControl;
+
+/// Islands that send migrants to an island.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Topology {
+    /// Only the previous island.
+    #[default]
+    Ring,
+    /// Every other island, the best of their migrants are taken.
+    FullyConnected,
+}
+
+impl Topology {
+    fn sources(&self, island: usize, islands: usize) -> Vec<usize> {
+        match self {
+            Self::Ring => vec![(island + islands - 1) % islands],
+            Self::FullyConnected => (0..islands).filter(|x| *x != island).collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for Topology {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Ring => "Ring",
+            Self::FullyConnected => "Fully connected",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Every `interval` populations each island replaces its `migrants` worst
+/// chromosomes with the best ones of the islands given by `topology`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Migration {
+    pub topology: Topology,
+    pub interval: usize,
+    pub migrants: usize,
+}
+
+impl Default for Migration {
+    fn default() -> Self {
+        Self {
+            topology: Topology::Ring,
+            interval: 10,
+            migrants: 2,
+        }
+    }
+}
+
+/// Result of [`solve`]: the solution of every island, in the order of the
+/// populations passed to it.
+#[derive(Debug, Clone)]
+pub struct IslandSolution<SolutionType> {
+    pub islands: Vec<SolutionType>,
+}
+
+struct Island<ChromosomeType, SolutionType> {
+    evolution: Evolution<ChromosomeType>,
+    solution: SolutionType,
+    rng: StdRng,
+}
+
+// Indexes of the chromosomes from the healthiest one
+fn ranked<ChromosomeType: Chromosome>(chromosomes: &[ChromosomeType]) -> Vec<usize> {
+    let health = chromosomes.iter().map(|x| x.health()).collect::<Vec<_>>();
+    let mut ranked = (0..chromosomes.len()).collect::<Vec<_>>();
+    ranked.sort_by(|x, y| health[*x].total_cmp(&health[*y]));
+    ranked
+}
+
+fn migrate<ChromosomeType: Chromosome, SolutionType>(
+    islands: &mut [Island<ChromosomeType, SolutionType>],
+    migration: Migration,
+) {
+    if islands.len() < 2 {
+        return;
+    }
+    for island in islands.iter_mut() {
+        island.evolution.renumber();
+    }
+    let emigrants = islands
+        .iter()
+        .enumerate()
+        .map(|(i, island)| {
+            let population = &island.evolution.population;
+            ranked(population)
+                .into_iter()
+                .take(migration.migrants.min(population.len().saturating_sub(1)))
+                .map(|x| population[x].migrate(i))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    for (i, island) in islands.iter_mut().enumerate() {
+        let incoming = migration
+            .topology
+            .sources(i, emigrants.len())
+            .into_iter()
+            .flat_map(|x| emigrants[x].iter().cloned())
+            .collect::<Vec<_>>();
+        let population = &mut island.evolution.population;
+        let amount = migration.migrants.min(population.len().saturating_sub(1));
+        let worst = ranked(population).into_iter().rev();
+        for (slot, x) in worst.zip(ranked(&incoming).into_iter().take(amount)) {
+            let mut migrant = incoming[x].clone();
+            migrant.settle(&population[slot]);
+            migrant.set_index(slot);
+            population[slot] = migrant;
+        }
+    }
+}
+
+/// [`solve`](super::solve) on every population of `islands`, each on its own
+/// thread, with a [`Migration`] between them. Returns the solution of every
+/// island; migrants are told where they come from by
+/// [`Chromosome::migrate`] and take over the island state of the chromosome
+/// they replace by [`Chromosome::settle`], so islands share nothing and a
+/// seeded run is reproducible.
+///
+/// Stagnation of `control` counts populations without a healthier offspring
+/// on any island and is checked between migrations.
+#[allow(clippy::too_many_arguments)]
+pub fn solve<
+    ChromosomeType: Chromosome + Send,
+    IterationType: GeneticIteration<ChromosomeType>,
+    SolutionType: Solution<ChromosomeType, IterationType> + Send,
+>(
+    population_amount: usize,
+    islands: Vec<Vec<ChromosomeType>>,
+    crossover_p: f32,
+    mutation_p: f32,
+    selection: &(impl Selection + Sync),
+    replacement: Replacement,
+    migration: Migration,
+    memetic: impl Fn(&mut ChromosomeType) + Sync,
+    control: &RunControl,
+    rng: &mut impl Rng,
+) -> IslandSolution<SolutionType> {
+    let wa_table =
+        weighted_rand::builder::WalkerTableBuilder::new(&[crossover_p, mutation_p]).build();
+    let mut islands = islands
+        .into_iter()
+        .map(|population| Island {
+            evolution: Evolution::new(population),
+            solution: SolutionType::new_solution(),
+            rng: StdRng::seed_from_u64(rng.gen()),
+        })
+        .collect::<Vec<_>>();
+
+    let interval = migration.interval.max(1);
+    let mut done = 0;
+    while done < population_amount {
+        let since_best = islands
+            .iter()
+            .map(|x| x.evolution.since_best)
+            .min()
+            .unwrap_or(0);
+        if control.should_stop(since_best) {
+            break;
+        }
+        let populations = interval.min(population_amount - done);
+        let (wa_table, memetic) = (&wa_table, &memetic);
+        let finished = std::thread::scope(|scope| {
+            let handles = islands
+                .iter_mut()
+                .map(|island| {
+                    scope.spawn(move || {
+                        for _ in 0..populations {
+                            if control.should_stop(since_best) {
+                                return false;
+                            }
+                            let (old, new) = island.evolution.step(
+                                wa_table,
+                                selection,
+                                replacement,
+                                memetic,
+                                &mut island.rng,
+                            );
+                            island
+                                .solution
+                                .add_iteration(IterationType::new_iter(old, new));
+                        }
+                        true
+                    })
+                })
+                .collect::<Vec<_>>();
+            let finished = handles
+                .into_iter()
+                .map(|x| x.join().unwrap())
+                .collect::<Vec<_>>();
+            finished.into_iter().all(|x| x)
+        });
+        if !finished {
+            break;
+        }
+        done += populations;
+        if done < population_amount {
+            migrate(&mut islands, migration);
+        }
+    }
+    IslandSolution {
+        islands: islands.into_iter().map(|x| x.solution).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use petgraph::stable_graph::NodeIndex;
+
+    use super::*;
+    use crate::genetic::mutation::{
+        Adaptation, MutationMix, PermutationMutation, SharedMutationMix,
+    };
+    use crate::genetic::selection::SelectionMethod;
+    use crate::genetic::{TSPChromosome, TSPIteration, TSPSolution};
+    use crate::tsp::DistanceMatrix;
+
+    type SuccessRates = Vec<(PermutationMutation, usize, usize)>;
+
+    // Best lengths of every population of every island and the success
+    // counts of every island's mix
+    fn run(seed: u64) -> (Vec<Vec<f32>>, Vec<SuccessRates>) {
+        let distances = Arc::new(DistanceMatrix::from_fn(12, |a, b| {
+            ((a * 7 + b * 3) % 11 + (b * 7 + a * 3) % 11) as f32
+        }));
+        let nodes = (0..12).map(NodeIndex::new).collect::<Vec<_>>();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mixes = (0..4)
+            .map(|_| {
+                SharedMutationMix::new(
+                    MutationMix::new(&[
+                        (PermutationMutation::Swap, 1.0),
+                        (PermutationMutation::Inversion, 1.0),
+                    ])
+                    .with_adaptation(Adaptation {
+                        learning_rate: 0.1,
+                        min_probability: 0.05,
+                    }),
+                )
+            })
+            .collect::<Vec<_>>();
+        let islands = mixes
+            .iter()
+            .map(|mix| {
+                TSPChromosome::generate_random_population(
+                    nodes.clone(),
+                    10,
+                    distances.clone(),
+                    &mut rng,
+                )
+                .into_iter()
+                .map(|x| x.with_mutation(mix.clone()))
+                .collect()
+            })
+            .collect();
+        let solution: IslandSolution<TSPSolution> = solve::<_, TSPIteration, _>(
+            30,
+            islands,
+            0.5,
+            0.5,
+            &SelectionMethod::default(),
+            Replacement::default(),
+            Migration {
+                topology: Topology::FullyConnected,
+                interval: 3,
+                migrants: 2,
+            },
+            |_| (),
+            &RunControl::new(),
+            &mut rng,
+        );
+        let lengths = solution
+            .islands
+            .iter()
+            .map(|x| {
+                x.iterations
+                    .iter()
+                    .map(|x| x.old[x.best_chromosome_i].path_length)
+                    .collect()
+            })
+            .collect();
+        let rates = mixes.iter().map(|x| x.lock().success_rates()).collect();
+        (lengths, rates)
+    }
+
+    #[test]
+    fn seeded_runs_are_reproducible() {
+        for seed in 0..5 {
+            assert_eq!(run(seed), run(seed));
+        }
+    }
+}
@@ -0,0 +1,324 @@
+//! Mutations of permutation-encoded chromosomes.
+
+use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use rand::{seq::SliceRandom, Rng};
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub enum PermutationMutation {
+    /// Exchanges two genes.
+    Swap,
+    /// Moves one gene to another position.
+    Insertion,
+    /// Reverses a segment, the 2-opt move of a tour.
+    Inversion,
+    /// Shuffles a segment.
+    Scramble,
+    /// Moves a segment to another position.
+    Displacement,
+}
+
+impl PermutationMutation {
+    pub const ALL: [Self; 5] = [
+        Self::Swap,
+        Self::Insertion,
+        Self::Inversion,
+        Self::Scramble,
+        Self::Displacement,
+    ];
+
+    /// Mutates `genes` and returns the positions that may have changed.
+    pub fn apply<T>(&self, genes: &mut [T], rng: &mut impl Rng) -> RangeInclusive<usize> {
+        let n = genes.len();
+        if n < 2 {
+            return 0..=0;
+        }
+        match self {
+            Self::Swap | Self::Insertion => {
+                let first = rng.gen_range(0..n);
+                let second = (first + rng.gen_range(1..n)) % n;
+                if *self == Self::Swap {
+                    swap_at(genes, first, second)
+                } else {
+                    insertion_at(genes, first, second)
+                }
+            }
+            Self::Inversion | Self::Scramble => {
+                let first = rng.gen_range(0..n - 1);
+                let second = rng.gen_range(first..n);
+                if *self == Self::Inversion {
+                    inversion_at(genes, first, second)
+                } else {
+                    genes[first..=second].shuffle(rng);
+                    first..=second
+                }
+            }
+            Self::Displacement => {
+                let first = rng.gen_range(0..n);
+                let len = rng.gen_range(1..=n - first);
+                let to = rng.gen_range(0..=n - len);
+                displacement_at(genes, first, len, to)
+            }
+        }
+    }
+}
+
+/// Swap of the genes at `first` and `second`, returns the positions that may
+/// have changed like [`PermutationMutation::apply`].
+pub fn swap_at<T>(genes: &mut [T], first: usize, second: usize) -> RangeInclusive<usize> {
+    genes.swap(first, second);
+    first.min(second)..=first.max(second)
+}
+
+/// Insertion of the gene at `from` at position `to`.
+pub fn insertion_at<T>(genes: &mut [T], from: usize, to: usize) -> RangeInclusive<usize> {
+    if from < to {
+        genes[from..=to].rotate_left(1);
+    } else {
+        genes[to..=from].rotate_right(1);
+    }
+    from.min(to)..=from.max(to)
+}
+
+/// Inversion of the segment `first..=second`.
+pub fn inversion_at<T>(genes: &mut [T], first: usize, second: usize) -> RangeInclusive<usize> {
+    genes[first..=second].reverse();
+    first..=second
+}
+
+/// Displacement of the `len` genes from `first`, `to` is the start of the
+/// segment once the other genes are around it.
+pub fn displacement_at<T>(
+    genes: &mut [T],
+    first: usize,
+    len: usize,
+    to: usize,
+) -> RangeInclusive<usize> {
+    if to < first {
+        genes[to..first + len].rotate_right(len);
+        to..=first + len - 1
+    } else {
+        genes[first..to + len].rotate_left(len);
+        first..=to + len - 1
+    }
+}
+
+impl std::fmt::Display for PermutationMutation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Swap => "Swap",
+            Self::Insertion => "Insertion",
+            Self::Inversion => "Inversion",
+            Self::Scramble => "Scramble",
+            Self::Displacement => "Displacement",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Adaptive weights: after every use an operator's weight moves by
+/// `learning_rate` towards 1 if the mutant beat its parent and towards 0
+/// otherwise, no operator drops below `min_probability`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Adaptation {
+    pub learning_rate: f32,
+    pub min_probability: f32,
+}
+
+/// Mutations used with their weights, plus how often each one was used and
+/// produced a mutant better than its parent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MutationMix {
+    operators: Vec<PermutationMutation>,
+    weights: Vec<f32>,
+    adaptation: Option<Adaptation>,
+    uses: Vec<usize>,
+    successes: Vec<usize>,
+}
+
+impl Default for MutationMix {
+    fn default() -> Self {
+        Self::new(&[(PermutationMutation::Scramble, 1.0)])
+    }
+}
+
+impl MutationMix {
+    /// `operators` with their weights, there has to be at least one.
+    pub fn new(operators: &[(PermutationMutation, f32)]) -> Self {
+        Self {
+            operators: operators.iter().map(|x| x.0).collect(),
+            weights: operators.iter().map(|x| x.1.max(0.0)).collect(),
+            adaptation: None,
+            uses: vec![0; operators.len()],
+            successes: vec![0; operators.len()],
+        }
+    }
+    pub fn with_adaptation(mut self, adaptation: Adaptation) -> Self {
+        self.adaptation = Some(adaptation);
+        self
+    }
+    pub fn operators(&self) -> &[PermutationMutation] {
+        &self.operators
+    }
+    pub fn probabilities(&self) -> Vec<f32> {
+        let n = self.operators.len() as f32;
+        let sum = self.weights.iter().sum::<f32>();
+        let min = self
+            .adaptation
+            .map_or(0.0, |x| x.min_probability.clamp(0.0, 1.0 / n));
+        self.weights
+            .iter()
+            .map(|x| {
+                let share = if sum > 0.0 { x / sum } else { 1.0 / n };
+                min + (1.0 - n * min) * share
+            })
+            .collect()
+    }
+    /// How often each operator was used and how often its mutant was better.
+    pub fn success_rates(&self) -> Vec<(PermutationMutation, usize, usize)> {
+        (0..self.operators.len())
+            .map(|i| (self.operators[i], self.uses[i], self.successes[i]))
+            .collect()
+    }
+    fn choose(&self, rng: &mut impl Rng) -> usize {
+        let probabilities = self.probabilities();
+        let mut x = rng.gen::<f32>();
+        for (i, p) in probabilities.iter().enumerate() {
+            if x < *p {
+                return i;
+            }
+            x -= p;
+        }
+        probabilities.len() - 1
+    }
+    /// Counts a use of operator `i`, `improved` tells whether the mutant beat
+    /// its parent.
+    pub fn record(&mut self, i: usize, improved: bool) {
+        self.uses[i] += 1;
+        if improved {
+            self.successes[i] += 1;
+        }
+        if let Some(adaptation) = self.adaptation {
+            let reward = if improved { 1.0 } else { 0.0 };
+            self.weights[i] += adaptation.learning_rate * (reward - self.weights[i]);
+        }
+    }
+    /// Mutates `genes` with one of the operators, returns its index for
+    /// [`record`](Self::record) and the positions that may have changed.
+    pub fn mutate<T>(&self, genes: &mut [T], rng: &mut impl Rng) -> (usize, RangeInclusive<usize>) {
+        let i = self.choose(rng);
+        let changed = self.operators[i].apply(genes, rng);
+        (i, changed)
+    }
+}
+
+/// A [`MutationMix`] shared by the chromosomes of a run, so the adaptive
+/// weights and the counts see every mutation.
+#[derive(Debug, Clone, Default)]
+pub struct SharedMutationMix(Arc<Mutex<MutationMix>>);
+
+impl SharedMutationMix {
+    pub fn new(mix: MutationMix) -> Self {
+        Self(Arc::new(Mutex::new(mix)))
+    }
+    pub fn lock(&self) -> MutexGuard<'_, MutationMix> {
+        self.0.lock().unwrap()
+    }
+}
+
+impl PartialEq for SharedMutationMix {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn mutated(
+        mut mutation: impl FnMut(&mut [usize]) -> RangeInclusive<usize>,
+    ) -> (Vec<usize>, RangeInclusive<usize>) {
+        let mut genes = (0..8).collect::<Vec<_>>();
+        let changed = mutation(&mut genes);
+        (genes, changed)
+    }
+
+    #[test]
+    fn mutants_are_permutations() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for n in [1, 2, 3, 5, 8, 20] {
+            for _ in 0..50 {
+                let mut parent = (0..n).collect::<Vec<_>>();
+                parent.shuffle(&mut rng);
+                for mutation in PermutationMutation::ALL {
+                    let mut genes = parent.clone();
+                    let changed = mutation.apply(&mut genes, &mut rng);
+                    let mut sorted = genes.clone();
+                    sorted.sort();
+                    assert_eq!(sorted, (0..n).collect::<Vec<_>>(), "{}", mutation);
+                    for i in (0..n).filter(|x| !changed.contains(x)) {
+                        assert_eq!(genes[i], parent[i], "{} outside {:?}", mutation, changed);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn swap_known_answer() {
+        let (genes, changed) = mutated(|x| swap_at(x, 1, 5));
+        assert_eq!(genes, [0, 5, 2, 3, 4, 1, 6, 7]);
+        assert_eq!(changed, 1..=5);
+        let (genes, changed) = mutated(|x| swap_at(x, 5, 1));
+        assert_eq!(genes, [0, 5, 2, 3, 4, 1, 6, 7]);
+        assert_eq!(changed, 1..=5);
+    }
+
+    #[test]
+    fn insertion_known_answer() {
+        let (genes, changed) = mutated(|x| insertion_at(x, 1, 5));
+        assert_eq!(genes, [0, 2, 3, 4, 5, 1, 6, 7]);
+        assert_eq!(changed, 1..=5);
+        let (genes, changed) = mutated(|x| insertion_at(x, 5, 1));
+        assert_eq!(genes, [0, 5, 1, 2, 3, 4, 6, 7]);
+        assert_eq!(changed, 1..=5);
+    }
+
+    #[test]
+    fn inversion_known_answer() {
+        let (genes, changed) = mutated(|x| inversion_at(x, 2, 5));
+        assert_eq!(genes, [0, 1, 5, 4, 3, 2, 6, 7]);
+        assert_eq!(changed, 2..=5);
+        let (genes, _) = mutated(|x| inversion_at(x, 3, 3));
+        assert_eq!(genes, (0..8).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn scramble_stays_in_its_segment() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..50 {
+            let (genes, changed) = mutated(|x| PermutationMutation::Scramble.apply(x, &mut rng));
+            let mut segment = genes[changed.clone()].to_vec();
+            segment.sort();
+            assert_eq!(segment, changed.clone().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn displacement_known_answer() {
+        let (genes, changed) = mutated(|x| displacement_at(x, 1, 3, 4));
+        assert_eq!(genes, [0, 4, 5, 6, 1, 2, 3, 7]);
+        assert_eq!(changed, 1..=6);
+        let (genes, changed) = mutated(|x| displacement_at(x, 4, 2, 1));
+        assert_eq!(genes, [0, 4, 5, 1, 2, 3, 6, 7]);
+        assert_eq!(changed, 1..=5);
+        // the whole tail moved to the front
+        let (genes, changed) = mutated(|x| displacement_at(x, 5, 3, 0));
+        assert_eq!(genes, [5, 6, 7, 0, 1, 2, 3, 4]);
+        assert_eq!(changed, 0..=7);
+    }
+}
@@ -0,0 +1,430 @@
+//! Multi-objective genetic algorithm (NSGA-II).
+//!
+//! Every objective is minimized, like `health`. A chromosome dominates
+//! another one when it is no worse in every objective and better in one.
+
+use std::sync::Arc;
+
+use rand::Rng;
+use weighted_rand::builder::NewBuilder;
+
+use super::{breed, path_length, Chromosome, GeneticIteration, Solution, TSPChromosome};
+use crate::run_control::RunControl;
+use crate::tsp::DistanceMatrix;
+
+pub trait MultiObjective: Chromosome {
+    /// Values of the objectives, lower is better; `health` is not used by
+    /// [`solve`].
+    fn objectives(&self) -> Vec<f32>;
+}
+
+/// Travel salesman problem with a second cost of every edge, e.g. the travel
+/// time besides the distance: the objectives are the tour lengths in
+/// [`TSPChromosome::distances`] and in `second_distances`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BiObjectiveTSPChromosome {
+    pub chromosome: TSPChromosome,
+    pub second_distances: Arc<DistanceMatrix>,
+    pub second_length: f32,
+}
+
+impl BiObjectiveTSPChromosome {
+    pub fn new(chromosome: TSPChromosome, second_distances: Arc<DistanceMatrix>) -> Self {
+        let second_length = path_length(&chromosome.travel_list, &second_distances) as f32;
+        Self {
+            chromosome,
+            second_distances,
+            second_length,
+        }
+    }
+    // Chromosome with the same second costs
+    fn with_chromosome(&self, chromosome: TSPChromosome) -> Self {
+        Self::new(chromosome, self.second_distances.clone())
+    }
+}
+
+impl Chromosome for BiObjectiveTSPChromosome {
+    fn mutate(&self, rng: &mut impl Rng) -> Self {
+        self.with_chromosome(self.chromosome.mutate(rng))
+    }
+    fn crossover(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+        let (first, second) = self.chromosome.crossover(&other.chromosome, rng);
+        (self.with_chromosome(first), self.with_chromosome(second))
+    }
+    fn health(&self) -> f32 {
+        self.chromosome.health()
+    }
+    fn set_index(&mut self, index: usize) {
+        self.chromosome.set_index(index);
+    }
+    fn get_index(&self) -> usize {
+        self.chromosome.get_index()
+    }
+    fn set_population(&mut self, population: usize) {
+        self.chromosome.set_population(population);
+    }
+    fn get_population(&mut self) -> usize {
+        self.chromosome.get_population()
+    }
+}
+
+impl MultiObjective for BiObjectiveTSPChromosome {
+    fn objectives(&self) -> Vec<f32> {
+        vec![self.chromosome.path_length, self.second_length]
+    }
+}
+
+pub fn dominates(a: &[f32], b: &[f32]) -> bool {
+    a.iter().zip(b).all(|(x, y)| x <= y) && a.iter().zip(b).any(|(x, y)| x < y)
+}
+
+/// Indexes of `objectives` split into fronts, the first one is not dominated
+/// at all, the next ones only by the fronts before them.
+pub fn non_dominated_sort(objectives: &[Vec<f32>]) -> Vec<Vec<usize>> {
+    let n = objectives.len();
+    let mut dominated = vec![vec![]; n];
+    let mut dominated_by = vec![0; n];
+    for a in 0..n {
+        for b in a + 1..n {
+            if dominates(&objectives[a], &objectives[b]) {
+                dominated[a].push(b);
+                dominated_by[b] += 1;
+            } else if dominates(&objectives[b], &objectives[a]) {
+                dominated[b].push(a);
+                dominated_by[a] += 1;
+            }
+        }
+    }
+    let mut fronts = vec![];
+    let mut front = (0..n).filter(|i| dominated_by[*i] == 0).collect::<Vec<_>>();
+    while !front.is_empty() {
+        let mut next = vec![];
+        for a in &front {
+            for b in &dominated[*a] {
+                dominated_by[*b] -= 1;
+                if dominated_by[*b] == 0 {
+                    next.push(*b);
+                }
+            }
+        }
+        fronts.push(front);
+        front = next;
+    }
+    fronts
+}
+
+/// Crowding distance of every chromosome of `front`: the sum over the
+/// objectives of the normalized gap between its neighbours, infinite at the
+/// ends of the front.
+#[allow(clippy::needless_range_loop)]
+pub fn crowding_distance(objectives: &[Vec<f32>], front: &[usize]) -> Vec<f64> {
+    let mut distance = vec![0.0; front.len()];
+    let amount = front.first().map_or(0, |x| objectives[*x].len());
+    let mut order = (0..front.len()).collect::<Vec<_>>();
+    for m in 0..amount {
+        let value = |i: usize| objectives[front[i]][m] as f64;
+        order.sort_by(|x, y| value(*x).total_cmp(&value(*y)));
+        let (first, last) = (order[0], order[order.len() - 1]);
+        distance[first] = f64::INFINITY;
+        distance[last] = f64::INFINITY;
+        let spread = value(last) - value(first);
+        if spread <= 0.0 {
+            continue;
+        }
+        for k in 1..order.len().saturating_sub(1) {
+            distance[order[k]] += (value(order[k + 1]) - value(order[k - 1])) / spread;
+        }
+    }
+    distance
+}
+
+// Front and crowding distance of every chromosome
+fn rank_and_crowding(objectives: &[Vec<f32>]) -> (Vec<usize>, Vec<f64>) {
+    let mut rank = vec![0; objectives.len()];
+    let mut crowding = vec![0.0; objectives.len()];
+    for (i, front) in non_dominated_sort(objectives).into_iter().enumerate() {
+        for (x, distance) in front.iter().zip(crowding_distance(objectives, &front)) {
+            rank[*x] = i;
+            crowding[*x] = distance;
+        }
+    }
+    (rank, crowding)
+}
+
+// One chromosome per point of the first front
+fn pareto_front<C: MultiObjective>(chromosomes: &[C]) -> Vec<C> {
+    let objectives = chromosomes
+        .iter()
+        .map(|x| x.objectives())
+        .collect::<Vec<_>>();
+    let mut front: Vec<usize> = vec![];
+    for i in non_dominated_sort(&objectives).swap_remove(0) {
+        if front.iter().all(|x| objectives[*x] != objectives[i]) {
+            front.push(i);
+        }
+    }
+    front.sort_by(|x, y| {
+        objectives[*x]
+            .iter()
+            .zip(&objectives[*y])
+            .map(|(a, b)| a.total_cmp(b))
+            .find(|x| x.is_ne())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    front.into_iter().map(|i| chromosomes[i].clone()).collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct ParetoIteration<C> {
+    pub old: Vec<C>,
+    pub new: Vec<C>,
+    /// Non-dominated chromosomes of `old` and `new`, sorted by the objectives.
+    pub front: Vec<C>,
+}
+
+impl<C: MultiObjective> GeneticIteration<C> for ParetoIteration<C> {
+    fn new_iter(old: Vec<C>, new: Vec<C>) -> Self {
+        let front = if old.is_empty() && new.is_empty() {
+            vec![]
+        } else {
+            pareto_front(&[old.as_slice(), new.as_slice()].concat())
+        };
+        Self { old, new, front }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParetoSolution<C> {
+    pub iterations: Vec<ParetoIteration<C>>,
+}
+
+impl<C> ParetoSolution<C> {
+    /// Pareto front of the last population.
+    pub fn front(&self) -> &[C] {
+        self.iterations.last().map_or(&[], |x| &x.front)
+    }
+}
+
+impl<C: MultiObjective> Solution<C, ParetoIteration<C>> for ParetoSolution<C> {
+    fn new_solution() -> Self {
+        Self { iterations: vec![] }
+    }
+    fn add_iteration(&mut self, iteration: ParetoIteration<C>) {
+        self.iterations.push(iteration);
+    }
+}
+
+/// NSGA-II: parents are picked by binary tournaments on the front and then
+/// the crowding distance, the next population is the best of the parents and
+/// the offspring in the same order.
+///
+/// `memetic` runs on every offspring, pass `|_| ()` for none. Stagnation of
+/// `control` counts populations without an offspring that joins the first
+/// front with a new point.
+#[allow(clippy::too_many_arguments)]
+pub fn solve<
+    ChromosomeType: MultiObjective,
+    IterationType: GeneticIteration<ChromosomeType>,
+    SolutionType: Solution<ChromosomeType, IterationType>,
+>(
+    population_amount: usize,
+    mut population: Vec<ChromosomeType>,
+    crossover_p: f32,
+    mutation_p: f32,
+    memetic: impl Fn(&mut ChromosomeType),
+    control: &RunControl,
+    rng: &mut impl Rng,
+) -> SolutionType {
+    let mut solution = SolutionType::new_solution();
+    let wa_table =
+        weighted_rand::builder::WalkerTableBuilder::new(&[crossover_p, mutation_p]).build();
+
+    let population_size = population.len();
+    let mut since_better = 0;
+    for population_i in 0..population_amount {
+        if population_size == 0 || control.should_stop(since_better) {
+            break;
+        }
+        let mut old = population.clone();
+        for (i, chromosome) in old.iter_mut().enumerate() {
+            chromosome.set_population(population_i);
+            chromosome.set_index(i);
+        }
+        let old_objectives = old.iter().map(|x| x.objectives()).collect::<Vec<_>>();
+        let (rank, crowding) = rank_and_crowding(&old_objectives);
+        let better = |a: usize, b: usize| {
+            rank[a] < rank[b] || (rank[a] == rank[b] && crowding[a] > crowding[b])
+        };
+        let mut pool = (0..population_size * 2)
+            .map(|_| {
+                let a = rng.gen_range(0..population_size);
+                let b = rng.gen_range(0..population_size);
+                if better(b, a) {
+                    b
+                } else {
+                    a
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut new = breed(&old, &mut pool, population_size, &wa_table, rng);
+        for (i, chromosome) in new.iter_mut().enumerate() {
+            memetic(chromosome);
+            chromosome.set_index(i);
+            chromosome.set_population(population_i);
+        }
+
+        let merged = [old.as_slice(), new.as_slice()].concat();
+        let objectives = merged.iter().map(|x| x.objectives()).collect::<Vec<_>>();
+        let fronts = non_dominated_sort(&objectives);
+        let improved = fronts[0].iter().any(|i| {
+            *i >= population_size && (0..population_size).all(|x| objectives[x] != objectives[*i])
+        });
+        if improved {
+            since_better = 0;
+        } else {
+            since_better += 1;
+        }
+        population.clear();
+        for front in fronts {
+            if population.len() + front.len() <= population_size {
+                population.extend(front.iter().map(|i| merged[*i].clone()));
+                continue;
+            }
+            let crowding = crowding_distance(&objectives, &front);
+            let mut order = (0..front.len()).collect::<Vec<_>>();
+            order.sort_by(|x, y| crowding[*y].total_cmp(&crowding[*x]));
+            for k in order.into_iter().take(population_size - population.len()) {
+                population.push(merged[front[k]].clone());
+            }
+            break;
+        }
+        solution.add_iteration(IterationType::new_iter(old, new));
+    }
+    solution
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::stable_graph::NodeIndex;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn points() -> Vec<Vec<f32>> {
+        [
+            [1.0, 5.0],
+            [2.0, 3.0],
+            [4.0, 1.0],
+            [3.0, 4.0],
+            [5.0, 5.0],
+            [2.0, 6.0],
+        ]
+        .into_iter()
+        .map(|x| x.to_vec())
+        .collect()
+    }
+
+    #[test]
+    fn fronts_of_hand_built_population() {
+        let mut fronts = non_dominated_sort(&points());
+        for front in fronts.iter_mut() {
+            front.sort();
+        }
+        assert_eq!(fronts, vec![vec![0, 1, 2], vec![3, 5], vec![4]]);
+    }
+
+    #[test]
+    fn crowding_of_hand_built_population() {
+        let (rank, crowding) = rank_and_crowding(&points());
+        assert_eq!(rank, vec![0, 0, 0, 1, 2, 1]);
+        // the middle of the first front: (4 - 1) / 3 + (5 - 1) / 4
+        assert_eq!(crowding[1], 2.0);
+        for i in [0, 2, 3, 4, 5] {
+            assert_eq!(crowding[i], f64::INFINITY);
+        }
+        let distance = crowding_distance(
+            &[
+                vec![0.0, 4.0],
+                vec![1.0, 3.0],
+                vec![3.0, 1.0],
+                vec![4.0, 0.0],
+            ],
+            &[0, 1, 2, 3],
+        );
+        assert_eq!(distance[1..3], [1.5, 1.5]);
+    }
+
+    // Every tour from node 0, each direction once or twice
+    fn tours(n: usize) -> Vec<Vec<usize>> {
+        let mut tours = vec![vec![0]];
+        for _ in 1..n {
+            tours = tours
+                .into_iter()
+                .flat_map(|tour| {
+                    (1..n)
+                        .filter(|x| !tour.contains(x))
+                        .map(|x| [tour.as_slice(), &[x]].concat())
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+        }
+        tours
+    }
+
+    #[test]
+    fn bi_objective_tsp_finds_the_known_front() {
+        // Whole-number costs drawn independently, so short tours are not fast
+        // ones and the sums are exact
+        let n = 6;
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut costs = || {
+            let mut matrix = DistanceMatrix::new(n);
+            for a in 0..n {
+                for b in a + 1..n {
+                    matrix.set(a, b, rng.gen_range(1..20) as f32);
+                }
+            }
+            Arc::new(matrix)
+        };
+        let (distances, times) = (costs(), costs());
+
+        let mut known = tours(n)
+            .into_iter()
+            .map(|tour| vec![distances.tour_length(&tour), times.tour_length(&tour)])
+            .collect::<Vec<_>>();
+        known = non_dominated_sort(&known)[0]
+            .iter()
+            .map(|i| known[*i].clone())
+            .collect();
+        known.sort_by(|x, y| x[0].total_cmp(&y[0]));
+        known.dedup();
+        assert!(known.len() >= 3, "{:?}", known);
+
+        let nodes = (0..n).map(NodeIndex::new).collect::<Vec<_>>();
+        let population = TSPChromosome::generate_random_population(nodes, 20, distances, &mut rng)
+            .into_iter()
+            .map(|x| BiObjectiveTSPChromosome::new(x, times.clone()))
+            .collect();
+        let solution: ParetoSolution<BiObjectiveTSPChromosome> = solve::<_, ParetoIteration<_>, _>(
+            50,
+            population,
+            0.5,
+            0.5,
+            |_| (),
+            &RunControl::new(),
+            &mut rng,
+        );
+        let front = solution
+            .front()
+            .iter()
+            .map(|x| x.objectives())
+            .collect::<Vec<_>>();
+        assert_eq!(front, known);
+
+        // the front is sorted by the first objective, its ends are kept first
+        let crowding = crowding_distance(&front, &(0..front.len()).collect::<Vec<_>>());
+        assert_eq!(crowding[0], f64::INFINITY);
+        assert_eq!(crowding[front.len() - 1], f64::INFINITY);
+        assert!(crowding[1..front.len() - 1].iter().all(|x| x.is_finite()));
+    }
+}
@@ -0,0 +1,202 @@
+//! Replacement strategies of [`solve`](super::solve): how the parents and
+//! their offspring make up the next population.
+
+use super::Chromosome;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Replacement {
+    /// The offspring replace the population except its `elites` best
+    /// chromosomes; the next best parents only fill the population when
+    /// there are too few offspring.
+    Generational { elites: usize },
+    /// The best of the parents and `offspring` offspring, (μ+λ).
+    MuPlusLambda { offspring: usize },
+    /// The best of `offspring` offspring, (μ,λ); the best parents only fill
+    /// the population when there are fewer offspring than it holds.
+    MuCommaLambda { offspring: usize },
+    /// `offspring` offspring replace the worst chromosomes.
+    SteadyState { offspring: usize },
+}
+
+impl Default for Replacement {
+    fn default() -> Self {
+        Self::Generational { elites: 1 }
+    }
+}
+
+impl Replacement {
+    /// Offspring to breed every population of `population_size` chromosomes.
+    pub fn offspring_amount(&self, population_size: usize) -> usize {
+        match self {
+            Self::Generational { .. } => population_size,
+            Self::MuPlusLambda { offspring } | Self::MuCommaLambda { offspring } => {
+                (*offspring).max(1)
+            }
+            Self::SteadyState { offspring } => (*offspring).clamp(1, population_size.max(1)),
+        }
+    }
+    /// Next population, as large as `parents`. The parents in it are marked
+    /// as survivors, see [`Chromosome::survive`].
+    pub fn replace<C: Chromosome>(&self, parents: &[C], offspring: &[C]) -> Vec<C> {
+        let size = parents.len();
+        let parents = &parents.iter().map(|x| x.survive()).collect::<Vec<_>>();
+        match self {
+            Self::Generational { elites } => {
+                let elites = (*elites).min(size);
+                let mut population = best(parents, elites);
+                population.append(&mut best(offspring, size - elites));
+                // the next best parents fill the places the offspring leave
+                let filled = elites + size - population.len();
+                population.extend(best(parents, filled).into_iter().skip(elites));
+                population
+            }
+            Self::MuPlusLambda { .. } => best(&[parents, offspring].concat(), size),
+            Self::MuCommaLambda { .. } => {
+                let mut population = best(offspring, size);
+                population.append(&mut best(parents, size - population.len()));
+                population
+            }
+            Self::SteadyState { .. } => {
+                let replaced = offspring.len().min(size);
+                let mut population = best(parents, size - replaced);
+                population.extend_from_slice(&offspring[..replaced]);
+                population
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Replacement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Generational { .. } => "Generational",
+            Self::MuPlusLambda { .. } => "(μ+λ)",
+            Self::MuCommaLambda { .. } => "(μ,λ)",
+            Self::SteadyState { .. } => "Steady-state",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// The `amount` healthiest chromosomes, best first
+fn best<C: Chromosome>(chromosomes: &[C], amount: usize) -> Vec<C> {
+    let health = chromosomes.iter().map(|x| x.health()).collect::<Vec<_>>();
+    let mut ranked = (0..chromosomes.len()).collect::<Vec<_>>();
+    ranked.sort_by(|x, y| health[*x].total_cmp(&health[*y]));
+    ranked
+        .into_iter()
+        .take(amount)
+        .map(|i| chromosomes[i].clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Health and whether the chromosome was carried over
+    #[derive(Debug, Clone, PartialEq)]
+    struct Value(f32, bool);
+
+    impl Chromosome for Value {
+        fn mutate(&self, _rng: &mut impl rand::Rng) -> Self {
+            self.clone()
+        }
+        fn crossover(&self, other: &Self, _rng: &mut impl rand::Rng) -> (Self, Self) {
+            (self.clone(), other.clone())
+        }
+        fn health(&self) -> f32 {
+            self.0
+        }
+        fn set_index(&mut self, _index: usize) {}
+        fn get_index(&self) -> usize {
+            0
+        }
+        fn set_population(&mut self, _population: usize) {}
+        fn get_population(&mut self) -> usize {
+            0
+        }
+        fn survive(&self) -> Self {
+            Value(self.0, true)
+        }
+    }
+
+    const REPLACEMENTS: [Replacement; 7] = [
+        Replacement::Generational { elites: 0 },
+        Replacement::Generational { elites: 2 },
+        Replacement::Generational { elites: 100 },
+        Replacement::MuPlusLambda { offspring: 3 },
+        Replacement::MuCommaLambda { offspring: 2 },
+        Replacement::MuCommaLambda { offspring: 20 },
+        Replacement::SteadyState { offspring: 2 },
+    ];
+
+    fn values(health: &[f32]) -> Vec<Value> {
+        health.iter().map(|x| Value(*x, false)).collect()
+    }
+
+    fn health(population: &[Value]) -> Vec<f32> {
+        let mut health = population.iter().map(|x| x.0).collect::<Vec<_>>();
+        health.sort_by(f32::total_cmp);
+        health
+    }
+
+    #[test]
+    fn population_size_is_preserved() {
+        for replacement in REPLACEMENTS {
+            for size in [0, 1, 2, 5, 10] {
+                let parents = values(&(0..size).map(|x| x as f32).collect::<Vec<_>>());
+                let amount = replacement.offspring_amount(size);
+                let offspring = values(&(0..amount).map(|x| x as f32 + 0.5).collect::<Vec<_>>());
+                let population = replacement.replace(&parents, &offspring);
+                assert_eq!(population.len(), size, "{:?}", replacement);
+                // only the parents are survivors
+                for x in &population {
+                    assert_eq!(x.1, x.0.fract() == 0.0, "{:?}", replacement);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn elites_are_kept() {
+        let parents = values(&[4.0, 1.0, 6.0, 2.0, 5.0]);
+        let offspring = values(&[0.5, 0.6, 0.7, 0.8, 0.9]);
+        let replace = |replacement: Replacement| health(&replacement.replace(&parents, &offspring));
+        assert_eq!(
+            replace(Replacement::Generational { elites: 0 }),
+            [0.5, 0.6, 0.7, 0.8, 0.9]
+        );
+        assert_eq!(
+            replace(Replacement::Generational { elites: 2 }),
+            [0.5, 0.6, 0.7, 1.0, 2.0]
+        );
+        assert_eq!(
+            replace(Replacement::MuPlusLambda { offspring: 5 }),
+            [0.5, 0.6, 0.7, 0.8, 0.9]
+        );
+        assert_eq!(
+            replace(Replacement::SteadyState { offspring: 5 }),
+            [0.5, 0.6, 0.7, 0.8, 0.9]
+        );
+        // worse offspring never push out the best parents
+        let offspring = values(&[7.0, 8.0]);
+        let replace = |replacement: Replacement| health(&replacement.replace(&parents, &offspring));
+        assert_eq!(
+            replace(Replacement::Generational { elites: 1 }),
+            [1.0, 2.0, 4.0, 7.0, 8.0]
+        );
+        assert_eq!(
+            replace(Replacement::MuPlusLambda { offspring: 2 }),
+            [1.0, 2.0, 4.0, 5.0, 6.0]
+        );
+        assert_eq!(
+            replace(Replacement::SteadyState { offspring: 2 }),
+            [1.0, 2.0, 4.0, 7.0, 8.0]
+        );
+        assert_eq!(
+            replace(Replacement::MuCommaLambda { offspring: 2 }),
+            [1.0, 2.0, 4.0, 7.0, 8.0]
+        );
+    }
+}
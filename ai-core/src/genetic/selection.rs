@@ -0,0 +1,320 @@
+//! Selection operators of [`solve`](super::solve).
+//!
+//! Chromosomes are picked by their `health`, the lower the better, so the
+//! operators work for any real costs (fractional, below 1 or negative).
+
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::{seq::SliceRandom, Rng};
+
+pub trait Selection {
+    /// Indexes of `amount` chromosomes picked from `health`, repeats allowed.
+    /// Empty when there is nothing to pick from.
+    fn select(&self, health: &[f32], amount: usize, rng: &mut impl Rng) -> Vec<usize>;
+}
+
+// Fitness to maximize: the distance to the worst health, shifted so the worst
+// chromosome keeps a small chance
+fn fitness(health: &[f32]) -> Vec<f64> {
+    let best = health.iter().copied().fold(f32::INFINITY, f32::min) as f64;
+    let worst = health.iter().copied().fold(f32::NEG_INFINITY, f32::max) as f64;
+    let spread = worst - best;
+    if !spread.is_finite() || spread == 0.0 {
+        return vec![1.0; health.len()];
+    }
+    health
+        .iter()
+        .map(|x| worst - *x as f64 + spread / health.len() as f64)
+        .collect()
+}
+
+// Positions of `health` from the best to the worst
+fn ranked(health: &[f32]) -> Vec<usize> {
+    let mut ranked = (0..health.len()).collect::<Vec<_>>();
+    ranked.sort_by(|x, y| health[*x].total_cmp(&health[*y]));
+    ranked
+}
+
+fn weighted(weights: &[f64], amount: usize, rng: &mut impl Rng) -> Vec<usize> {
+    if weights.is_empty() {
+        return vec![];
+    }
+    match WeightedIndex::new(weights) {
+        Ok(table) => (0..amount).map(|_| table.sample(rng)).collect(),
+        Err(_) => (0..amount)
+            .map(|_| rng.gen_range(0..weights.len()))
+            .collect(),
+    }
+}
+
+/// Fitness-proportional (roulette wheel) selection.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Roulette;
+
+impl Selection for Roulette {
+    fn select(&self, health: &[f32], amount: usize, rng: &mut impl Rng) -> Vec<usize> {
+        weighted(&fitness(health), amount, rng)
+    }
+}
+
+/// The best of `size` chromosomes drawn at random.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tournament {
+    pub size: usize,
+}
+
+impl Selection for Tournament {
+    fn select(&self, health: &[f32], amount: usize, rng: &mut impl Rng) -> Vec<usize> {
+        if health.is_empty() {
+            return vec![];
+        }
+        (0..amount)
+            .map(|_| {
+                (0..self.size.max(1))
+                    .map(|_| rng.gen_range(0..health.len()))
+                    .min_by(|x, y| health[*x].total_cmp(&health[*y]))
+                    .unwrap()
+            })
+            .collect()
+    }
+}
+
+/// Linear ranking: the best chromosome is `pressure` (in `[1, 2]`) times as
+/// likely as the average one, the worst `2 - pressure` times.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rank {
+    pub pressure: f32,
+}
+
+impl Selection for Rank {
+    fn select(&self, health: &[f32], amount: usize, rng: &mut impl Rng) -> Vec<usize> {
+        let n = health.len();
+        let s = self.pressure.clamp(1.0, 2.0) as f64;
+        let mut weights = vec![0.0; n];
+        for (rank, i) in ranked(health).into_iter().enumerate() {
+            let position = if n > 1 {
+                (n - 1 - rank) as f64 / (n - 1) as f64
+            } else {
+                1.0
+            };
+            weights[i] = 2.0 - s + 2.0 * (s - 1.0) * position;
+        }
+        weighted(&weights, amount, rng)
+    }
+}
+
+/// Stochastic universal sampling: `amount` evenly spaced pointers on one
+/// spin of the fitness-proportional wheel.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct StochasticUniversalSampling;
+
+impl Selection for StochasticUniversalSampling {
+    fn select(&self, health: &[f32], amount: usize, rng: &mut impl Rng) -> Vec<usize> {
+        if health.is_empty() {
+            return vec![];
+        }
+        let fitness = fitness(health);
+        let step = fitness.iter().sum::<f64>() / amount.max(1) as f64;
+        let mut pointer = rng.gen::<f64>() * step;
+        let mut sum = 0.0;
+        let mut selected = Vec::with_capacity(amount);
+        for (i, x) in fitness.iter().enumerate() {
+            sum += x;
+            while pointer < sum && selected.len() < amount {
+                selected.push(i);
+                pointer += step;
+            }
+        }
+        // rounding can leave the last pointers past the wheel
+        while selected.len() < amount {
+            selected.push(fitness.len() - 1);
+        }
+        // the pointers come in wheel order, parents are paired from neighbours
+        selected.shuffle(rng);
+        selected
+    }
+}
+
+/// Uniform choice among the best `ratio` part of the chromosomes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Truncation {
+    pub ratio: f32,
+}
+
+impl Selection for Truncation {
+    fn select(&self, health: &[f32], amount: usize, rng: &mut impl Rng) -> Vec<usize> {
+        if health.is_empty() {
+            return vec![];
+        }
+        let ranked = ranked(health);
+        let kept = ((health.len() as f32 * self.ratio).ceil() as usize).clamp(1, health.len());
+        (0..amount)
+            .map(|_| ranked[rng.gen_range(0..kept)])
+            .collect()
+    }
+}
+
+/// Probabilities proportional to `exp(-(health - best) / temperature)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Boltzmann {
+    pub temperature: f32,
+}
+
+impl Selection for Boltzmann {
+    fn select(&self, health: &[f32], amount: usize, rng: &mut impl Rng) -> Vec<usize> {
+        let best = health.iter().copied().fold(f32::INFINITY, f32::min) as f64;
+        let temperature = (self.temperature as f64).max(f64::MIN_POSITIVE);
+        let weights = health
+            .iter()
+            .map(|x| (-(*x as f64 - best) / temperature).exp())
+            .collect::<Vec<_>>();
+        weighted(&weights, amount, rng)
+    }
+}
+
+/// One of the built-in operators, for choosing the selection at run time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SelectionMethod {
+    Roulette,
+    Tournament(Tournament),
+    Rank(Rank),
+    StochasticUniversalSampling,
+    Truncation(Truncation),
+    Boltzmann(Boltzmann),
+}
+
+impl Default for SelectionMethod {
+    fn default() -> Self {
+        Self::Tournament(Tournament { size: 2 })
+    }
+}
+
+impl Selection for SelectionMethod {
+    fn select(&self, health: &[f32], amount: usize, rng: &mut impl Rng) -> Vec<usize> {
+        match self {
+            Self::Roulette => Roulette.select(health, amount, rng),
+            Self::Tournament(x) => x.select(health, amount, rng),
+            Self::Rank(x) => x.select(health, amount, rng),
+            Self::StochasticUniversalSampling => {
+                StochasticUniversalSampling.select(health, amount, rng)
+            }
+            Self::Truncation(x) => x.select(health, amount, rng),
+            Self::Boltzmann(x) => x.select(health, amount, rng),
+        }
+    }
+}
+
+impl std::fmt::Display for SelectionMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Roulette => "Roulette",
+            Self::Tournament(_) => "Tournament",
+            Self::Rank(_) => "Rank",
+            Self::StochasticUniversalSampling => "Stochastic universal sampling",
+            Self::Truncation(_) => "Truncation",
+            Self::Boltzmann(_) => "Boltzmann",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    const METHODS: [SelectionMethod; 6] = [
+        SelectionMethod::Roulette,
+        SelectionMethod::Tournament(Tournament { size: 3 }),
+        SelectionMethod::Rank(Rank { pressure: 1.5 }),
+        SelectionMethod::StochasticUniversalSampling,
+        SelectionMethod::Truncation(Truncation { ratio: 0.5 }),
+        SelectionMethod::Boltzmann(Boltzmann { temperature: 1.0 }),
+    ];
+
+    fn counts(selected: &[usize], n: usize) -> Vec<usize> {
+        let mut counts = vec![0; n];
+        for x in selected {
+            counts[*x] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn selected_indexes_are_in_range() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for n in [0, 1, 2, 3, 5, 8, 20] {
+            for _ in 0..20 {
+                let health = (0..n)
+                    .map(|_| rng.gen_range(-10.0..10.0))
+                    .collect::<Vec<f32>>();
+                for method in METHODS {
+                    for amount in [0, 1, n, 2 * n + 1] {
+                        let selected = method.select(&health, amount, &mut rng);
+                        let expected = if n == 0 { 0 } else { amount };
+                        assert_eq!(selected.len(), expected, "{}", method);
+                        assert!(selected.iter().all(|x| *x < n), "{}", method);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn equal_health_is_picked_evenly() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let selected = StochasticUniversalSampling.select(&[2.0; 4], 8, &mut rng);
+        assert_eq!(counts(&selected, 4), [2, 2, 2, 2]);
+    }
+
+    #[test]
+    fn roulette_known_answer() {
+        // fitness 1.5 and 0.5, so the better one is picked 3 times in 4
+        let mut rng = StdRng::seed_from_u64(0);
+        let selected = Roulette.select(&[0.0, 1.0], 20_000, &mut rng);
+        let share = counts(&selected, 2)[0] as f64 / 20_000.0;
+        assert!((share - 0.75).abs() < 0.02, "{}", share);
+    }
+
+    #[test]
+    fn tournament_known_answer() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let selected = Tournament { size: 64 }.select(&[3.0, 1.0, 2.0], 100, &mut rng);
+        assert_eq!(counts(&selected, 3), [0, 100, 0]);
+    }
+
+    #[test]
+    fn rank_known_answer() {
+        // with the highest pressure the worst chromosome has no chance
+        let mut rng = StdRng::seed_from_u64(0);
+        let selected = Rank { pressure: 2.0 }.select(&[5.0, -1.0], 100, &mut rng);
+        assert_eq!(counts(&selected, 2), [0, 100]);
+    }
+
+    #[test]
+    fn stochastic_universal_sampling_known_answer() {
+        // pointers 0.5 apart on a wheel of 1.5 and 0.5, wherever they start
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let selected = StochasticUniversalSampling.select(&[0.0, 1.0], 4, &mut rng);
+            assert_eq!(counts(&selected, 2), [3, 1]);
+        }
+    }
+
+    #[test]
+    fn truncation_known_answer() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let health = [4.0, 0.5, 7.0, -2.0, 3.0, 6.0, 5.0, 8.0];
+        let selected = Truncation { ratio: 0.25 }.select(&health, 100, &mut rng);
+        let counts = counts(&selected, 8);
+        assert_eq!(counts[1] + counts[3], 100);
+        assert!(counts[1] > 0 && counts[3] > 0);
+    }
+
+    #[test]
+    fn boltzmann_known_answer() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let selected = Boltzmann { temperature: 1e-6 }.select(&[0.5, 0.25, 0.75], 100, &mut rng);
+        assert_eq!(counts(&selected, 3), [0, 100, 0]);
+    }
+}
@@ -0,0 +1,203 @@
+//! Convergence and diversity statistics of every population of a
+//! [`TSPSolution`](super::TSPSolution).
+
+use std::collections::{HashMap, HashSet};
+
+use super::genealogy::Origin;
+use super::{TSPChromosome, TSPChromosomeType};
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GenerationStats {
+    pub best: f32,
+    pub mean: f32,
+    pub median: f32,
+    pub worst: f32,
+    pub std_dev: f32,
+    /// Different tours, whatever node they start from and their direction.
+    pub unique: usize,
+    /// Mean number of edges of a tour that another tour of the population
+    /// does not have.
+    pub mean_distance: f32,
+    /// Every operator that bred the offspring (by name), how many offspring it
+    /// bred and how many of them are shorter than their best parent.
+    pub operators: Vec<(Origin, usize, usize)>,
+}
+
+impl GenerationStats {
+    /// Statistics of the population `old` and its offspring `new`.
+    pub fn new(old: &[TSPChromosome], new: &[TSPChromosome]) -> Self {
+        let mut lengths = old.iter().map(|x| x.path_length).collect::<Vec<_>>();
+        lengths.sort_by(f32::total_cmp);
+        let n = lengths.len().max(1) as f32;
+        let mean = lengths.iter().sum::<f32>() / n;
+        let variance = lengths.iter().map(|x| (x - mean).powi(2)).sum::<f32>() / n;
+        let median = match lengths.len() {
+            0 => 0.0,
+            len if len % 2 == 1 => lengths[len / 2],
+            len => (lengths[len / 2 - 1] + lengths[len / 2]) / 2.0,
+        };
+        Self {
+            best: lengths.first().copied().unwrap_or(0.0),
+            mean,
+            median,
+            worst: lengths.last().copied().unwrap_or(0.0),
+            std_dev: variance.sqrt(),
+            unique: old
+                .iter()
+                .map(|x| normalized(&x.travel_list.iter().map(|x| x.index()).collect::<Vec<_>>()))
+                .collect::<HashSet<_>>()
+                .len(),
+            mean_distance: mean_distance(old),
+            operators: operators(old, new),
+        }
+    }
+}
+
+// The tour from its smallest node, towards its smaller neighbour
+fn normalized(tour: &[usize]) -> Vec<usize> {
+    let n = tour.len();
+    let Some(start) = (0..n).min_by_key(|x| tour[*x]) else {
+        return vec![];
+    };
+    if tour[(start + 1) % n] <= tour[(start + n - 1) % n] {
+        (0..n).map(|i| tour[(start + i) % n]).collect()
+    } else {
+        (0..n).map(|i| tour[(start + n - i) % n]).collect()
+    }
+}
+
+// Every pair of tours shares `c * (c - 1) / 2` times an edge found in `c` tours
+fn mean_distance(population: &[TSPChromosome]) -> f32 {
+    let p = population.len();
+    if p < 2 {
+        return 0.0;
+    }
+    let mut counts: HashMap<(usize, usize), usize> = HashMap::new();
+    for x in population {
+        let n = x.travel_list.len();
+        for i in 0..n {
+            let a = x.travel_list[i].index();
+            let b = x.travel_list[(i + 1) % n].index();
+            *counts.entry((a.min(b), a.max(b))).or_default() += 1;
+        }
+    }
+    let shared = counts.values().map(|c| c * (c - 1) / 2).sum::<usize>() as f64;
+    let edges = population[0].travel_list.len() as f64;
+    (edges - shared / (p * (p - 1) / 2) as f64) as f32
+}
+
+fn operators(old: &[TSPChromosome], new: &[TSPChromosome]) -> Vec<(Origin, usize, usize)> {
+    let parent = |i: usize| old.get(i).map_or(f32::INFINITY, |x| x.path_length);
+    let mut operators: Vec<(Origin, usize, usize)> = vec![];
+    for x in new {
+        let (origin, parent_length) = match &x.chromosome_type {
+            TSPChromosomeType::Crossover(a, b) => (
+                Origin::Crossover(x.crossover),
+                parent(a.chromosome_index).min(parent(b.chromosome_index)),
+            ),
+            TSPChromosomeType::Mutation(a, operator) => {
+                (Origin::Mutation(*operator), parent(a.chromosome_index))
+            }
+            _ => continue,
+        };
+        let shorter = (x.path_length < parent_length) as usize;
+        match operators.iter_mut().find(|y| y.0 == origin) {
+            Some(y) => {
+                y.1 += 1;
+                y.2 += shorter;
+            }
+            None => operators.push((origin, 1, shorter)),
+        }
+    }
+    operators.sort_by_key(|x| x.0.to_string());
+    operators
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use petgraph::graph::NodeIndex;
+
+    use super::*;
+    use crate::genetic::{
+        crossover::PermutationCrossover, mutation::PermutationMutation, TSPParentInfo,
+    };
+    use crate::tsp::DistanceMatrix;
+
+    fn chromosome(
+        tour: &[usize],
+        length: f32,
+        chromosome_type: TSPChromosomeType,
+    ) -> TSPChromosome {
+        let distances = Arc::new(DistanceMatrix::from_fn(5, |_, _| 1.0));
+        let travel_list = tour.iter().map(|x| NodeIndex::new(*x)).collect();
+        let mut x = TSPChromosome::new(0, 0, travel_list, distances);
+        x.path_length = length;
+        x.chromosome_type = chromosome_type;
+        x
+    }
+
+    fn parent(chromosome_index: usize) -> TSPParentInfo {
+        TSPParentInfo {
+            chromosome_index,
+            population_index: 0,
+        }
+    }
+
+    #[test]
+    fn hand_built_population() {
+        let old = [
+            ([0, 1, 2, 3, 4], 10.0),
+            // the same tour from another node and backwards
+            ([2, 3, 4, 0, 1], 20.0),
+            ([0, 4, 3, 2, 1], 30.0),
+            // shares three of five edges with the others
+            ([0, 2, 1, 3, 4], 40.0),
+        ]
+        .map(|(tour, length)| chromosome(&tour, length, TSPChromosomeType::NoHistory));
+        let new = [
+            chromosome(
+                &[0, 1, 2, 3, 4],
+                5.0,
+                TSPChromosomeType::Crossover(parent(0), parent(1)),
+            ),
+            chromosome(
+                &[0, 1, 2, 3, 4],
+                50.0,
+                TSPChromosomeType::Mutation(parent(3), PermutationMutation::Swap),
+            ),
+            chromosome(
+                &[0, 1, 2, 3, 4],
+                8.0,
+                TSPChromosomeType::Mutation(parent(0), PermutationMutation::Swap),
+            ),
+            chromosome(
+                &[0, 1, 2, 3, 4],
+                1.0,
+                TSPChromosomeType::Survivor(parent(0)),
+            ),
+        ];
+        let stats = GenerationStats::new(&old, &new);
+        assert_eq!(stats.best, 10.0);
+        assert_eq!(stats.worst, 40.0);
+        assert_eq!(stats.mean, 25.0);
+        assert_eq!(stats.median, 25.0);
+        assert!((stats.std_dev - 125f32.sqrt()).abs() < 1e-5);
+        assert_eq!(stats.unique, 2);
+        assert_eq!(stats.mean_distance, 1.0);
+        assert_eq!(
+            stats.operators,
+            [
+                (Origin::Crossover(PermutationCrossover::default()), 1, 1),
+                (Origin::Mutation(PermutationMutation::Swap), 2, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_population() {
+        let stats = GenerationStats::new(&[], &[]);
+        assert_eq!(stats, GenerationStats::default());
+    }
+}
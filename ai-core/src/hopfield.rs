@@ -1,6 +1,6 @@
 use std::ops::Add;
 
-use ndarray::{linalg::Dot, prelude::*, ShapeBuilder};
+use ndarray::{prelude::*, ShapeBuilder};
 
 pub struct Network {
     pub w: ndarray::Array2<f32>,
@@ -30,7 +30,7 @@ impl Network {
             w: Array::zeros((n, n).f()),
         }
     }
-    pub fn train(mut self, x: &Vec<ndarray::Array1<i32>>) -> Self {
+    pub fn train(mut self, x: &[ndarray::Array1<i32>]) -> Self {
         let n = x.len();
         let z = x
             .iter()
@@ -39,11 +39,11 @@ impl Network {
                     .unwrap()
             })
             .collect::<Vec<_>>();
-        for i in 0..z.len() {
-            self.w = self.w.add(z[i].t().dot(&z[i]));
+        for z in &z {
+            self.w = self.w.add(z.t().dot(z));
         }
         self.w.diag_mut().iter_mut().for_each(|x| *x = 0.0);
-        self.w.iter_mut().for_each(|x| *x = *x / n as f32);
+        self.w.iter_mut().for_each(|x| *x /= n as f32);
         self
     }
     pub fn predict(&self, v: &ndarray::Array1<i32>) -> Vec<i32> {
@@ -55,7 +55,6 @@ impl Network {
             let old_y = y.clone();
             y = self.w.dot(&y);
             y.iter_mut().for_each(|x| *x = Network::sign(*x));
-            println!("y:{}", y);
             if old_y == y {
                 break;
             }
@@ -1,6 +1,6 @@
 use std::ops::Add;
 
-use ndarray::{linalg::Dot, prelude::*, ShapeBuilder};
+use ndarray::{prelude::*, ShapeBuilder};
 
 pub struct Network {
     pub w: ndarray::Array2<i32>,
@@ -21,7 +21,7 @@ impl Network {
             w: Array::zeros((n, m).f()),
         }
     }
-    pub fn train(mut self, x: &Vec<ndarray::Array1<i32>>, y: &Vec<ndarray::Array1<i32>>) -> Self {
+    pub fn train(mut self, x: &[ndarray::Array1<i32>], y: &[ndarray::Array1<i32>]) -> Self {
         let x = x
             .iter()
             .map(|v| v.to_shape((1, v.len())).unwrap())
@@ -0,0 +1,18 @@
+//! Headless implementations of every algorithm shown by the GUI apps.
+//!
+//! The crate has no GUI dependencies, so it can be used from services,
+//! benchmarks and tests as well as from the eframe frontends.
+
+pub mod activation;
+pub mod ant_algo;
+pub mod art1;
+pub mod error_func;
+pub mod genetic;
+pub mod hopfield;
+pub mod kosko;
+pub mod local_search;
+pub mod neuro;
+pub mod run_control;
+pub mod simulated_annealing;
+pub mod tsp;
+pub mod tsplib;
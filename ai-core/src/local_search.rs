@@ -0,0 +1,352 @@
+//! Local improvement of travel salesman tours.
+//!
+//! Tours are closed and list node ids, `distance(a, b)` is the length of the
+//! edge `a - b`. `neighbours[a]` (nearest first, see
+//! [`DistanceMatrix::neighbour_lists`](crate::tsp::DistanceMatrix::neighbour_lists))
+//! limits the nodes tried as new neighbours of `a`, without it every node
+//! of the tour is tried.
+
+// Smallest gain of an applied move, so rounding errors can't loop forever
+const EPS: f32 = 1e-4;
+
+/// Moves used by [`improve`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LocalSearch {
+    pub two_opt: bool,
+    pub or_opt: bool,
+    pub three_opt: bool,
+    /// Size of the neighbour lists, `None` tries every node.
+    pub neighbours: Option<usize>,
+}
+
+impl LocalSearch {
+    pub fn is_enabled(&self) -> bool {
+        self.two_opt || self.or_opt || self.three_opt
+    }
+}
+
+fn positions(tour: &[usize]) -> Vec<usize> {
+    let mut pos = vec![usize::MAX; tour.iter().max().map_or(0, |x| x + 1)];
+    for (i, node) in tour.iter().enumerate() {
+        pos[*node] = i;
+    }
+    pos
+}
+
+fn candidates(a: usize, tour: &[usize], neighbours: Option<&[Vec<usize>]>) -> Vec<usize> {
+    match neighbours {
+        Some(neighbours) => neighbours[a].clone(),
+        None => tour.to_vec(),
+    }
+}
+
+/// Reverses parts of the tour while that makes it shorter.
+pub fn two_opt(
+    tour: &mut [usize],
+    distance: &impl Fn(usize, usize) -> f32,
+    neighbours: Option<&[Vec<usize>]>,
+) -> bool {
+    let n = tour.len();
+    if n < 4 {
+        return false;
+    }
+    let mut pos = positions(tour);
+    let mut improved_any = false;
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 0..n {
+            let (a, b) = (tour[i], tour[(i + 1) % n]);
+            for c in candidates(a, tour, neighbours) {
+                if c == a {
+                    continue;
+                }
+                // neighbours are sorted, so no later one can shorten the tour
+                if neighbours.is_some() && distance(a, c) >= distance(a, b) {
+                    break;
+                }
+                let (p, q) = (i.min(pos[c]), i.max(pos[c]));
+                let (x, y, z, w) = (tour[p], tour[p + 1], tour[q], tour[(q + 1) % n]);
+                let delta = distance(x, z) + distance(y, w) - distance(x, y) - distance(z, w);
+                if delta < -EPS {
+                    tour[p + 1..=q].reverse();
+                    for k in p + 1..=q {
+                        pos[tour[k]] = k;
+                    }
+                    improved = true;
+                    improved_any = true;
+                    break;
+                }
+            }
+        }
+    }
+    improved_any
+}
+
+/// Moves segments of 1 to 3 nodes (possibly reversed) between other nodes
+/// while that makes the tour shorter.
+pub fn or_opt(
+    tour: &mut Vec<usize>,
+    distance: &impl Fn(usize, usize) -> f32,
+    neighbours: Option<&[Vec<usize>]>,
+) -> bool {
+    let n = tour.len();
+    let mut improved_any = false;
+    let mut improved = true;
+    while improved {
+        improved = false;
+        'search: for segment_len in 1..=3 {
+            if n < segment_len + 3 {
+                break;
+            }
+            let pos = positions(tour);
+            for i in 0..=n - segment_len {
+                let (first, last) = (tour[i], tour[i + segment_len - 1]);
+                let (prev, next) = (tour[(i + n - 1) % n], tour[(i + segment_len) % n]);
+                let removed = distance(prev, first) + distance(last, next) - distance(prev, next);
+                let in_segment = |x: usize| (pos[x] + n - i) % n < segment_len;
+                for c in candidates(first, tour, neighbours) {
+                    if in_segment(c) {
+                        continue;
+                    }
+                    let before = tour[(pos[c] + n - 1) % n];
+                    let after = tour[(pos[c] + 1) % n];
+                    for (x, y) in [(c, after), (before, c)] {
+                        if in_segment(x) || in_segment(y) {
+                            continue;
+                        }
+                        let forward = distance(x, first) + distance(last, y) - distance(x, y);
+                        let backward = distance(x, last) + distance(first, y) - distance(x, y);
+                        if forward.min(backward) - removed < -EPS {
+                            let mut segment = tour.drain(i..i + segment_len).collect::<Vec<_>>();
+                            if backward < forward {
+                                segment.reverse();
+                            }
+                            let at = tour.iter().position(|node| *node == x).unwrap() + 1;
+                            tour.splice(at..at, segment);
+                            improved = true;
+                            improved_any = true;
+                            break 'search;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    improved_any
+}
+
+/// Replaces three edges of the tour at once while that makes it shorter.
+pub fn three_opt(
+    tour: &mut [usize],
+    distance: &impl Fn(usize, usize) -> f32,
+    neighbours: Option<&[Vec<usize>]>,
+) -> bool {
+    let n = tour.len();
+    if n < 6 {
+        return false;
+    }
+    let mut improved_any = false;
+    let mut improved = true;
+    while improved {
+        improved = false;
+        let pos = positions(tour);
+        'search: for i in 1..n {
+            for d in candidates(tour[i - 1], tour, neighbours) {
+                let j = pos[d];
+                if j <= i {
+                    continue;
+                }
+                for k in j + 1..=n {
+                    if reconnect(tour, i, j, k, distance) {
+                        improved = true;
+                        improved_any = true;
+                        break 'search;
+                    }
+                }
+            }
+        }
+    }
+    improved_any
+}
+
+// Edges `A-B`, `C-D` and `E-F` before positions `i < j < k` are replaced by
+// the shortest reconnection, returns whether the tour changed
+fn reconnect(
+    tour: &mut [usize],
+    i: usize,
+    j: usize,
+    k: usize,
+    distance: &impl Fn(usize, usize) -> f32,
+) -> bool {
+    let n = tour.len();
+    let (a, b, c, d, e, f) = (
+        tour[i - 1],
+        tour[i],
+        tour[j - 1],
+        tour[j],
+        tour[k - 1],
+        tour[k % n],
+    );
+    let d0 = distance(a, b) + distance(c, d) + distance(e, f);
+    let d1 = distance(a, c) + distance(b, d) + distance(e, f);
+    let d2 = distance(a, b) + distance(c, e) + distance(d, f);
+    let d3 = distance(a, d) + distance(e, b) + distance(c, f);
+    let d4 = distance(f, b) + distance(c, d) + distance(e, a);
+    if d1 - d0 < -EPS {
+        tour[i..j].reverse();
+    } else if d2 - d0 < -EPS {
+        tour[j..k].reverse();
+    } else if d4 - d0 < -EPS {
+        tour[i..k].reverse();
+    } else if d3 - d0 < -EPS {
+        tour[i..k].rotate_left(j - i);
+    } else {
+        return false;
+    }
+    true
+}
+
+/// Applies the moves of `search` until none of them shortens the tour.
+///
+/// Returns whether the tour changed.
+pub fn improve(
+    tour: &mut Vec<usize>,
+    distance: &impl Fn(usize, usize) -> f32,
+    search: &LocalSearch,
+    neighbours: Option<&[Vec<usize>]>,
+) -> bool {
+    let mut improved_any = false;
+    loop {
+        let mut improved = false;
+        if search.two_opt {
+            improved |= two_opt(tour, distance, neighbours);
+        }
+        if search.or_opt {
+            improved |= or_opt(tour, distance, neighbours);
+        }
+        if search.three_opt {
+            improved |= three_opt(tour, distance, neighbours);
+        }
+        if !improved {
+            return improved_any;
+        }
+        improved_any = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+    use super::*;
+    use crate::tsp::DistanceMatrix;
+
+    fn matrix(points: &[(f32, f32)]) -> DistanceMatrix {
+        DistanceMatrix::from_fn(points.len(), |a, b| {
+            let (x, y) = (points[a].0 - points[b].0, points[a].1 - points[b].1);
+            (x * x + y * y).sqrt()
+        })
+    }
+
+    fn is_permutation(tour: &[usize], n: usize) -> bool {
+        let mut sorted = tour.to_vec();
+        sorted.sort();
+        sorted == (0..n).collect::<Vec<_>>()
+    }
+
+    const SEARCHES: [LocalSearch; 4] = [
+        LocalSearch {
+            two_opt: true,
+            or_opt: false,
+            three_opt: false,
+            neighbours: None,
+        },
+        LocalSearch {
+            two_opt: false,
+            or_opt: true,
+            three_opt: false,
+            neighbours: None,
+        },
+        LocalSearch {
+            two_opt: false,
+            or_opt: false,
+            three_opt: true,
+            neighbours: None,
+        },
+        LocalSearch {
+            two_opt: true,
+            or_opt: true,
+            three_opt: true,
+            neighbours: None,
+        },
+    ];
+
+    #[test]
+    fn tours_stay_permutations_and_never_get_longer() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for n in [1, 2, 3, 4, 5, 6, 7, 10, 25] {
+            for _ in 0..20 {
+                let points = (0..n)
+                    .map(|_| (rng.gen_range(0.0..100.0), rng.gen_range(0.0..100.0)))
+                    .collect::<Vec<_>>();
+                let distances = matrix(&points);
+                let distance = |a, b| distances.get(a, b);
+                let mut start = (0..n).collect::<Vec<_>>();
+                start.shuffle(&mut rng);
+                for k in [None, Some(5)] {
+                    let lists = k.map(|k| distances.neighbour_lists(k));
+                    for search in SEARCHES {
+                        let mut tour = start.clone();
+                        improve(&mut tour, &distance, &search, lists.as_deref());
+                        assert!(is_permutation(&tour, n), "{:?} {:?}", search, tour);
+                        assert!(
+                            distances.tour_length(&tour) <= distances.tour_length(&start) + 1e-3,
+                            "{:?}",
+                            search
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn two_opt_uncrosses_a_square() {
+        let distances = matrix(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]);
+        let distance = |a, b| distances.get(a, b);
+        for lists in [None, Some(distances.neighbour_lists(3))] {
+            let mut tour = vec![0, 2, 1, 3];
+            assert!(two_opt(&mut tour, &distance, lists.as_deref()));
+            assert_eq!(distances.tour_length(&tour), 4.0);
+            assert!(!two_opt(&mut tour, &distance, lists.as_deref()));
+        }
+    }
+
+    #[test]
+    fn neighbour_lists_give_the_full_scan_length() {
+        // On a circle the only tour without crossings goes around it, so both
+        // searches must find it
+        let n = 20;
+        let points = (0..n)
+            .map(|i| {
+                let angle = i as f32 * std::f32::consts::TAU / n as f32;
+                (100.0 * angle.cos(), 100.0 * angle.sin())
+            })
+            .collect::<Vec<_>>();
+        let distances = matrix(&points);
+        let distance = |a, b| distances.get(a, b);
+        let around = distances.tour_length(&(0..n).collect::<Vec<_>>());
+        let lists = distances.neighbour_lists(n - 1);
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..20 {
+            let mut start = (0..n).collect::<Vec<_>>();
+            start.shuffle(&mut rng);
+            let (mut full, mut listed) = (start.clone(), start.clone());
+            two_opt(&mut full, &distance, None);
+            two_opt(&mut listed, &distance, Some(&lists));
+            assert!((distances.tour_length(&full) - around).abs() < 1e-2);
+            assert!((distances.tour_length(&listed) - around).abs() < 1e-2);
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use crate::activation::*;
 use crate::error_func::*;
+use crate::run_control::RunControl;
 use rand::distributions::{Distribution, Uniform};
+use rand::Rng;
 use std::str::FromStr;
 
 #[derive(Debug, PartialEq)]
@@ -17,7 +19,7 @@ pub struct Sample {
     pub solution: Vec<f32>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct NeuralLayer {
     raw_input: Vec<f32>,
     input: Vec<f32>,
@@ -35,6 +37,7 @@ impl NeuralLayer {
         neurons_amount: usize,
         back_links_amount: usize,
         activation: ActivationFunc,
+        rng: &mut impl Rng,
     ) -> Self {
         Self {
             raw_input: vec![0.0; neurons_amount],
@@ -44,7 +47,7 @@ impl NeuralLayer {
             weights: (0..neurons_amount)
                 .map(|_| {
                     (0..back_links_amount)
-                        .map(|_| rand::random::<f32>())
+                        .map(|_| rng.gen::<f32>())
                         .collect::<Vec<f32>>()
                 })
                 .collect::<Vec<_>>(),
@@ -93,29 +96,28 @@ impl NeuralLayer {
             }
         }
         let df = self.activation.df(self.input.clone());
-        for i in 0..self.grad.len() {
-            self.grad[i] *= df[i];
+        for (g, d) in self.grad.iter_mut().zip(df.iter()) {
+            *g *= d;
         }
         self.old_grads.push(self.grad.clone());
 
         (self.grad.clone(), self.weights.clone())
     }
     pub fn correct(&mut self, prev_outputs: Vec<Vec<f32>>, learning_rate: f32) {
-        if prev_outputs.len() == 0 {
+        if prev_outputs.is_empty() {
             self.clear();
             return;
         }
 
-        for k in 0..self.old_grads.len() {
-            for i in 0..self.weights.len() {
-                for j in 0..self.weights[i].len() {
-                    self.weights[i][j] = self.weights[i][j]
-                        - learning_rate * self.old_grads[k][i] * prev_outputs[k][j]
-                            / self.old_grads.len() as f32;
+        let batch_len = self.old_grads.len() as f32;
+        for (grads, outputs) in self.old_grads.iter().zip(prev_outputs.iter()) {
+            for (weights, grad) in self.weights.iter_mut().zip(grads.iter()) {
+                for (w, output) in weights.iter_mut().zip(outputs.iter()) {
+                    *w -= learning_rate * grad * output / batch_len;
                 }
             }
-            for i in 0..self.basis.len() {
-                self.basis[i] -= learning_rate * self.old_grads[k][i] / self.old_grads.len() as f32;
+            for (b, grad) in self.basis.iter_mut().zip(grads.iter()) {
+                *b -= learning_rate * grad / batch_len;
             }
         }
 
@@ -125,6 +127,11 @@ impl NeuralLayer {
         self.old_grads.clear();
         self.old_outputs.clear();
     }
+    /// Copies weights and basis of `other`, reusing the allocated buffers.
+    fn copy_parameters(&mut self, other: &NeuralLayer) {
+        self.weights.clone_from(&other.weights);
+        self.basis.clone_from(&other.basis);
+    }
 }
 
 #[derive(Debug)]
@@ -164,7 +171,7 @@ pub fn neural_layer_to_json(layer: &NeuralLayer) -> NeuralLayerJson {
 
 pub fn neural_to_json(net: &NeuralNetwork) -> NeuralNetworkJson {
     NeuralNetworkJson {
-        layers: net.layers.iter().map(|x| neural_layer_to_json(x)).collect(),
+        layers: net.layers.iter().map(neural_layer_to_json).collect(),
         batch_size: net.batch_size,
         epoch_amount: net.epoch_amount,
         learning_rate: net.learning_rate,
@@ -174,20 +181,24 @@ pub fn neural_to_json(net: &NeuralNetwork) -> NeuralNetworkJson {
 }
 
 fn json_to_layer(j: NeuralLayerJson) -> NeuralLayer {
-    let mut layer = NeuralLayer::new(
-        j.weights.len(),
-        j.weights[0].len(),
-        ActivationFunc::from_str(&j.activation).unwrap(),
-    );
-    layer.weights = j.weights;
-    layer.basis = j.basis;
-    layer
+    let neurons_amount = j.weights.len();
+    NeuralLayer {
+        raw_input: vec![0.0; neurons_amount],
+        input: vec![0.0; neurons_amount],
+        output: vec![0.0; neurons_amount],
+        basis: j.basis,
+        weights: j.weights,
+        grad: vec![0.0; neurons_amount],
+        old_grads: vec![],
+        old_outputs: vec![],
+        activation: Activation::new(ActivationFunc::from_str(&j.activation).unwrap()),
+    }
 }
 
 pub fn json_to_network(j: NeuralNetworkJson) -> NeuralNetwork {
-    let layers = j.layers.into_iter().map(|x| json_to_layer(x)).collect();
+    let layers = j.layers.into_iter().map(json_to_layer).collect();
     NeuralNetwork {
-        layers: layers,
+        layers,
         batch_size: j.batch_size,
         epoch_amount: j.epoch_amount,
         learning_rate: j.learning_rate,
@@ -197,15 +208,16 @@ pub fn json_to_network(j: NeuralNetworkJson) -> NeuralNetwork {
 }
 
 impl NeuralNetwork {
-    pub fn new(layers: Vec<usize>, labels: Vec<String>) -> NeuralNetwork {
+    pub fn new(layers: Vec<usize>, labels: Vec<String>, rng: &mut impl Rng) -> NeuralNetwork {
         assert!(layers[layers.len() - 1] == labels.len());
         let mut neuro_layers: Vec<NeuralLayer> = Vec::with_capacity(layers.len());
-        neuro_layers.push(NeuralLayer::new(layers[0], 0, ActivationFunc::Sigmoid));
+        neuro_layers.push(NeuralLayer::new(layers[0], 0, ActivationFunc::Sigmoid, rng));
         for i in 1..layers.len() {
             neuro_layers.push(NeuralLayer::new(
                 layers[i],
                 layers[i - 1],
                 ActivationFunc::Sigmoid,
+                rng,
             ));
         }
         for k in 1..neuro_layers.len() {
@@ -214,10 +226,9 @@ impl NeuralNetwork {
                 .sqrt();
             let glorot = 6.0f32.sqrt() / fan;
             let between = Uniform::from(-glorot..glorot);
-            let mut rng = rand::thread_rng();
             for i in 0..neuro_layers[k].weights.len() {
                 for j in 0..neuro_layers[k].weights[i].len() {
-                    neuro_layers[k].weights[i][j] = between.sample(&mut rng);
+                    neuro_layers[k].weights[i][j] = between.sample(rng);
                 }
             }
         }
@@ -226,7 +237,7 @@ impl NeuralNetwork {
             batch_size: 1,
             epoch_amount: 100,
             error_function: ErrorFunction::new(ErrorFunc::Simple),
-            labels: labels,
+            labels,
             learning_rate: 0.1,
         }
     }
@@ -263,15 +274,12 @@ impl NeuralNetwork {
     }
     pub fn with_epoch(self, epoch_amount: usize) -> Self {
         Self {
-            epoch_amount: epoch_amount,
+            epoch_amount,
             ..self
         }
     }
     pub fn with_batch_size(self, batch_size: usize) -> Self {
-        Self {
-            batch_size: batch_size,
-            ..self
-        }
+        Self { batch_size, ..self }
     }
     fn forward(&mut self, data: Vec<f32>) {
         self.layers[0].raw_input = data.clone();
@@ -290,7 +298,7 @@ impl NeuralNetwork {
             .iter()
             .zip(desired_output.iter())
             .zip(last_activation.df(self.layers[self.layers.len() - 1].input.clone()))
-            .map(|((x, y), z)| self.error_function.df(*x, *y) as f32 * z)
+            .map(|((x, y), z)| self.error_function.df(*x, *y) * z)
             .collect::<Vec<_>>();
         let cost = grad.iter().sum::<f32>();
         let mut weights = self.layers[self.layers.len() - 1].weights.clone();
@@ -320,21 +328,49 @@ impl NeuralNetwork {
         }
         self.layers[0].correct(vec![], learning_rate);
     }
-    pub fn train(&mut self, data: Vec<Sample>, learning_rate: f32) {
+    /// Returns the cost of every epoch done.
+    ///
+    /// Stops early when `control` says so (stagnation counts epochs without a
+    /// lower cost). Either way the weights of the epoch with the lowest cost are
+    /// kept.
+    pub fn train(
+        &mut self,
+        data: Vec<Sample>,
+        learning_rate: f32,
+        control: &RunControl,
+    ) -> Vec<f32> {
         self.learning_rate = learning_rate;
-        let mut batches: Vec<Batch> = data
+        let batches: Vec<Batch> = data
             .chunks(self.batch_size)
             .map(|samples| Batch::new(samples.into()))
             .collect();
-        for i in 0..self.epoch_amount {
+        let mut best_cost = f32::INFINITY;
+        let mut best_layers = self.layers.clone();
+        let mut since_best = 0;
+        let mut costs = vec![];
+        for _ in 0..self.epoch_amount {
+            if control.should_stop(since_best) {
+                break;
+            }
             let mut epoch_cost = 0.0;
-            for j in 0..batches.len() {
-                epoch_cost += self.train_step(&batches[j], learning_rate);
+            for batch in &batches {
+                epoch_cost += self.train_step(batch, learning_rate);
             }
-            if i % 100 == 0 {
-                println!("Cost: {} ({})", epoch_cost, i);
+            if epoch_cost < best_cost {
+                best_cost = epoch_cost;
+                for (best, layer) in best_layers.iter_mut().zip(&self.layers) {
+                    best.copy_parameters(layer);
+                }
+                since_best = 0;
+            } else {
+                since_best += 1;
             }
+            costs.push(epoch_cost);
+        }
+        for (layer, best) in self.layers.iter_mut().zip(&best_layers) {
+            layer.copy_parameters(best);
         }
+        costs
     }
     fn clear_layers(&mut self) {
         for i in 0..self.layers.len() {
@@ -376,6 +412,48 @@ struct Batch {
 }
 impl Batch {
     fn new(data: Vec<Sample>) -> Self {
-        Self { data: data }
+        Self { data }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn xor() -> Vec<Sample> {
+        [[0.0, 0.0], [0.0, 1.0], [1.0, 0.0], [1.0, 1.0]]
+            .into_iter()
+            .map(|x| Sample {
+                data: x.to_vec(),
+                solution: vec![(x[0] != x[1]) as u8 as f32],
+            })
+            .collect()
+    }
+
+    fn network(epoch_amount: usize) -> NeuralNetwork {
+        let mut rng = StdRng::seed_from_u64(5);
+        NeuralNetwork::new(vec![2, 3, 1], vec!["xor".to_string()], &mut rng)
+            .with_epoch(epoch_amount)
+            .with_batch_size(2)
+    }
+
+    #[test]
+    fn epoch_limit_keeps_best_weights() {
+        let mut net = network(40);
+        let costs = net.train(xor(), 50.0, &RunControl::new());
+        assert_eq!(costs.len(), 40);
+        let best = (0..costs.len())
+            .min_by(|&i, &j| costs[i].total_cmp(&costs[j]))
+            .unwrap();
+        assert!(best + 1 < costs.len(), "the last epoch is the best one");
+
+        let mut reference = network(best + 1);
+        reference.train(xor(), 50.0, &RunControl::new());
+        for (layer, expected) in net.layers.iter().zip(&reference.layers) {
+            assert_eq!(layer.weights, expected.weights);
+            assert_eq!(layer.basis, expected.basis);
+        }
     }
 }
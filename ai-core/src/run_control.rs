@@ -0,0 +1,107 @@
+//! Stopping and pausing of long running solvers.
+//!
+//! A [`RunControl`] is cloned into the solver, the clones share the cancel
+//! and pause flags, so the caller can stop or pause the run from another
+//! thread. Solvers call [`RunControl::should_stop`] once per iteration and
+//! return what they found so far when it says so.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Default)]
+pub struct RunControl {
+    cancelled: Arc<AtomicBool>,
+    paused: Arc<(Mutex<bool>, Condvar)>,
+    deadline: Option<Instant>,
+    // iterations without improvement before the run stops
+    stagnation: Option<usize>,
+}
+
+impl RunControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+    /// Stops the run `limit` after now.
+    pub fn with_time_limit(self, limit: Duration) -> Self {
+        self.with_deadline(Instant::now() + limit)
+    }
+    /// Stops the run after `iterations` iterations without a better result.
+    pub fn with_stagnation(mut self, iterations: usize) -> Self {
+        self.stagnation = Some(iterations);
+        self
+    }
+    pub fn cancel(&self) {
+        // under the pause lock, so a solver can't miss the wake up between
+        // checking the flag and waiting
+        let _paused = self.paused.0.lock().unwrap();
+        self.cancelled.store(true, Ordering::SeqCst);
+        // a paused solver has to wake up to see it
+        self.paused.1.notify_all();
+    }
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+    pub fn pause(&self) {
+        *self.paused.0.lock().unwrap() = true;
+    }
+    pub fn resume(&self) {
+        *self.paused.0.lock().unwrap() = false;
+        self.paused.1.notify_all();
+    }
+    pub fn is_paused(&self) -> bool {
+        *self.paused.0.lock().unwrap()
+    }
+    pub fn is_expired(&self) -> bool {
+        self.deadline.is_some_and(|x| Instant::now() >= x)
+    }
+    /// Blocks while the run is paused, then returns whether the solver has to
+    /// stop. `stagnation` is the number of iterations since the last improvement.
+    pub fn should_stop(&self, stagnation: usize) -> bool {
+        let (paused, resumed) = &*self.paused;
+        let mut is_paused = paused.lock().unwrap();
+        while *is_paused && !self.is_cancelled() && !self.is_expired() {
+            is_paused = match self.deadline {
+                Some(deadline) => {
+                    let timeout = deadline.saturating_duration_since(Instant::now());
+                    resumed.wait_timeout(is_paused, timeout).unwrap().0
+                }
+                None => resumed.wait(is_paused).unwrap(),
+            };
+        }
+        drop(is_paused);
+        self.is_cancelled() || self.is_expired() || self.stagnation.is_some_and(|x| stagnation >= x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancel_wakes_a_paused_solver() {
+        for _ in 0..100 {
+            let control = RunControl::new();
+            control.pause();
+            let solver = {
+                let control = control.clone();
+                std::thread::spawn(move || control.should_stop(0))
+            };
+            control.cancel();
+            assert!(solver.join().unwrap());
+        }
+    }
+
+    #[test]
+    fn stagnation_and_deadline_stop() {
+        let control = RunControl::new().with_stagnation(3);
+        assert!(!control.should_stop(2));
+        assert!(control.should_stop(3));
+        let control = RunControl::new().with_time_limit(Duration::ZERO);
+        assert!(control.should_stop(0));
+    }
+}
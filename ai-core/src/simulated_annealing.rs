@@ -0,0 +1,417 @@
+use rand::Rng;
+
+use crate::run_control::RunControl;
+
+pub mod bin_packing;
+pub mod calibration;
+pub mod colouring;
+pub mod cooling;
+pub mod function;
+pub mod queens;
+pub mod sudoku;
+pub mod tour;
+
+use calibration::{calibrate_temperature, Frozen, InitialTemperature};
+use cooling::{CoolingSchedule, Reheat};
+
+// Состояние решения
+pub trait State {
+    // Ход из состояния, например переставляемые элементы. Состояние без
+    // быстрого расчета разницы энергии может ходить новым состоянием целиком,
+    // см. `replacement`
+    type Move;
+    // Первоначальное решение
+    fn setup(&mut self);
+    // Расчет энергии
+    fn energy(&self) -> f64;
+    // Шаг изменения решения
+    fn changed_state(&self, rng: &mut impl Rng) -> Self;
+    // Ход из состояния с энергией `energy` и разница энергии после него без
+    // ее полного пересчета
+    fn propose(&self, energy: f64, rng: &mut impl Rng) -> (Self::Move, f64);
+    // Применить ход к состоянию, из которого он предложен
+    fn apply(&mut self, change: Self::Move);
+}
+
+// Ход новым состоянием из `changed_state` и разница его полной энергии, для
+// `propose` при `type Move = Self` и `apply` вида `*self = change`
+pub fn replacement<T: State>(state: &T, energy: f64, rng: &mut impl Rng) -> (T, f64) {
+    let new_state = state.changed_state(rng);
+    let delta = new_state.energy() - energy;
+    (new_state, delta)
+}
+
+// Информация об итерации решения
+#[derive(Debug, Clone)]
+pub struct SolutionStepInfo<T: State + Clone> {
+    // Номер итерации
+    pub index: usize,
+    // Температура на итерации
+    pub temperature: f64,
+    // Финальная энергия
+    pub final_energy: f64,
+    // Кол-во плохих решений
+    pub bad_decisions: i64,
+    // Кол-во хороших решений
+    pub good_decisions: i64,
+    // Конечное состояние в итерации
+    pub final_state: T,
+}
+
+// Информация о решении
+#[derive(Debug, Clone)]
+pub struct SolutionInfo<T: State + Clone> {
+    // Мин температура
+    pub min_temperature: f64,
+    // Макс температура, подобранная при `InitialTemperature::Calibrated`
+    pub max_temperature: f64,
+    // Способ задания макс температуры
+    pub initial_temperature: InitialTemperature,
+    // Кол-во шагов без изменения температуры
+    pub n_steps: i64,
+    // Закон понижения температуры
+    pub schedule: CoolingSchedule,
+    // Повторный нагрев
+    pub reheat: Option<Reheat>,
+    // Кол-во нагревов
+    pub reheats: usize,
+    // Остановка при замерзании
+    pub frozen: Option<Frozen>,
+    // Лучшее состояние за все решение, его энергия и номер итерации, на
+    // которой оно найдено (0 и для начального состояния)
+    pub best_state: T,
+    pub best_energy: f64,
+    pub best_step: usize,
+    // Возврат к лучшему состоянию, если температура закончилась хуже него,
+    // и кол-во возвратов
+    pub return_to_best: bool,
+    pub returns: usize,
+    // Данные о шагах
+    pub steps: Vec<SolutionStepInfo<T>>,
+}
+
+// Имитация отжига
+// Температура понижается по закону `schedule` от `initial_temperature` до
+// `min_temperature` или до замерзания `frozen`, `reheat` - повторный нагрев
+// вместо остановки, `return_to_best` - возврат к лучшему состоянию в конце
+// температуры, закончившейся хуже него
+// Возвращает конечное состояние, лучшее - в `SolutionInfo::best_state`
+// Останавливается раньше по сигналу `control`, застой считается в температурах
+// без улучшения энергии
+#[allow(clippy::too_many_arguments)]
+pub fn sim_ang<T: State + Clone>(
+    init_state: T,
+    min_temperature: f64,
+    initial_temperature: InitialTemperature,
+    schedule: CoolingSchedule,
+    reheat: Option<Reheat>,
+    frozen: Option<Frozen>,
+    return_to_best: bool,
+    n_steps: i64,
+    control: &RunControl,
+    rng: &mut impl Rng,
+) -> (T, SolutionInfo<T>) {
+    // Настроим первоначальное состояние решения
+    let mut state = init_state;
+    state.setup();
+
+    // Без шагов с ухудшением температура не важна
+    let max_temperature = match initial_temperature {
+        InitialTemperature::Fixed(temperature) => temperature,
+        InitialTemperature::Calibrated {
+            acceptance,
+            samples,
+        } => calibrate_temperature(&state, acceptance, samples, rng).unwrap_or(1.0),
+    };
+
+    // Параметры решения
+    let mut temperature = max_temperature;
+    let mut energy = state.energy();
+    let mut solution_info = SolutionInfo {
+        min_temperature,
+        max_temperature,
+        initial_temperature,
+        n_steps,
+        schedule,
+        reheat,
+        reheats: 0,
+        frozen,
+        best_state: state.clone(),
+        best_energy: energy,
+        best_step: 0,
+        return_to_best,
+        returns: 0,
+        steps: vec![],
+    };
+    // Номер итерации
+    let mut step_index = 0;
+    // Кол-во температур без улучшения лучшей энергии
+    let mut since_best = 0;
+    // Текущее состояние лучшее, но еще не скопировано в `best_state`. Копируем
+    // его только перед ухудшением, а не при каждом улучшении
+    let mut best_is_current = false;
+    // Номер температуры от начала или от последнего нагрева и кол-во температур
+    // без улучшения энергии с последнего нагрева
+    let mut k = 0;
+    let mut since_reheat = 0;
+    // Кол-во температур подряд с замерзанием
+    let mut frozen_for = 0;
+
+    while temperature > min_temperature && !control.should_stop(since_best) {
+        // Подсчитываем количество плохих и хороших решений
+        let mut bad_decisions = 0i64;
+        let mut good_decisions = 0i64;
+        let best_before = solution_info.best_energy;
+
+        // n шагов без изменения температуры
+        for _ in 0..n_steps {
+            // Предлагаем ход и разницу энергии
+            let (change, delta_energy) = state.propose(energy, rng);
+            // Новое решение хуже старого
+            if delta_energy > 0.0 {
+                // Оценим вероятность допуска
+                let p = f64::exp(-delta_energy / temperature);
+                let bound_p = rng.gen::<f64>();
+                if p > bound_p {
+                    bad_decisions += 1;
+                    if best_is_current {
+                        solution_info.best_state = state.clone();
+                        best_is_current = false;
+                    }
+                    state.apply(change);
+                    energy += delta_energy;
+                }
+            } else {
+                good_decisions += 1;
+                state.apply(change);
+                energy += delta_energy;
+                if energy < solution_info.best_energy {
+                    solution_info.best_energy = energy;
+                    solution_info.best_step = step_index;
+                    best_is_current = true;
+                }
+            }
+        }
+        let improved = solution_info.best_energy < best_before;
+        // Разницы энергии накапливают ошибку округления, пересчитаем ее полностью
+        energy = state.energy();
+        if best_is_current {
+            solution_info.best_energy = energy;
+        }
+        if improved {
+            since_best = 0;
+            since_reheat = 0;
+        } else {
+            since_best += 1;
+            since_reheat += 1;
+        }
+        let uphill = bad_decisions as f64 / n_steps.max(1) as f64;
+        match frozen {
+            Some(frozen) if !improved && uphill <= frozen.acceptance => frozen_for += 1,
+            _ => frozen_for = 0,
+        }
+        let is_frozen = frozen.is_some_and(|x| frozen_for >= x.temperatures);
+        // Сохраним данные об итерации
+        solution_info.steps.push(SolutionStepInfo {
+            index: step_index,
+            temperature,
+            final_energy: energy,
+            bad_decisions,
+            good_decisions,
+            final_state: state.clone(),
+        });
+        step_index += 1;
+        // Вернемся к лучшему состоянию
+        if return_to_best && energy > solution_info.best_energy {
+            state = solution_info.best_state.clone();
+            energy = solution_info.best_energy;
+            solution_info.returns += 1;
+        }
+        // Понизим температуру
+        k += 1;
+        let acceptance = (good_decisions + bad_decisions) as f64 / n_steps.max(1) as f64;
+        temperature = schedule.next(k, temperature, min_temperature, max_temperature, acceptance);
+        // Нагреем снова, если температура дошла до минимума, решение замерзло
+        // или энергия застряла
+        let mut reheated = false;
+        if let Some(reheat) = reheat {
+            let stuck = reheat.after > 0 && since_reheat >= reheat.after;
+            if (temperature <= min_temperature || is_frozen || stuck)
+                && solution_info.reheats < reheat.times
+            {
+                temperature = max_temperature * reheat.fraction;
+                solution_info.reheats += 1;
+                reheated = true;
+                k = 0;
+                since_reheat = 0;
+                frozen_for = 0;
+            }
+        }
+        if is_frozen && !reheated {
+            break;
+        }
+    }
+    if best_is_current {
+        solution_info.best_state = state.clone();
+    }
+    (state, solution_info)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::tsp::DistanceMatrix;
+    use bin_packing::BinPackingState;
+    use colouring::ColouringState;
+    use function::{Function, FunctionState};
+    use queens::QueenState;
+    use sudoku::SudokuState;
+    use tour::TourState;
+
+    const SUDOKU: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    // Разница энергии хода равна разнице полных энергий до и после него
+    fn check_deltas<T: State + Clone>(name: &str, mut state: T, rng: &mut StdRng) {
+        state.setup();
+        for _ in 0..500 {
+            let before = state.energy();
+            let (change, delta) = state.propose(before, rng);
+            state.apply(change);
+            let after = state.energy();
+            assert!(
+                (after - before - delta).abs() < 1e-6,
+                "{}: {} - {} != {}",
+                name,
+                after,
+                before,
+                delta
+            );
+        }
+    }
+
+    #[test]
+    fn deltas_match_energies() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let points = (0..12)
+            .map(|_| (rng.gen_range(0.0..100.0), rng.gen_range(0.0..100.0)))
+            .collect::<Vec<(f32, f32)>>();
+        let distances = DistanceMatrix::from_fn(points.len(), |a, b| {
+            let (x, y) = (points[a].0 - points[b].0, points[a].1 - points[b].1);
+            (x * x + y * y).sqrt()
+        });
+        let edges = (0..20)
+            .flat_map(|a| (a + 1..20).map(move |b| (a, b)))
+            .filter(|_| rng.gen_bool(0.3))
+            .collect::<Vec<_>>();
+        let sizes = (0..30).map(|_| rng.gen_range(0.05..0.6)).collect();
+
+        check_deltas("tour", TourState::new(Arc::new(distances)), &mut rng);
+        for n in [1, 2, 4, 8, 30] {
+            check_deltas("queens", QueenState::new(n), &mut rng);
+        }
+        let neighbours = ColouringState::neighbour_lists(20, &edges);
+        check_deltas(
+            "colouring",
+            ColouringState::new(Arc::new(neighbours), 3),
+            &mut rng,
+        );
+        check_deltas(
+            "bin packing",
+            BinPackingState::new(Arc::new(sizes), 1.0),
+            &mut rng,
+        );
+        check_deltas("sudoku", SudokuState::parse(SUDOKU).unwrap(), &mut rng);
+        for function in [Function::Rastrigin, Function::Rosenbrock] {
+            check_deltas("function", FunctionState::new(function, 5, 0.5), &mut rng);
+        }
+    }
+
+    // Точка на прямой с неровной энергией, запоминает энергии всех состояний
+    #[derive(Debug, Clone)]
+    struct Walk {
+        x: i64,
+        seen: Rc<RefCell<Vec<f64>>>,
+    }
+
+    impl State for Walk {
+        type Move = i64;
+        fn setup(&mut self) {
+            self.x = 40;
+            self.seen.borrow_mut().push(self.energy());
+        }
+        fn energy(&self) -> f64 {
+            (self.x * self.x % 37) as f64 + self.x.abs() as f64 / 10.0
+        }
+        fn changed_state(&self, rng: &mut impl Rng) -> Self {
+            let mut state = self.clone();
+            state.x = self.step(rng);
+            state
+        }
+        fn propose(&self, energy: f64, rng: &mut impl Rng) -> (i64, f64) {
+            let x = self.step(rng);
+            let next = Walk {
+                x,
+                seen: self.seen.clone(),
+            };
+            (x, next.energy() - energy)
+        }
+        fn apply(&mut self, x: i64) {
+            self.x = x;
+            self.seen.borrow_mut().push(self.energy());
+        }
+    }
+
+    impl Walk {
+        fn step(&self, rng: &mut impl Rng) -> i64 {
+            (self.x + rng.gen_range(-3..=3)).clamp(-50, 50)
+        }
+    }
+
+    fn walk(return_to_best: bool) -> (Walk, SolutionInfo<Walk>, Vec<f64>) {
+        let seen = Rc::new(RefCell::new(vec![]));
+        let (state, info) = sim_ang(
+            Walk {
+                x: 0,
+                seen: seen.clone(),
+            },
+            0.1,
+            InitialTemperature::Fixed(20.0),
+            CoolingSchedule::default(),
+            None,
+            None,
+            return_to_best,
+            50,
+            &RunControl::new(),
+            &mut StdRng::seed_from_u64(0),
+        );
+        let seen = seen.borrow().clone();
+        (state, info, seen)
+    }
+
+    #[test]
+    fn best_state_has_the_lowest_energy_seen() {
+        for return_to_best in [false, true] {
+            let (_, info, seen) = walk(return_to_best);
+            let lowest = seen.iter().copied().fold(f64::INFINITY, f64::min);
+            assert_eq!(info.best_energy, lowest);
+            assert_eq!(info.best_state.energy(), lowest);
+        }
+    }
+
+    #[test]
+    fn return_to_best_restores_the_best_state() {
+        let (state, info, _) = walk(true);
+        assert!(info.returns > 0);
+        // Последняя температура кончается возвратом к лучшему решению, если
+        // не нашла его сама
+        assert_eq!(state.x, info.best_state.x);
+        assert_eq!(state.energy(), info.best_energy);
+        let (_, info, _) = walk(false);
+        assert_eq!(info.returns, 0);
+    }
+}
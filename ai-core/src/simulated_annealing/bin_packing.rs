@@ -0,0 +1,157 @@
+// Упаковка в контейнеры
+
+use std::sync::Arc;
+
+use rand::Rng;
+
+use super::State;
+
+// Предметы размеров `sizes` в контейнерах вместимости `capacity`,
+// bins[i] - контейнер предмета i. Контейнеры никогда не переполняются
+#[derive(Debug, Clone)]
+pub struct BinPackingState {
+    pub bins: Vec<usize>,
+    pub sizes: Arc<Vec<f64>>,
+    pub capacity: f64,
+    // Заполненность и кол-во предметов каждого контейнера
+    loads: Vec<f64>,
+    counts: Vec<usize>,
+}
+
+// Ход: перенос предмета в другой контейнер или обмен двух предметов
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Packing {
+    Keep,
+    Put(usize, usize),
+    Exchange(usize, usize),
+}
+
+impl BinPackingState {
+    pub fn new(sizes: Arc<Vec<f64>>, capacity: f64) -> Self {
+        Self {
+            bins: vec![],
+            sizes,
+            capacity,
+            loads: vec![],
+            counts: vec![],
+        }
+    }
+    // Кол-во непустых контейнеров
+    pub fn used_bins(&self) -> usize {
+        self.counts.iter().filter(|x| **x > 0).count()
+    }
+    // Вклад контейнера в энергию: чем полнее, тем меньше
+    fn cost(&self, load: f64, count: usize) -> f64 {
+        if count == 0 {
+            0.0
+        } else {
+            1.0 - (load / self.capacity).powi(2)
+        }
+    }
+    fn fits(&self, load: f64) -> bool {
+        load <= self.capacity * (1.0 + 1e-9)
+    }
+    // Переносим предмет в контейнер другого случайного предмета, если он туда
+    // помещается, иначе меняем их местами
+    fn packing(&self, rng: &mut impl Rng) -> (Packing, f64) {
+        let i = rng.gen_range(0..self.bins.len());
+        let j = rng.gen_range(0..self.bins.len());
+        let (a, b) = (self.bins[i], self.bins[j]);
+        let (size_i, size_j) = (self.sizes[i], self.sizes[j]);
+        let before =
+            self.cost(self.loads[a], self.counts[a]) + self.cost(self.loads[b], self.counts[b]);
+        if a == b {
+            (Packing::Keep, 0.0)
+        } else if self.fits(self.loads[b] + size_i) {
+            let after = self.cost(self.loads[a] - size_i, self.counts[a] - 1)
+                + self.cost(self.loads[b] + size_i, self.counts[b] + 1);
+            (Packing::Put(i, b), after - before)
+        } else if self.fits(self.loads[b] - size_j + size_i)
+            && self.fits(self.loads[a] - size_i + size_j)
+        {
+            let after = self.cost(self.loads[a] - size_i + size_j, self.counts[a])
+                + self.cost(self.loads[b] - size_j + size_i, self.counts[b]);
+            (Packing::Exchange(i, j), after - before)
+        } else {
+            (Packing::Keep, 0.0)
+        }
+    }
+    // Перенос предмета в контейнер
+    fn put(&mut self, item: usize, bin: usize) {
+        let from = self.bins[item];
+        self.loads[from] -= self.sizes[item];
+        self.counts[from] -= 1;
+        self.loads[bin] += self.sizes[item];
+        self.counts[bin] += 1;
+        self.bins[item] = bin;
+    }
+    fn pack(&mut self, packing: Packing) {
+        match packing {
+            Packing::Keep => (),
+            Packing::Put(item, bin) => self.put(item, bin),
+            Packing::Exchange(first, second) => {
+                let (a, b) = (self.bins[first], self.bins[second]);
+                self.put(first, b);
+                self.put(second, a);
+            }
+        }
+    }
+}
+
+impl State for BinPackingState {
+    type Move = Packing;
+    fn changed_state(&self, rng: &mut impl Rng) -> Self {
+        let mut state = self.clone();
+        state.pack(self.packing(rng).0);
+        state
+    }
+    // Сумма по непустым контейнерам 1 - заполненность², так энергия меньше и
+    // при меньшем кол-ве контейнеров, и при более полных контейнерах
+    fn energy(&self) -> f64 {
+        let mut loads = vec![0.0; self.loads.len()];
+        for (item, bin) in self.bins.iter().enumerate() {
+            loads[*bin] += self.sizes[item];
+        }
+        loads
+            .iter()
+            .zip(&self.counts)
+            .map(|(load, count)| self.cost(*load, *count))
+            .sum()
+    }
+    // Ход меняет только два контейнера
+    fn propose(&self, _energy: f64, rng: &mut impl Rng) -> (Packing, f64) {
+        self.packing(rng)
+    }
+    fn apply(&mut self, packing: Packing) {
+        self.pack(packing);
+    }
+    // Каждый предмет в своем контейнере
+    fn setup(&mut self) {
+        self.bins = (0..self.sizes.len()).collect();
+        self.loads = self.sizes.to_vec();
+        self.counts = vec![1; self.sizes.len()];
+    }
+}
+
+impl std::fmt::Display for BinPackingState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Bins used: {}", self.used_bins())?;
+        for bin in 0..self.counts.len() {
+            if self.counts[bin] == 0 {
+                continue;
+            }
+            let items = (0..self.bins.len())
+                .filter(|x| self.bins[*x] == bin)
+                .map(|x| format!("{:.2}", self.sizes[x]))
+                .collect::<Vec<_>>();
+            writeln!(
+                f,
+                "{:.2}/{:.2}: {}",
+                self.loads[bin],
+                self.capacity,
+                items.join(" ")
+            )?;
+        }
+        Ok(())
+    }
+}
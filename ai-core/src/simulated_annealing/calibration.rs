@@ -0,0 +1,193 @@
+// Подбор начальной температуры и остановка при замерзании
+
+use rand::Rng;
+
+use super::State;
+
+// Начальная (макс) температура
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InitialTemperature {
+    // Заданная температура
+    Fixed(f64),
+    // Температура, при которой принимается доля `acceptance` ухудшений среди
+    // `samples` случайных шагов, см. `calibrate_temperature`
+    Calibrated { acceptance: f64, samples: usize },
+}
+
+impl Default for InitialTemperature {
+    fn default() -> Self {
+        Self::Calibrated {
+            acceptance: 0.8,
+            samples: 1000,
+        }
+    }
+}
+
+// Температура, при которой средняя вероятность принять ухудшение среди шагов
+// случайного блуждания из `state` равна `acceptance`.
+// Блуждание принимает каждый шаг, чтобы не застрять в начальном состоянии.
+// None, если ни один шаг не ухудшил энергию. `acceptance` должна быть в (0, 1)
+pub fn calibrate_temperature<T: State + Clone>(
+    state: &T,
+    acceptance: f64,
+    samples: usize,
+    rng: &mut impl Rng,
+) -> Option<f64> {
+    assert!(
+        acceptance > 0.0 && acceptance < 1.0,
+        "acceptance {} is not in (0, 1)",
+        acceptance
+    );
+    // Разницы энергии шагов с ухудшением
+    let mut deltas = vec![];
+    let mut current = state.clone();
+    let mut energy = current.energy();
+    for _ in 0..samples {
+        let (change, delta) = current.propose(energy, rng);
+        if delta > 0.0 {
+            deltas.push(delta);
+        }
+        energy += delta;
+        current.apply(change);
+    }
+    if deltas.is_empty() {
+        return None;
+    }
+
+    // Доля принятых ухудшений растет вместе с температурой, ищем ее делением пополам
+    let accepted = |temperature: f64| {
+        deltas
+            .iter()
+            .map(|x| f64::exp(-x / temperature))
+            .sum::<f64>()
+            / deltas.len() as f64
+    };
+    let mut low = 0.0;
+    let mut high = deltas.iter().copied().fold(f64::MIN_POSITIVE, f64::max);
+    while accepted(high) < acceptance {
+        low = high;
+        high *= 2.0;
+    }
+    for _ in 0..100 {
+        let middle = (low + high) / 2.0;
+        if accepted(middle) < acceptance {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    Some(high)
+}
+
+// Замерзание: `temperatures` температур подряд принята доля ухудшений не больше
+// `acceptance`, и энергия не улучшилась
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frozen {
+    pub acceptance: f64,
+    pub temperatures: usize,
+}
+
+impl Default for Frozen {
+    fn default() -> Self {
+        Self {
+            acceptance: 0.02,
+            temperatures: 5,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::simulated_annealing::tour::TourState;
+    use crate::tsp::DistanceMatrix;
+
+    // Шаг вверх или вниз на 1
+    #[derive(Debug, Clone)]
+    struct Stairs(i64);
+
+    impl State for Stairs {
+        type Move = i64;
+        fn setup(&mut self) {}
+        fn energy(&self) -> f64 {
+            self.0 as f64
+        }
+        fn changed_state(&self, rng: &mut impl Rng) -> Self {
+            Stairs(self.0 + self.propose(0.0, rng).0)
+        }
+        fn propose(&self, _energy: f64, rng: &mut impl Rng) -> (i64, f64) {
+            let step = if rng.gen() { 1 } else { -1 };
+            (step, step as f64)
+        }
+        fn apply(&mut self, step: i64) {
+            self.0 += step;
+        }
+    }
+
+    #[test]
+    fn known_acceptance() {
+        // Все ухудшения равны 1, поэтому exp(-1 / t) = acceptance
+        let mut rng = StdRng::seed_from_u64(0);
+        for acceptance in [0.01, 0.3, 0.5, 0.8, 0.99] {
+            let t = calibrate_temperature(&Stairs(0), acceptance, 100, &mut rng).unwrap();
+            assert!(
+                (f64::exp(-1.0 / t) - acceptance).abs() < 1e-9,
+                "{}",
+                acceptance
+            );
+        }
+    }
+
+    #[test]
+    fn tour_acceptance_is_roughly_requested() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let points = (0..30)
+            .map(|_| (rng.gen_range(0.0..100.0), rng.gen_range(0.0..100.0)))
+            .collect::<Vec<(f32, f32)>>();
+        let distances = DistanceMatrix::from_fn(points.len(), |a, b| {
+            let (x, y) = (points[a].0 - points[b].0, points[a].1 - points[b].1);
+            (x * x + y * y).sqrt()
+        });
+        let mut state = TourState::new(Arc::new(distances));
+        state.setup();
+        for acceptance in [0.2, 0.5, 0.8] {
+            let t = calibrate_temperature(&state, acceptance, 2000, &mut rng).unwrap();
+            // Доля принятых ухудшений на другом блуждании
+            let (mut current, mut accepted) = (state.clone(), vec![]);
+            let mut energy = current.energy();
+            for _ in 0..2000 {
+                let (change, delta) = current.propose(energy, &mut rng);
+                if delta > 0.0 {
+                    accepted.push(f64::exp(-delta / t));
+                }
+                current.apply(change);
+                energy += delta;
+            }
+            let share = accepted.iter().sum::<f64>() / accepted.len() as f64;
+            assert!(
+                (share - acceptance).abs() < 0.05,
+                "{} != {}",
+                share,
+                acceptance
+            );
+        }
+    }
+
+    #[test]
+    fn no_uphill_steps() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut state = TourState::new(Arc::new(DistanceMatrix::from_fn(3, |_, _| 1.0)));
+        state.setup();
+        assert_eq!(calibrate_temperature(&state, 0.5, 100, &mut rng), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "not in (0, 1)")]
+    fn invalid_acceptance() {
+        calibrate_temperature(&Stairs(0), 1.0, 100, &mut StdRng::seed_from_u64(0));
+    }
+}
@@ -0,0 +1,93 @@
+// Раскраска графа
+
+use std::sync::Arc;
+
+use rand::Rng;
+
+use super::State;
+
+// Раскраска вершин графа `neighbours` в `colours` цветов
+#[derive(Debug, Clone)]
+pub struct ColouringState {
+    pub colouring: Vec<usize>,
+    pub colours: usize,
+    // Списки соседей каждой вершины
+    pub neighbours: Arc<Vec<Vec<usize>>>,
+}
+
+impl ColouringState {
+    pub fn new(neighbours: Arc<Vec<Vec<usize>>>, colours: usize) -> Self {
+        Self {
+            colouring: vec![],
+            colours,
+            neighbours,
+        }
+    }
+    // Списки соседей графа из `vertices` вершин и ребер `edges`
+    pub fn neighbour_lists(vertices: usize, edges: &[(usize, usize)]) -> Vec<Vec<usize>> {
+        let mut neighbours = vec![vec![]; vertices];
+        for (a, b) in edges {
+            neighbours[*a].push(*b);
+            neighbours[*b].push(*a);
+        }
+        neighbours
+    }
+    // Кол-во соседей вершины цвета `colour`
+    fn conflicts(&self, vertex: usize, colour: usize) -> usize {
+        self.neighbours[vertex]
+            .iter()
+            .filter(|x| self.colouring[**x] == colour)
+            .count()
+    }
+    // Случайная вершина и другой цвет для нее
+    fn recolouring(&self, rng: &mut impl Rng) -> (usize, usize) {
+        let vertex = rng.gen_range(0..self.colouring.len());
+        let colour =
+            (self.colouring[vertex] + rng.gen_range(1..self.colours.max(2))) % self.colours.max(1);
+        (vertex, colour)
+    }
+}
+
+impl State for ColouringState {
+    // Вершина и ее новый цвет
+    type Move = (usize, usize);
+    // Перекрашиваем случайную вершину
+    fn changed_state(&self, rng: &mut impl Rng) -> Self {
+        let mut state = self.clone();
+        let (vertex, colour) = self.recolouring(rng);
+        state.colouring[vertex] = colour;
+        state
+    }
+    // Кол-во ребер с одинаковыми цветами концов
+    fn energy(&self) -> f64 {
+        let conflicts = (0..self.colouring.len())
+            .map(|x| self.conflicts(x, self.colouring[x]))
+            .sum::<usize>();
+        (conflicts / 2) as f64
+    }
+    // Перекраска меняет только ребра вершины
+    fn propose(&self, _energy: f64, rng: &mut impl Rng) -> ((usize, usize), f64) {
+        let (vertex, colour) = self.recolouring(rng);
+        let delta = self.conflicts(vertex, colour) as f64
+            - self.conflicts(vertex, self.colouring[vertex]) as f64;
+        ((vertex, colour), delta)
+    }
+    fn apply(&mut self, (vertex, colour): (usize, usize)) {
+        self.colouring[vertex] = colour;
+    }
+    // Цвета по кругу
+    fn setup(&mut self) {
+        self.colouring = (0..self.neighbours.len())
+            .map(|x| x % self.colours.max(1))
+            .collect();
+    }
+}
+
+impl std::fmt::Display for ColouringState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (vertex, colour) in self.colouring.iter().enumerate() {
+            writeln!(f, "{}: {}", vertex, colour)?;
+        }
+        Ok(())
+    }
+}
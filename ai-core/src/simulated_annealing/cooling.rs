@@ -0,0 +1,152 @@
+// Законы понижения температуры и повторный нагрев
+
+// Закон понижения температуры, k - номер температуры от начала или от
+// последнего нагрева, T0 - макс температура, Tmin - мин температура
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CoolingSchedule {
+    // T = T * alpha
+    Geometric { alpha: f64 },
+    // T = T - step
+    Linear { step: f64 },
+    // Geman–Geman: T = c / ln(1 + k), но не выше T0
+    Logarithmic { c: f64 },
+    // Lundy–Mees: T = T / (1 + beta * T)
+    LundyMees { beta: f64 },
+    // T = Tmin + (T0 - Tmin) / (1 + exp(2 ln(T0 - Tmin) / n * (k - n / 2))),
+    // доходит до Tmin за n = cycles температур
+    ExponentialAdditive { cycles: usize },
+    // T = T * alpha^(r / target), r - доля принятых решений на температуре:
+    // чем больше решений принято, тем быстрее остывание
+    Adaptive { alpha: f64, target: f64 },
+}
+
+impl Default for CoolingSchedule {
+    fn default() -> Self {
+        Self::Geometric { alpha: 0.98 }
+    }
+}
+
+// Мин значение параметров step, c, beta и target: при неположительных
+// температура не понижается и отжиг не заканчивается
+const MIN_PARAMETER: f64 = 1e-9;
+
+impl CoolingSchedule {
+    // Следующая температура после температуры номер k - 1
+    pub fn next(
+        &self,
+        k: usize,
+        temperature: f64,
+        min_temperature: f64,
+        max_temperature: f64,
+        acceptance: f64,
+    ) -> f64 {
+        match *self {
+            Self::Geometric { alpha } => temperature * alpha,
+            Self::Linear { step } => temperature - step.max(MIN_PARAMETER),
+            Self::Logarithmic { c } => {
+                (c.max(MIN_PARAMETER) / (1.0 + k as f64).ln()).min(max_temperature)
+            }
+            Self::LundyMees { beta } => temperature / (1.0 + beta.max(MIN_PARAMETER) * temperature),
+            Self::ExponentialAdditive { cycles } => {
+                if k >= cycles {
+                    return min_temperature;
+                }
+                let range = max_temperature - min_temperature;
+                // При малом разбросе температур ln(range) не положителен
+                let rate = 2.0 * range.ln().max(1.0) / cycles as f64;
+                min_temperature + range / (1.0 + f64::exp(rate * (k as f64 - cycles as f64 / 2.0)))
+            }
+            // Степень ограничена, чтобы температура понижалась и без принятых решений
+            Self::Adaptive { alpha, target } => {
+                temperature * alpha.powf((acceptance / target.max(MIN_PARAMETER)).clamp(0.5, 2.0))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for CoolingSchedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Geometric { .. } => "Geometric",
+            Self::Linear { .. } => "Linear",
+            Self::Logarithmic { .. } => "Logarithmic",
+            Self::LundyMees { .. } => "Lundy–Mees",
+            Self::ExponentialAdditive { .. } => "Exponential additive",
+            Self::Adaptive { .. } => "Adaptive",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Повторный нагрев до доли `fraction` макс температуры, не больше `times` раз.
+// Нагрев происходит после `after` температур без улучшения энергии
+// (0 - никогда) или по достижении мин температуры. fraction = 1 - перезапуск
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reheat {
+    pub after: usize,
+    pub fraction: f64,
+    pub times: usize,
+}
+
+impl Default for Reheat {
+    fn default() -> Self {
+        Self {
+            after: 0,
+            fraction: 1.0,
+            times: 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Кол-во температур до мин температуры, не больше limit
+    fn temperatures(schedule: CoolingSchedule, limit: usize) -> usize {
+        let (min, max) = (0.01, 100.0);
+        let mut temperature = max;
+        let mut k = 1;
+        while temperature > min && k < limit {
+            temperature = schedule.next(k, temperature, min, max, 0.0);
+            assert!(!temperature.is_nan());
+            k += 1;
+        }
+        k
+    }
+
+    #[test]
+    fn non_positive_parameters_still_cool() {
+        for x in [0.0, -1.0] {
+            for schedule in [
+                CoolingSchedule::Linear { step: x },
+                CoolingSchedule::Logarithmic { c: x },
+                CoolingSchedule::LundyMees { beta: x },
+                CoolingSchedule::Adaptive {
+                    alpha: 0.9,
+                    target: x,
+                },
+            ] {
+                let first = schedule.next(1, 100.0, 0.01, 100.0, 0.0);
+                assert!(first < 100.0, "{} with {}", schedule, x);
+            }
+        }
+    }
+
+    #[test]
+    fn schedules_reach_min_temperature() {
+        for schedule in [
+            CoolingSchedule::default(),
+            CoolingSchedule::Linear { step: 1.0 },
+            CoolingSchedule::Logarithmic { c: 1e-3 },
+            CoolingSchedule::LundyMees { beta: 0.01 },
+            CoolingSchedule::ExponentialAdditive { cycles: 100 },
+            CoolingSchedule::Adaptive {
+                alpha: 0.9,
+                target: 0.5,
+            },
+        ] {
+            assert!(temperatures(schedule, 100_000) < 100_000, "{}", schedule);
+        }
+    }
+}
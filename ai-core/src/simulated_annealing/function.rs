@@ -0,0 +1,134 @@
+// Минимизация функций многих переменных
+
+use std::f64::consts::PI;
+
+use rand::Rng;
+
+use super::State;
+
+// Тестовая функция, минимум у обеих 0
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Function {
+    // 10n + Σ x² - 10cos(2πx), минимум в 0
+    #[default]
+    Rastrigin,
+    // Σ 100(x[i+1] - x[i]²)² + (1 - x[i])², минимум в 1
+    Rosenbrock,
+}
+
+impl Function {
+    // Область поиска по каждой координате
+    pub fn bounds(&self) -> (f64, f64) {
+        match self {
+            Self::Rastrigin => (-5.12, 5.12),
+            Self::Rosenbrock => (-2.048, 2.048),
+        }
+    }
+    // Слагаемые функции, в которые входит координата i, равная `value`
+    fn terms(&self, x: &[f64], i: usize, value: f64) -> f64 {
+        let at = |j: usize| if j == i { value } else { x[j] };
+        match self {
+            Self::Rastrigin => 10.0 + value * value - 10.0 * (2.0 * PI * value).cos(),
+            Self::Rosenbrock => {
+                let term =
+                    |j: usize| 100.0 * (at(j + 1) - at(j) * at(j)).powi(2) + (1.0 - at(j)).powi(2);
+                let mut sum = 0.0;
+                if i > 0 {
+                    sum += term(i - 1);
+                }
+                if i + 1 < x.len() {
+                    sum += term(i);
+                }
+                sum
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Rastrigin => "Rastrigin",
+            Self::Rosenbrock => "Rosenbrock",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Точка x, шаг меняет одну координату не больше чем на `step`
+#[derive(Debug, Clone)]
+pub struct FunctionState {
+    pub x: Vec<f64>,
+    pub function: Function,
+    pub dimensions: usize,
+    pub step: f64,
+}
+
+impl FunctionState {
+    pub fn new(function: Function, dimensions: usize, step: f64) -> Self {
+        Self {
+            x: vec![],
+            function,
+            dimensions,
+            step,
+        }
+    }
+    // Случайная координата и ее новое значение в пределах области
+    fn shift(&self, rng: &mut impl Rng) -> (usize, f64) {
+        let (low, high) = self.function.bounds();
+        let i = rng.gen_range(0..self.x.len());
+        let value = (self.x[i] + rng.gen_range(-self.step..=self.step)).clamp(low, high);
+        (i, value)
+    }
+}
+
+impl State for FunctionState {
+    // Координата и ее новое значение
+    type Move = (usize, f64);
+    fn changed_state(&self, rng: &mut impl Rng) -> Self {
+        let mut state = self.clone();
+        let (i, value) = self.shift(rng);
+        state.x[i] = value;
+        state
+    }
+    // Значение функции
+    fn energy(&self) -> f64 {
+        match self.function {
+            Function::Rastrigin => (0..self.x.len())
+                .map(|i| self.function.terms(&self.x, i, self.x[i]))
+                .sum(),
+            // Каждое слагаемое входит в terms двух соседних координат
+            Function::Rosenbrock => {
+                (0..self.x.len())
+                    .map(|i| self.function.terms(&self.x, i, self.x[i]))
+                    .sum::<f64>()
+                    / 2.0
+            }
+        }
+    }
+    // Сдвиг координаты меняет только слагаемые с ней
+    fn propose(&self, _energy: f64, rng: &mut impl Rng) -> ((usize, f64), f64) {
+        let (i, value) = self.shift(rng);
+        let delta =
+            self.function.terms(&self.x, i, value) - self.function.terms(&self.x, i, self.x[i]);
+        ((i, value), delta)
+    }
+    fn apply(&mut self, (i, value): (usize, f64)) {
+        self.x[i] = value;
+    }
+    // Угол области поиска
+    fn setup(&mut self) {
+        self.x = vec![self.function.bounds().0 * 0.9; self.dimensions];
+    }
+}
+
+impl std::fmt::Display for FunctionState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let x = self
+            .x
+            .iter()
+            .map(|x| format!("{:.4}", x))
+            .collect::<Vec<_>>();
+        write!(f, "{}({})", self.function, x.join(", "))
+    }
+}
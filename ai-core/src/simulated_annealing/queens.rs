@@ -0,0 +1,248 @@
+// Задача о королевах
+
+use rand::Rng;
+
+use super::State;
+
+// Состояние задачи о королевах: в строке i королева стоит в столбце positions[i]
+#[derive(Debug, Clone)]
+pub struct QueenState {
+    positions: Vec<usize>,
+    pub n: usize,
+    // Кол-во королев на каждой диагонали и побочной диагонали
+    diagonals: Vec<usize>,
+    anti_diagonals: Vec<usize>,
+}
+
+impl QueenState {
+    pub fn new(n: usize) -> Self {
+        Self {
+            positions: vec![],
+            n,
+            diagonals: vec![],
+            anti_diagonals: vec![],
+        }
+    }
+    // Столбцы королев по строкам. Только для чтения: кол-во королев на
+    // диагоналях меняется вместе с ними
+    pub fn positions(&self) -> &[usize] {
+        &self.positions
+    }
+    // Расстановка королев по столбцам `positions` с пересчетом диагоналей
+    fn place(&mut self, positions: Vec<usize>) {
+        self.n = positions.len();
+        self.positions = positions;
+        self.diagonals = vec![0; (2 * self.n).saturating_sub(1)];
+        self.anti_diagonals = vec![0; (2 * self.n).saturating_sub(1)];
+        for row in 0..self.n {
+            let column = self.positions[row];
+            let (d, a) = (self.diagonal(row, column), self.anti_diagonal(row, column));
+            self.diagonals[d] += 1;
+            self.anti_diagonals[a] += 1;
+        }
+    }
+    fn diagonal(&self, row: usize, column: usize) -> usize {
+        row + column
+    }
+    fn anti_diagonal(&self, row: usize, column: usize) -> usize {
+        row + self.n - 1 - column
+    }
+    // Перестановка королев строк first и second с учетом диагоналей
+    fn swap(&mut self, first: usize, second: usize) {
+        // Иначе королева снимается с диагоналей дважды
+        if first == second {
+            return;
+        }
+        for row in [first, second] {
+            let column = self.positions[row];
+            let (d, a) = (self.diagonal(row, column), self.anti_diagonal(row, column));
+            self.diagonals[d] -= 1;
+            self.anti_diagonals[a] -= 1;
+        }
+        self.positions.swap(first, second);
+        for row in [first, second] {
+            let column = self.positions[row];
+            let (d, a) = (self.diagonal(row, column), self.anti_diagonal(row, column));
+            self.diagonals[d] += 1;
+            self.anti_diagonals[a] += 1;
+        }
+    }
+}
+
+// Изменение кол-ва пар на диагоналях `counts` после снятия королев с диагоналей
+// `removed` и постановки на `added`
+fn pairs_delta(counts: &[usize], removed: [usize; 2], added: [usize; 2]) -> f64 {
+    let mut changed: Vec<(usize, i64)> = vec![];
+    let mut count = |diagonal: usize, change: i64| {
+        let i = match changed.iter().position(|x| x.0 == diagonal) {
+            Some(i) => i,
+            None => {
+                changed.push((diagonal, counts[diagonal] as i64));
+                changed.len() - 1
+            }
+        };
+        // Пар на диагонали было c * (c - 1) / 2
+        let before = changed[i].1;
+        changed[i].1 += change;
+        if change > 0 {
+            before
+        } else {
+            -(before - 1)
+        }
+    };
+    let mut delta = 0;
+    for x in removed {
+        delta += count(x, -1);
+    }
+    for x in added {
+        delta += count(x, 1);
+    }
+    delta as f64
+}
+
+impl State for QueenState {
+    // Строки переставляемых королев
+    type Move = (usize, usize);
+    // Переставляем двух королев местами
+    fn changed_state(&self, rng: &mut impl Rng) -> Self {
+        let mut state = self.clone();
+        let first_index = rng.gen_range(0..self.positions.len());
+        let second_index = rng.gen_range(0..self.positions.len());
+        state.swap(first_index, second_index);
+        state
+    }
+    // Расчет энергии: кол-во пар королев на одной диагонали
+    fn energy(&self) -> f64 {
+        self.diagonals
+            .iter()
+            .chain(&self.anti_diagonals)
+            .map(|x| x * x.saturating_sub(1) / 2)
+            .sum::<usize>() as f64
+    }
+    // Перестановка меняет только четыре диагонали каждого направления
+    fn propose(&self, _energy: f64, rng: &mut impl Rng) -> ((usize, usize), f64) {
+        let first_index = rng.gen_range(0..self.positions.len());
+        let second_index = rng.gen_range(0..self.positions.len());
+        let (a, b) = (self.positions[first_index], self.positions[second_index]);
+        let delta = if first_index == second_index {
+            0.0
+        } else {
+            pairs_delta(
+                &self.diagonals,
+                [
+                    self.diagonal(first_index, a),
+                    self.diagonal(second_index, b),
+                ],
+                [
+                    self.diagonal(first_index, b),
+                    self.diagonal(second_index, a),
+                ],
+            ) + pairs_delta(
+                &self.anti_diagonals,
+                [
+                    self.anti_diagonal(first_index, a),
+                    self.anti_diagonal(second_index, b),
+                ],
+                [
+                    self.anti_diagonal(first_index, b),
+                    self.anti_diagonal(second_index, a),
+                ],
+            )
+        };
+        ((first_index, second_index), delta)
+    }
+    fn apply(&mut self, (first, second): (usize, usize)) {
+        self.swap(first, second);
+    }
+    // Такая генерация гарантирует отсутствие повторений в столбцах
+    fn setup(&mut self) {
+        self.place((0..self.n).collect());
+    }
+}
+
+impl std::fmt::Display for QueenState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for column in &self.positions {
+            writeln!(
+                f,
+                "{}",
+                (0..self.n)
+                    .map(|x| if x == *column { 'Q' } else { '.' })
+                    .collect::<String>()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+    use super::*;
+
+    fn board(positions: &[usize]) -> QueenState {
+        let mut state = QueenState::new(positions.len());
+        state.place(positions.to_vec());
+        state
+    }
+
+    // Перебор всех пар королев
+    fn attacking_pairs(positions: &[usize]) -> f64 {
+        let mut pairs = 0;
+        for i in 0..positions.len() {
+            for j in i + 1..positions.len() {
+                if positions[i].abs_diff(positions[j]) == j - i {
+                    pairs += 1;
+                }
+            }
+        }
+        pairs as f64
+    }
+
+    #[test]
+    fn energy_counts_attacking_pairs() {
+        // Все королевы на одной диагонали, по три на диагоналях обоих
+        // направлений и расстановка без пар
+        for positions in [
+            vec![0, 1, 2, 3, 4],
+            vec![4, 3, 2, 1, 0],
+            vec![0, 1, 2, 5, 4, 3],
+            vec![1, 3, 0, 2],
+            vec![0],
+            vec![],
+        ] {
+            assert_eq!(
+                board(&positions).energy(),
+                attacking_pairs(&positions),
+                "{:?}",
+                positions
+            );
+        }
+        let mut rng = StdRng::seed_from_u64(0);
+        for n in [2, 3, 5, 8, 13] {
+            for _ in 0..50 {
+                let mut positions = (0..n).collect::<Vec<_>>();
+                positions.shuffle(&mut rng);
+                assert_eq!(board(&positions).energy(), attacking_pairs(&positions));
+            }
+        }
+    }
+
+    #[test]
+    fn delta_matches_recomputed_energy() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for n in [1, 2, 3, 6, 10] {
+            let mut state = QueenState::new(n);
+            state.setup();
+            for _ in 0..300 {
+                let before = state.energy();
+                let (change, delta) = state.propose(before, &mut rng);
+                state.apply(change);
+                let after = attacking_pairs(state.positions());
+                assert_eq!(state.energy(), after, "{:?}", state.positions());
+                assert_eq!(after - before, delta, "{:?}", state.positions());
+            }
+        }
+    }
+}
@@ -0,0 +1,167 @@
+// Судоку
+
+use std::sync::Arc;
+
+use rand::Rng;
+
+use super::State;
+
+// Сетка судоку 9x9 по строкам, 0 - пустая клетка в `puzzle`.
+// В каждом квадрате 3x3 всегда стоят разные цифры, конфликты бывают только в
+// строках и столбцах
+#[derive(Debug, Clone)]
+pub struct SudokuState {
+    pub grid: Vec<u8>,
+    pub puzzle: Arc<Vec<u8>>,
+    // Свободные клетки каждого квадрата, где их хотя бы две
+    free: Arc<Vec<Vec<usize>>>,
+}
+
+impl SudokuState {
+    // Судоку из 81 цифры по строкам, пустые клетки - 0 или '.', остальные
+    // символы пропускаются
+    pub fn parse(puzzle: &str) -> Option<Self> {
+        let puzzle = puzzle
+            .chars()
+            .filter_map(|x| match x {
+                '.' => Some(0),
+                x => x.to_digit(10).map(|x| x as u8),
+            })
+            .collect::<Vec<_>>();
+        if puzzle.len() != 81 {
+            return None;
+        }
+        let free = (0..9)
+            .map(|square| {
+                Self::square(square)
+                    .filter(|x| puzzle[*x] == 0)
+                    .collect::<Vec<_>>()
+            })
+            .filter(|x| x.len() >= 2)
+            .collect();
+        Some(Self {
+            grid: vec![],
+            puzzle: Arc::new(puzzle),
+            free: Arc::new(free),
+        })
+    }
+    // Клетки квадрата 3x3
+    fn square(square: usize) -> impl Iterator<Item = usize> {
+        let (row, column) = (square / 3 * 3, square % 3 * 3);
+        (0..9).map(move |x| (row + x / 3) * 9 + column + x % 3)
+    }
+    // Цифра клетки после обмена клеток `swap`
+    fn digit(&self, cell: usize, swap: (usize, usize)) -> u8 {
+        if cell == swap.0 {
+            self.grid[swap.1]
+        } else if cell == swap.1 {
+            self.grid[swap.0]
+        } else {
+            self.grid[cell]
+        }
+    }
+    // Кол-во недостающих цифр в клетках после обмена `swap`
+    fn missing(&self, cells: impl Iterator<Item = usize>, swap: (usize, usize)) -> usize {
+        let mut seen = [false; 10];
+        for cell in cells {
+            seen[self.digit(cell, swap) as usize] = true;
+        }
+        9 - seen[1..].iter().filter(|x| **x).count()
+    }
+    // Кол-во недостающих цифр в строках и столбцах клеток
+    fn lines_cost(&self, cells: [usize; 2], swap: (usize, usize)) -> usize {
+        let mut rows = cells.map(|x| x / 9).to_vec();
+        let mut columns = cells.map(|x| x % 9).to_vec();
+        rows.dedup();
+        columns.dedup();
+        rows.iter()
+            .map(|row| self.missing((0..9).map(|x| row * 9 + x), swap))
+            .chain(
+                columns
+                    .iter()
+                    .map(|column| self.missing((0..9).map(|x| x * 9 + column), swap)),
+            )
+            .sum()
+    }
+    // Две случайные свободные клетки одного квадрата
+    fn exchange(&self, rng: &mut impl Rng) -> Option<(usize, usize)> {
+        if self.free.is_empty() {
+            return None;
+        }
+        let cells = &self.free[rng.gen_range(0..self.free.len())];
+        let first = rng.gen_range(0..cells.len());
+        let second = (first + rng.gen_range(1..cells.len())) % cells.len();
+        Some((cells[first], cells[second]))
+    }
+}
+
+impl State for SudokuState {
+    // Обмениваемые клетки, None - в судоку нечего менять
+    type Move = Option<(usize, usize)>;
+    // Меняем местами две свободные клетки квадрата
+    fn changed_state(&self, rng: &mut impl Rng) -> Self {
+        let mut state = self.clone();
+        if let Some((first, second)) = self.exchange(rng) {
+            state.grid.swap(first, second);
+        }
+        state
+    }
+    // Кол-во недостающих цифр во всех строках и столбцах
+    fn energy(&self) -> f64 {
+        let no_swap = (81, 81);
+        (0..9)
+            .map(|i| {
+                self.missing((0..9).map(|x| i * 9 + x), no_swap)
+                    + self.missing((0..9).map(|x| x * 9 + i), no_swap)
+            })
+            .sum::<usize>() as f64
+    }
+    // Обмен меняет только строки и столбцы двух клеток
+    fn propose(&self, _energy: f64, rng: &mut impl Rng) -> (Option<(usize, usize)>, f64) {
+        let Some((first, second)) = self.exchange(rng) else {
+            return (None, 0.0);
+        };
+        let cells = [first, second];
+        let delta = self.lines_cost(cells, (first, second)) as f64
+            - self.lines_cost(cells, (81, 81)) as f64;
+        (Some((first, second)), delta)
+    }
+    fn apply(&mut self, exchange: Option<(usize, usize)>) {
+        if let Some((first, second)) = exchange {
+            self.grid.swap(first, second);
+        }
+    }
+    // Заполняем пустые клетки каждого квадрата недостающими в нем цифрами
+    fn setup(&mut self) {
+        self.grid = self.puzzle.to_vec();
+        for square in 0..9 {
+            let cells = Self::square(square).collect::<Vec<_>>();
+            let mut digits = (1..=9u8)
+                .filter(|x| !cells.iter().any(|cell| self.puzzle[*cell] == *x))
+                .cycle();
+            for cell in Self::square(square) {
+                if self.grid[cell] == 0 {
+                    self.grid[cell] = digits.next().unwrap_or(1);
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for SudokuState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in 0..9 {
+            if row > 0 && row % 3 == 0 {
+                writeln!(f)?;
+            }
+            for column in 0..9 {
+                if column > 0 && column % 3 == 0 {
+                    write!(f, " ")?;
+                }
+                write!(f, "{}", self.grid[row * 9 + column])?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+// Задача коммивояжера
+
+use std::sync::Arc;
+
+use rand::Rng;
+
+use super::State;
+use crate::tsp::DistanceMatrix;
+
+// Замкнутый маршрут по всем вершинам `distances`
+#[derive(Debug, Clone)]
+pub struct TourState {
+    pub tour: Vec<usize>,
+    pub distances: Arc<DistanceMatrix>,
+}
+
+impl TourState {
+    pub fn new(distances: Arc<DistanceMatrix>) -> Self {
+        Self {
+            tour: vec![],
+            distances,
+        }
+    }
+    fn distance(&self, a: usize, b: usize) -> f64 {
+        self.distances.get(self.tour[a], self.tour[b]) as f64
+    }
+    // Случайный отрезок маршрута i..=j для разворота (2-opt)
+    fn segment(&self, rng: &mut impl Rng) -> (usize, usize) {
+        let n = self.tour.len();
+        let i = rng.gen_range(0..n);
+        let j = rng.gen_range(0..n);
+        (i.min(j), i.max(j))
+    }
+    // Разница длины маршрута после разворота отрезка i..=j
+    fn reverse_delta(&self, i: usize, j: usize) -> f64 {
+        let n = self.tour.len();
+        // Разворот всего маршрута или одной вершины длину не меняет
+        if i == j || (i == 0 && j == n - 1) {
+            return 0.0;
+        }
+        let (before, after) = ((i + n - 1) % n, (j + 1) % n);
+        self.distance(before, j) + self.distance(i, after)
+            - self.distance(before, i)
+            - self.distance(j, after)
+    }
+}
+
+impl State for TourState {
+    // Разворачиваемый отрезок
+    type Move = (usize, usize);
+    // Разворачиваем случайный отрезок маршрута
+    fn changed_state(&self, rng: &mut impl Rng) -> Self {
+        let mut state = self.clone();
+        let (i, j) = self.segment(rng);
+        state.tour[i..=j].reverse();
+        state
+    }
+    // Длина маршрута
+    fn energy(&self) -> f64 {
+        let n = self.tour.len();
+        (0..n).map(|i| self.distance(i, (i + 1) % n)).sum()
+    }
+    // Разворот меняет только два ребра
+    fn propose(&self, _energy: f64, rng: &mut impl Rng) -> ((usize, usize), f64) {
+        let (i, j) = self.segment(rng);
+        ((i, j), self.reverse_delta(i, j))
+    }
+    fn apply(&mut self, (i, j): (usize, usize)) {
+        self.tour[i..=j].reverse();
+    }
+    // Вершины по порядку
+    fn setup(&mut self) {
+        self.tour = (0..self.distances.len()).collect();
+    }
+}
+
+impl std::fmt::Display for TourState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let tour = self.tour.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        write!(f, "{}", tour.join(" → "))
+    }
+}
@@ -0,0 +1,113 @@
+use petgraph::visit::{EdgeRef, IntoEdgeReferences, IntoNodeIdentifiers, NodeIndexable};
+
+/// Dense symmetric matrix of distances between `len()` nodes.
+///
+/// Nodes are addressed by their position `0..len()`. Missing edges have
+/// an infinite distance and are never traveled by the solvers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistanceMatrix {
+    size: usize,
+    distances: Vec<f32>,
+}
+
+impl DistanceMatrix {
+    /// Matrix of `size` nodes without any edges.
+    pub fn new(size: usize) -> Self {
+        let mut distances = vec![f32::INFINITY; size * size];
+        for i in 0..size {
+            distances[i * size + i] = 0.0;
+        }
+        Self { size, distances }
+    }
+    /// Complete graph with `distance(a, b)` between every pair of nodes.
+    pub fn from_fn(size: usize, distance: impl Fn(usize, usize) -> f32) -> Self {
+        let mut matrix = Self::new(size);
+        for a in 0..size {
+            for b in a + 1..size {
+                matrix.set(a, b, distance(a, b));
+            }
+        }
+        matrix
+    }
+    /// Builds the matrix from any petgraph graph.
+    ///
+    /// Returns the matrix together with the node ids, so position `i` in the
+    /// matrix (and in the tours found by the solvers) is node `nodes[i]`.
+    pub fn from_graph<G>(g: G, distance: impl Fn(&G::EdgeWeight) -> f32) -> (Self, Vec<G::NodeId>)
+    where
+        G: IntoNodeIdentifiers + IntoEdgeReferences + NodeIndexable,
+    {
+        let nodes = g.node_identifiers().collect::<Vec<_>>();
+        let mut positions = vec![usize::MAX; g.node_bound()];
+        for (i, node) in nodes.iter().enumerate() {
+            positions[g.to_index(*node)] = i;
+        }
+        let mut matrix = Self::new(nodes.len());
+        for edge in g.edge_references() {
+            matrix.set(
+                positions[g.to_index(edge.source())],
+                positions[g.to_index(edge.target())],
+                distance(edge.weight()),
+            );
+        }
+        (matrix, nodes)
+    }
+    pub fn len(&self) -> usize {
+        self.size
+    }
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+    pub fn get(&self, a: usize, b: usize) -> f32 {
+        self.distances[a * self.size + b]
+    }
+    /// Sets the distance of the edge `a - b` in both directions.
+    pub fn set(&mut self, a: usize, b: usize, distance: f32) {
+        self.distances[a * self.size + b] = distance;
+        self.distances[b * self.size + a] = distance;
+    }
+    pub fn has_edge(&self, a: usize, b: usize) -> bool {
+        a != b && self.get(a, b).is_finite()
+    }
+    /// The `k` nearest neighbours of every node, nearest first.
+    pub fn neighbour_lists(&self, k: usize) -> Vec<Vec<usize>> {
+        (0..self.size)
+            .map(|a| {
+                let mut neighbours = (0..self.size)
+                    .filter(|b| self.has_edge(a, *b))
+                    .collect::<Vec<_>>();
+                neighbours.sort_by(|x, y| self.get(a, *x).total_cmp(&self.get(a, *y)));
+                neighbours.truncate(k);
+                neighbours
+            })
+            .collect()
+    }
+    /// Greedy tour from `start` that always goes to the nearest unvisited node.
+    pub fn nearest_neighbour_tour(&self, start: usize) -> Vec<usize> {
+        let mut visited = vec![false; self.size];
+        let mut tour = Vec::with_capacity(self.size);
+        let mut current = start;
+        while tour.len() != self.size {
+            visited[current] = true;
+            tour.push(current);
+            if let Some(next) = (0..self.size)
+                .filter(|x| !visited[*x])
+                .min_by(|x, y| self.get(current, *x).total_cmp(&self.get(current, *y)))
+            {
+                current = next;
+            }
+        }
+        tour
+    }
+    /// Length of the closed tour visiting nodes in the given order.
+    pub fn tour_length(&self, tour: &[usize]) -> f32 {
+        if tour.is_empty() {
+            return 0.0;
+        }
+        let mut sum = 0.0;
+        for i in 0..tour.len() - 1 {
+            sum += self.get(tour[i], tour[i + 1]);
+        }
+        sum + self.get(tour[tour.len() - 1], tour[0])
+    }
+}
@@ -0,0 +1,565 @@
+//! Reading and writing [TSPLIB](http://comopt.ifi.uni-heidelberg.de/software/TSPLIB95/) files.
+//!
+//! Supported instances: `EUC_2D`, `ATT`, `GEO` and `EXPLICIT` weights given
+//! as `FULL_MATRIX` or `UPPER_ROW`. Tours use positions `0..dimension`,
+//! the same positions as the [`DistanceMatrix`] of the instance.
+
+use std::path::Path;
+
+use crate::tsp::DistanceMatrix;
+
+#[derive(Debug)]
+pub enum TsplibError {
+    Io(std::io::Error),
+    Parse(String),
+    Unsupported(String),
+}
+
+impl std::fmt::Display for TsplibError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "io error: {}", e),
+            Self::Parse(e) => write!(f, "parse error: {}", e),
+            Self::Unsupported(e) => write!(f, "unsupported: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TsplibError {}
+
+impl From<std::io::Error> for TsplibError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EdgeWeightType {
+    Euc2d,
+    Att,
+    Geo,
+    Explicit,
+}
+
+#[derive(Debug, Clone)]
+pub struct TspInstance {
+    pub name: String,
+    pub comment: String,
+    pub edge_weight_type: EdgeWeightType,
+    // NODE_COORD_SECTION, if the file has one
+    pub coords: Option<Vec<(f64, f64)>>,
+    // DISPLAY_DATA_SECTION, if the file has one
+    pub display: Option<Vec<(f64, f64)>>,
+    pub distances: DistanceMatrix,
+}
+
+impl TspInstance {
+    pub fn dimension(&self) -> usize {
+        self.distances.len()
+    }
+    pub fn tour_length(&self, tour: &[usize]) -> f32 {
+        self.distances.tour_length(tour)
+    }
+    /// Planar `(x, y)` coordinates to draw the nodes at, if the file has any.
+    ///
+    /// `GEO` coordinates are `(latitude, longitude)`, so they are swapped.
+    pub fn display_coords(&self) -> Option<Vec<(f64, f64)>> {
+        if let Some(display) = &self.display {
+            return Some(display.clone());
+        }
+        let coords = self.coords.as_ref()?;
+        if self.edge_weight_type == EdgeWeightType::Geo {
+            Some(coords.iter().map(|(lat, long)| (*long, *lat)).collect())
+        } else {
+            Some(coords.clone())
+        }
+    }
+}
+
+// Header fields and data sections of a TSPLIB file
+#[derive(Default)]
+struct RawFile {
+    fields: Vec<(String, String)>,
+    sections: Vec<(String, Vec<String>)>,
+}
+
+impl RawFile {
+    fn field(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+    fn section(&self, key: &str) -> Option<&Vec<String>> {
+        self.sections.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+}
+
+fn parse_raw(s: &str) -> RawFile {
+    let mut raw = RawFile::default();
+    for line in s.lines().map(|x| x.trim()).filter(|x| !x.is_empty()) {
+        if line == "EOF" {
+            break;
+        }
+        if line.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            match line.split_once(':') {
+                Some((key, value)) => raw
+                    .fields
+                    .push((key.trim().to_uppercase(), value.trim().into())),
+                None => raw.sections.push((line.to_uppercase(), vec![])),
+            }
+        } else if let Some((_, data)) = raw.sections.last_mut() {
+            data.extend(line.split_whitespace().map(String::from));
+        }
+    }
+    raw
+}
+
+fn parse_number<T: std::str::FromStr>(s: &str) -> Result<T, TsplibError> {
+    s.parse()
+        .map_err(|_| TsplibError::Parse(format!("`{}` is not a number", s)))
+}
+
+fn parse_coords(data: &[String], dimension: usize) -> Result<Vec<(f64, f64)>, TsplibError> {
+    if data.len() < dimension * 3 {
+        return Err(TsplibError::Parse(format!(
+            "expected {} coordinates, found {}",
+            dimension,
+            data.len() / 3
+        )));
+    }
+    let mut coords = vec![(0.0, 0.0); dimension];
+    for chunk in data.chunks(3).take(dimension) {
+        let id: usize = parse_number(&chunk[0])?;
+        if id == 0 || id > dimension {
+            return Err(TsplibError::Parse(format!("node {} is out of range", id)));
+        }
+        coords[id - 1] = (parse_number(&chunk[1])?, parse_number(&chunk[2])?);
+    }
+    Ok(coords)
+}
+
+fn euc_2d(a: (f64, f64), b: (f64, f64)) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt().round() as f32
+}
+
+// Pseudo-Euclidean distance of the att48 / att532 instances
+fn att(a: (f64, f64), b: (f64, f64)) -> f32 {
+    let r = (((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)) / 10.0).sqrt();
+    let t = r.round();
+    if t < r {
+        (t + 1.0) as f32
+    } else {
+        t as f32
+    }
+}
+
+// Latitude or longitude in radians of a `DDD.MM` coordinate
+// TSPLIB defines GEO distances with a truncated PI, the known optima rely on it
+#[allow(clippy::approx_constant)]
+fn geo_radians(x: f64) -> f64 {
+    const PI: f64 = 3.141592;
+    let deg = x.trunc();
+    let min = x - deg;
+    PI * (deg + 5.0 * min / 3.0) / 180.0
+}
+
+fn geo(a: (f64, f64), b: (f64, f64)) -> f32 {
+    const RRR: f64 = 6378.388;
+    let (lat_a, long_a) = (geo_radians(a.0), geo_radians(a.1));
+    let (lat_b, long_b) = (geo_radians(b.0), geo_radians(b.1));
+    let q1 = (long_a - long_b).cos();
+    let q2 = (lat_a - lat_b).cos();
+    let q3 = (lat_a + lat_b).cos();
+    (RRR * (0.5 * ((1.0 + q1) * q2 - (1.0 - q1) * q3)).acos() + 1.0).trunc() as f32
+}
+
+fn explicit(raw: &RawFile, dimension: usize) -> Result<DistanceMatrix, TsplibError> {
+    let format = raw.field("EDGE_WEIGHT_FORMAT").unwrap_or("FULL_MATRIX");
+    let data = raw
+        .section("EDGE_WEIGHT_SECTION")
+        .ok_or_else(|| TsplibError::Parse("missing EDGE_WEIGHT_SECTION".into()))?;
+    let expected = match format {
+        "FULL_MATRIX" => dimension * dimension,
+        "UPPER_ROW" => dimension * dimension.saturating_sub(1) / 2,
+        _ => return Err(TsplibError::Unsupported(format!("{} format", format))),
+    };
+    if data.len() < expected {
+        return Err(TsplibError::Parse(format!(
+            "expected {} edge weights, found {}",
+            expected,
+            data.len()
+        )));
+    }
+    let weights = data
+        .iter()
+        .take(expected)
+        .map(|x| parse_number::<f32>(x))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut matrix = DistanceMatrix::new(dimension);
+    let mut weights = weights.into_iter();
+    for a in 0..dimension {
+        let first = if format == "FULL_MATRIX" { 0 } else { a + 1 };
+        for b in first..dimension {
+            let weight = weights.next().unwrap();
+            if a < b {
+                matrix.set(a, b, weight);
+            }
+        }
+    }
+    Ok(matrix)
+}
+
+/// Parses the content of a `.tsp` file.
+pub fn parse_tsp(s: &str) -> Result<TspInstance, TsplibError> {
+    let raw = parse_raw(s);
+    if let Some(kind) = raw.field("TYPE") {
+        if kind != "TSP" {
+            return Err(TsplibError::Unsupported(format!("{} problems", kind)));
+        }
+    }
+    let dimension: usize = parse_number(
+        raw.field("DIMENSION")
+            .ok_or_else(|| TsplibError::Parse("missing DIMENSION".into()))?,
+    )?;
+    let edge_weight_type = match raw.field("EDGE_WEIGHT_TYPE").unwrap_or("EUC_2D") {
+        "EUC_2D" => EdgeWeightType::Euc2d,
+        "ATT" => EdgeWeightType::Att,
+        "GEO" => EdgeWeightType::Geo,
+        "EXPLICIT" => EdgeWeightType::Explicit,
+        other => return Err(TsplibError::Unsupported(format!("{} weights", other))),
+    };
+    let coords = match raw.section("NODE_COORD_SECTION") {
+        Some(data) => Some(parse_coords(data, dimension)?),
+        None => None,
+    };
+    let display = match raw.section("DISPLAY_DATA_SECTION") {
+        Some(data) => Some(parse_coords(data, dimension)?),
+        None => None,
+    };
+    let distances = if edge_weight_type == EdgeWeightType::Explicit {
+        explicit(&raw, dimension)?
+    } else {
+        let coords = coords
+            .as_ref()
+            .ok_or_else(|| TsplibError::Parse("missing NODE_COORD_SECTION".into()))?;
+        let distance = match edge_weight_type {
+            EdgeWeightType::Att => att,
+            EdgeWeightType::Geo => geo,
+            _ => euc_2d,
+        };
+        DistanceMatrix::from_fn(dimension, |a, b| distance(coords[a], coords[b]))
+    };
+    Ok(TspInstance {
+        name: raw.field("NAME").unwrap_or_default().into(),
+        comment: raw.field("COMMENT").unwrap_or_default().into(),
+        edge_weight_type,
+        coords,
+        display,
+        distances,
+    })
+}
+
+pub fn read_tsp(path: impl AsRef<Path>) -> Result<TspInstance, TsplibError> {
+    parse_tsp(&std::fs::read_to_string(path)?)
+}
+
+/// Parses the content of a `.tour` (or `.opt.tour`) file of an instance with
+/// `dimension` nodes, the tour has to visit every node exactly once.
+pub fn parse_tour(s: &str, dimension: usize) -> Result<Vec<usize>, TsplibError> {
+    let raw = parse_raw(s);
+    if let Some(field) = raw.field("DIMENSION") {
+        let field: usize = parse_number(field)?;
+        if field != dimension {
+            return Err(TsplibError::Parse(format!(
+                "tour of {} nodes for an instance of {}",
+                field, dimension
+            )));
+        }
+    }
+    let data = raw
+        .section("TOUR_SECTION")
+        .ok_or_else(|| TsplibError::Parse("missing TOUR_SECTION".into()))?;
+    let mut tour = vec![];
+    let mut visited = vec![false; dimension];
+    for x in data {
+        let id: i64 = parse_number(x)?;
+        if id == -1 {
+            break;
+        }
+        if id < 1 || id as usize > dimension {
+            return Err(TsplibError::Parse(format!("node {} is out of range", id)));
+        }
+        let node = id as usize - 1;
+        if visited[node] {
+            return Err(TsplibError::Parse(format!("node {} is visited twice", id)));
+        }
+        visited[node] = true;
+        tour.push(node);
+    }
+    if tour.len() != dimension {
+        return Err(TsplibError::Parse(format!(
+            "expected {} nodes in tour, found {}",
+            dimension,
+            tour.len()
+        )));
+    }
+    Ok(tour)
+}
+
+pub fn read_tour(path: impl AsRef<Path>, dimension: usize) -> Result<Vec<usize>, TsplibError> {
+    parse_tour(&std::fs::read_to_string(path)?, dimension)
+}
+
+/// Formats a tour as the content of a `.tour` file.
+pub fn format_tour(name: &str, tour: &[usize], length: f32) -> String {
+    let mut s = format!(
+        "NAME : {}\nCOMMENT : Length = {}\nTYPE : TOUR\nDIMENSION : {}\nTOUR_SECTION\n",
+        name,
+        length,
+        tour.len()
+    );
+    for node in tour {
+        s += &format!("{}\n", node + 1);
+    }
+    s + "-1\nEOF\n"
+}
+
+pub fn write_tour(
+    path: impl AsRef<Path>,
+    name: &str,
+    tour: &[usize],
+    length: f32,
+) -> Result<(), TsplibError> {
+    Ok(std::fs::write(path, format_tour(name, tour, length))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ATT48: &str = "NAME : att48
+TYPE : TSP
+DIMENSION : 48
+EDGE_WEIGHT_TYPE : ATT
+NODE_COORD_SECTION
+1 6734 1453
+2 2233 10
+3 5530 1424
+4 401 841
+5 3082 1644
+6 7608 4458
+7 7573 3716
+8 7265 1268
+9 6898 1885
+10 1112 2049
+11 5468 2606
+12 5989 2873
+13 4706 2674
+14 4612 2035
+15 6347 2683
+16 6107 669
+17 7611 5184
+18 7462 3590
+19 7732 4723
+20 5900 3561
+21 4483 3369
+22 6101 1110
+23 5199 2182
+24 1633 2809
+25 4307 2322
+26 675 1006
+27 7555 4819
+28 7541 3981
+29 3177 756
+30 7352 4506
+31 7545 2801
+32 3245 3305
+33 6426 3173
+34 4608 1198
+35 23 2216
+36 7248 3779
+37 7762 4595
+38 7392 2244
+39 3484 2829
+40 6271 2135
+41 4985 140
+42 1916 1569
+43 7280 4899
+44 7509 3239
+45 10 2676
+46 6807 2993
+47 5185 3258
+48 3023 1942
+EOF
+";
+
+    const ATT48_OPT: &str = "NAME : att48.opt.tour
+TYPE : TOUR
+DIMENSION : 48
+TOUR_SECTION
+1 8 38 31 44 18 7 28 6 37 19 27 17 43 30 36 46 33 20 47 21 32 39 48 5 42 24 10 45 35
+4 26 2 29 34 41 16 22 3 23 14 25 13 11 12 15 40 9
+-1
+EOF
+";
+
+    const ULYSSES16: &str = "NAME: ulysses16.tsp
+TYPE: TSP
+DIMENSION: 16
+EDGE_WEIGHT_TYPE: GEO
+DISPLAY_DATA_TYPE: COORD_DISPLAY
+NODE_COORD_SECTION
+ 1 38.24 20.42
+ 2 39.57 26.15
+ 3 40.56 25.32
+ 4 36.26 23.12
+ 5 33.48 10.54
+ 6 37.56 12.19
+ 7 38.42 13.11
+ 8 37.52 20.44
+ 9 41.23 9.10
+ 10 41.17 13.05
+ 11 36.08 -5.21
+ 12 38.47 15.13
+ 13 38.15 15.35
+ 14 37.51 15.17
+ 15 35.49 14.32
+ 16 39.36 19.56
+EOF
+";
+
+    const ULYSSES16_OPT: &str = "NAME : ulysses16.opt.tour
+TYPE : TOUR
+DIMENSION : 16
+TOUR_SECTION
+1 14 13 12 7 6 15 5 11 9 10 16 3 2 4 8
+-1
+EOF
+";
+
+    fn parse_error(s: &str) -> String {
+        match parse_tsp(s) {
+            Ok(_) => panic!("parsed a malformed file"),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn euc_2d_rounds_to_nearest() {
+        let instance = parse_tsp(
+            "DIMENSION : 3
+EDGE_WEIGHT_TYPE : EUC_2D
+NODE_COORD_SECTION
+1 0 0
+2 1.5 2
+3 0 2.4
+",
+        )
+        .unwrap();
+        assert_eq!(instance.distances.get(0, 1), 3.0);
+        assert_eq!(instance.distances.get(0, 2), 2.0);
+        assert_eq!(instance.distances.get(1, 2), 2.0);
+    }
+
+    #[test]
+    fn att48_optimal_tour() {
+        let instance = parse_tsp(ATT48).unwrap();
+        let tour = parse_tour(ATT48_OPT, instance.dimension()).unwrap();
+        assert_eq!(instance.tour_length(&tour), 10628.0);
+    }
+
+    #[test]
+    fn ulysses16_optimal_tour() {
+        let instance = parse_tsp(ULYSSES16).unwrap();
+        let tour = parse_tour(ULYSSES16_OPT, instance.dimension()).unwrap();
+        assert_eq!(instance.tour_length(&tour), 6859.0);
+        // GEO coordinates are drawn as (longitude, latitude)
+        assert_eq!(instance.display_coords().unwrap()[0], (20.42, 38.24));
+    }
+
+    #[test]
+    fn explicit_formats_give_the_same_matrix() {
+        let full = parse_tsp(
+            "DIMENSION : 4
+EDGE_WEIGHT_TYPE : EXPLICIT
+EDGE_WEIGHT_FORMAT : FULL_MATRIX
+EDGE_WEIGHT_SECTION
+0 1 2 3
+1 0 4 5
+2 4 0 6
+3 5 6 0
+",
+        )
+        .unwrap();
+        let upper = parse_tsp(
+            "DIMENSION : 4
+EDGE_WEIGHT_TYPE : EXPLICIT
+EDGE_WEIGHT_FORMAT : UPPER_ROW
+EDGE_WEIGHT_SECTION
+1 2 3
+4 5
+6
+",
+        )
+        .unwrap();
+        for a in 0..4 {
+            for b in 0..4 {
+                if a != b {
+                    assert_eq!(full.distances.get(a, b), upper.distances.get(a, b));
+                }
+            }
+        }
+        assert_eq!(upper.distances.get(3, 1), 5.0);
+        assert_eq!(upper.tour_length(&[0, 1, 2, 3]), 1.0 + 4.0 + 6.0 + 3.0);
+    }
+
+    #[test]
+    fn malformed_headers_are_rejected() {
+        assert!(parse_error("TYPE : TSP\nNODE_COORD_SECTION\n1 0 0\n").contains("DIMENSION"));
+        assert!(parse_error("DIMENSION : two\n").contains("`two` is not a number"));
+        assert!(parse_error("TYPE : ATSP\nDIMENSION : 2\n").starts_with("unsupported"));
+        assert!(
+            parse_error("DIMENSION : 2\nEDGE_WEIGHT_TYPE : EUC_3D\n").starts_with("unsupported")
+        );
+        assert!(parse_error("DIMENSION : 2\nEDGE_WEIGHT_TYPE : EUC_2D\n")
+            .contains("NODE_COORD_SECTION"));
+        assert!(parse_error("DIMENSION : 2\nNODE_COORD_SECTION\n1 0 0\n")
+            .contains("expected 2 coordinates"));
+        assert!(
+            parse_error("DIMENSION : 2\nNODE_COORD_SECTION\n1 0 0\n3 1 1\n")
+                .contains("out of range")
+        );
+        assert!(parse_error(
+            "DIMENSION : 2\nEDGE_WEIGHT_TYPE : EXPLICIT\nEDGE_WEIGHT_FORMAT : LOWER_ROW\nEDGE_WEIGHT_SECTION\n1\n"
+        )
+        .starts_with("unsupported"));
+        assert!(parse_error(
+            "DIMENSION : 3\nEDGE_WEIGHT_TYPE : EXPLICIT\nEDGE_WEIGHT_FORMAT : UPPER_ROW\nEDGE_WEIGHT_SECTION\n1 2\n"
+        )
+        .contains("expected 3 edge weights"));
+    }
+
+    #[test]
+    fn tours_are_validated() {
+        let tour = |s: &str| parse_tour(&format!("TOUR_SECTION\n{}\n-1\n", s), 4);
+        assert_eq!(tour("2 4 1 3").unwrap(), vec![1, 3, 0, 2]);
+        assert!(tour("1 2 3 5")
+            .unwrap_err()
+            .to_string()
+            .contains("out of range"));
+        assert!(tour("0 1 2 3")
+            .unwrap_err()
+            .to_string()
+            .contains("out of range"));
+        assert!(tour("1 2 2 3").unwrap_err().to_string().contains("twice"));
+        assert!(tour("1 2 3")
+            .unwrap_err()
+            .to_string()
+            .contains("expected 4"));
+        assert!(parse_tour("DIMENSION : 3\nTOUR_SECTION\n1 2 3\n-1\n", 4).is_err());
+        assert!(parse_tour("DIMENSION : 4\n", 4).is_err());
+        let written = format_tour("test", &[3, 1, 0, 2], 10.0);
+        assert_eq!(parse_tour(&written, 4).unwrap(), vec![3, 1, 0, 2]);
+    }
+}
@@ -1,18 +1,25 @@
-use ant_algo::IterationInfo;
-use crossbeam::channel::{unbounded, Receiver, Sender};
+use ai_core::{
+    ant_algo::{self, History, IterationInfo, PheromoneStrategy},
+    local_search::LocalSearch,
+    run_control::RunControl,
+    tsp::DistanceMatrix,
+    tsplib::{self, TspInstance},
+};
+use crossbeam::channel::{unbounded, Receiver, Sender, TryRecvError};
 use eframe::{run_native, App, CreationContext};
-use egui::{CollapsingHeader, Color32, Context, ScrollArea, Slider, Ui, Vec2};
+use egui::{CollapsingHeader, Color32, Context, Label, RichText, ScrollArea, Slider, Ui, Vec2};
 use egui_graphs::{Change, ChangeNode, Edge, Graph, GraphView, Node, SettingsInteraction};
 use petgraph::{
     stable_graph::{NodeIndex, StableUnGraph},
     visit::EdgeRef,
     Undirected,
 };
-use rand::Rng;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{collections::HashMap, time::Duration};
 
-mod ant_algo;
 mod settings;
 
+#[derive(Clone)]
 pub struct AntOptions {
     alpha: f32,
     beta: f32,
@@ -21,6 +28,19 @@ pub struct AntOptions {
     nodes: i64,
     ant_amount: i64,
     iterations_amount: i64,
+    strategy: PheromoneStrategy,
+    use_candidates: bool,
+    candidates: usize,
+    local_search: LocalSearch,
+    use_local_search_neighbours: bool,
+    local_search_neighbours: usize,
+    seed: u64,
+    history: History,
+    use_time_limit: bool,
+    // seconds
+    time_limit: f32,
+    use_stagnation: bool,
+    stagnation: usize,
 }
 
 impl Default for AntOptions {
@@ -33,18 +53,37 @@ impl Default for AntOptions {
             nodes: 3,
             ant_amount: 1,
             iterations_amount: 1,
+            strategy: PheromoneStrategy::default(),
+            use_candidates: false,
+            candidates: 10,
+            local_search: LocalSearch::default(),
+            use_local_search_neighbours: true,
+            local_search_neighbours: 10,
+            seed: new_seed(),
+            history: History::default(),
+            use_time_limit: false,
+            time_limit: 60.0,
+            use_stagnation: false,
+            stagnation: 100,
         }
     }
 }
 
 struct SolutionInfo {
+    // Kept iterations, the best one is always kept
     solution: Vec<IterationInfo>,
+    // Position of the best iteration in `solution`
     best_iteration: usize,
-    best_ant: i64,
+    iterations_done: usize,
+    iterations_amount: usize,
+    // Graph node for each position in the distance matrix
+    nodes: Vec<NodeIndex>,
+    positions: HashMap<NodeIndex, usize>,
+    seed: u64,
 }
 
 pub struct AntApp {
-    g: Graph<(), ant_algo::EdgeInfo, Undirected>,
+    g: Graph<(), f32, Undirected>,
     ant_options: AntOptions,
     settings_style: settings::SettingsStyle,
     settings_navigation: settings::SettingsNavigation,
@@ -57,18 +96,60 @@ pub struct AntApp {
     drag_enabled: bool,
     changes_receiver: Receiver<Change>,
     changes_sender: Sender<Change>,
+    // Iteration index and the iteration if it is kept, from the solver thread
+    iterations_receiver: Option<Receiver<(usize, Option<IterationInfo>)>>,
+    // Stops and pauses the solver thread
+    control: Option<RunControl>,
+    tsp_path: String,
+    opt_tour_path: String,
+    tour_path: String,
+    instance_name: String,
+    optimal_length: Option<f32>,
+    tsplib_error: Option<String>,
+    // Places the random nodes, seeded with the seed so the instance can be built again
+    instance_rng: StdRng,
+}
+
+// Seeds stay below 2^32, so they are shown and typed in exactly
+fn new_seed() -> u64 {
+    rand::random::<u32>() as u64
 }
 
 fn distance(a: Vec2, b: Vec2) -> f32 {
     ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
 }
 
+// Node locations of a TSPLIB instance scaled to 1000 points wide,
+// nodes without coordinates are placed on a circle
+fn instance_locations(instance: &TspInstance) -> Vec<Vec2> {
+    let n = instance.dimension();
+    let Some(coords) = instance.display_coords() else {
+        return (0..n)
+            .map(|i| {
+                let angle = std::f32::consts::TAU * i as f32 / n as f32;
+                Vec2::new(500.0 * angle.cos(), 500.0 * angle.sin())
+            })
+            .collect();
+    };
+    let min_x = coords.iter().map(|x| x.0).fold(f64::INFINITY, f64::min);
+    let max_x = coords.iter().map(|x| x.0).fold(f64::NEG_INFINITY, f64::max);
+    let min_y = coords.iter().map(|x| x.1).fold(f64::INFINITY, f64::min);
+    let max_y = coords.iter().map(|x| x.1).fold(f64::NEG_INFINITY, f64::max);
+    let scale = 1000.0 / (max_x - min_x).max(max_y - min_y).max(f64::EPSILON);
+    coords
+        .iter()
+        .map(|(x, y)| Vec2::new(((x - min_x) * scale) as f32, ((max_y - y) * scale) as f32))
+        .collect()
+}
+
 impl AntApp {
     fn new(_: &CreationContext<'_>) -> Self {
         let (changes_sender, changes_receiver) = unbounded();
+        let ant_options = AntOptions::default();
         let mut app = Self {
             g: Graph::from(&StableUnGraph::default()),
-            ant_options: AntOptions::default(),
+            instance_rng: StdRng::seed_from_u64(ant_options.seed),
+            ant_options,
             settings_style: settings::SettingsStyle::default(),
             settings_navigation: settings::SettingsNavigation::default(),
             solution: None,
@@ -78,8 +159,16 @@ impl AntApp {
             show_pheromones: true,
             pheromones_k: 1.0,
             drag_enabled: false,
-            changes_receiver: changes_receiver,
-            changes_sender: changes_sender,
+            changes_receiver,
+            changes_sender,
+            iterations_receiver: None,
+            control: None,
+            tsp_path: "".into(),
+            opt_tour_path: "".into(),
+            tour_path: "".into(),
+            instance_name: "".into(),
+            optimal_length: None,
+            tsplib_error: None,
         };
         for _ in 0..app.ant_options.nodes {
             app.add_random_node();
@@ -106,15 +195,10 @@ impl AntApp {
         let indexes: Vec<_> = self.g.g.node_indices().collect();
         indexes.into_iter().for_each(|x| {
             if x != node {
-                let mut edge_data = ant_algo::EdgeInfo {
-                    distance: distance(
-                        self.g.node(x).unwrap().location(),
-                        self.g.node(node).unwrap().location(),
-                    ),
-                    pheromones: 0.0,
-                    probability_parameters: 0.0,
-                };
-                edge_data.recalculate(self.ant_options.alpha, self.ant_options.beta);
+                let edge_data = distance(
+                    self.g.node(x).unwrap().location(),
+                    self.g.node(node).unwrap().location(),
+                );
                 self.g.g.add_edge(
                     x,
                     node,
@@ -132,13 +216,13 @@ impl AntApp {
 
         self.g.g.remove_node(idx).unwrap();
     }
-    fn random_node_idx(&self) -> Option<NodeIndex> {
+    fn random_node_idx(&mut self) -> Option<NodeIndex> {
         let nodes_cnt = self.g.g.node_count();
         if nodes_cnt == 0 {
             return None;
         }
 
-        let random_n_idx = rand::thread_rng().gen_range(0..nodes_cnt);
+        let random_n_idx = self.instance_rng.gen_range(0..nodes_cnt);
         self.g.g.node_indices().nth(random_n_idx)
     }
     fn remove_random_node(&mut self) {
@@ -154,7 +238,7 @@ impl AntApp {
 
         let random_n = self.g.g.node_weight(random_n_idx.unwrap()).unwrap();
         // location of new node is in surrounging of random existing node
-        let mut rng = rand::thread_rng();
+        let rng = &mut self.instance_rng;
         let x_sign = (rng.gen_range(0..1) * -2 + 1) as f32;
         let y_sign = (rng.gen_range(0..1) * -2 + 1) as f32;
         let location = Vec2::new(
@@ -167,7 +251,11 @@ impl AntApp {
     }
     fn ant_options_sliders(&mut self, ui: &mut Ui) {
         let nodes_before = self.ant_options.nodes;
-        ui.add(Slider::new(&mut self.ant_options.nodes, 3..=200).text("Nodes"));
+        ui.add(
+            Slider::new(&mut self.ant_options.nodes, 3..=200)
+                .clamp_to_range(false)
+                .text("Nodes"),
+        );
         let delta_nodes = self.ant_options.nodes - nodes_before;
         if delta_nodes != 0 {
             self.reset_graph();
@@ -188,6 +276,246 @@ impl AntApp {
         ui.add(
             Slider::new(&mut self.ant_options.iterations_amount, 1..=512).text("Iterations amount"),
         );
+        ui.checkbox(&mut self.ant_options.use_candidates, "Candidate lists");
+        ui.add_enabled(
+            self.ant_options.use_candidates,
+            Slider::new(&mut self.ant_options.candidates, 1..=50).text("Nearest neighbours"),
+        );
+        self.strategy_options(ui);
+        ui.label("Local search");
+        ui.checkbox(&mut self.ant_options.local_search.two_opt, "2-opt");
+        ui.checkbox(&mut self.ant_options.local_search.or_opt, "Or-opt");
+        ui.checkbox(&mut self.ant_options.local_search.three_opt, "3-opt");
+        ui.checkbox(
+            &mut self.ant_options.use_local_search_neighbours,
+            "Local search neighbour lists",
+        );
+        ui.add_enabled(
+            self.ant_options.use_local_search_neighbours,
+            Slider::new(&mut self.ant_options.local_search_neighbours, 1..=50)
+                .text("Local search neighbours"),
+        );
+        self.history_options(ui);
+        ui.checkbox(&mut self.ant_options.use_time_limit, "Time limit");
+        ui.add_enabled(
+            self.ant_options.use_time_limit,
+            Slider::new(&mut self.ant_options.time_limit, 1. ..=600.).text("Seconds"),
+        );
+        ui.checkbox(&mut self.ant_options.use_stagnation, "Stop on stagnation");
+        ui.add_enabled(
+            self.ant_options.use_stagnation,
+            Slider::new(&mut self.ant_options.stagnation, 1..=512)
+                .text("Iterations without a better path"),
+        );
+        ui.horizontal(|ui| {
+            let seed_before = self.ant_options.seed;
+            ui.add(egui::DragValue::new(&mut self.ant_options.seed).prefix("Seed: "));
+            if ui.button("New seed").clicked() {
+                self.ant_options.seed = new_seed();
+            }
+            if self.ant_options.seed != seed_before {
+                self.instance_rng = StdRng::seed_from_u64(self.ant_options.seed);
+            }
+        });
+    }
+    fn run_control(&self) -> RunControl {
+        let mut control = RunControl::new();
+        if self.ant_options.use_time_limit {
+            control = control.with_time_limit(Duration::from_secs_f32(self.ant_options.time_limit));
+        }
+        if self.ant_options.use_stagnation {
+            control = control.with_stagnation(self.ant_options.stagnation);
+        }
+        control
+    }
+    fn run_control_ui(&self, ui: &mut Ui) {
+        let Some(control) = self
+            .control
+            .as_ref()
+            .filter(|_| self.iterations_receiver.is_some())
+        else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            if control.is_paused() {
+                if ui.button("Resume").clicked() {
+                    control.resume();
+                }
+            } else if ui.button("Pause").clicked() {
+                control.pause();
+            }
+            if ui.button("Stop").clicked() {
+                control.cancel();
+            }
+        });
+    }
+    fn local_search(&self) -> LocalSearch {
+        LocalSearch {
+            neighbours: self
+                .ant_options
+                .use_local_search_neighbours
+                .then_some(self.ant_options.local_search_neighbours),
+            ..self.ant_options.local_search
+        }
+    }
+    fn history_options(&mut self, ui: &mut Ui) {
+        let histories = [
+            History::None,
+            History::BestOnly,
+            History::EveryNth(10),
+            History::Full,
+        ];
+        let selected = &mut self.ant_options.history;
+        egui::ComboBox::from_label("Kept iterations")
+            .selected_text(selected.to_string())
+            .show_ui(ui, |ui| {
+                for history in histories {
+                    let is_selected =
+                        std::mem::discriminant(selected) == std::mem::discriminant(&history);
+                    if ui
+                        .selectable_label(is_selected, history.to_string())
+                        .clicked()
+                        && !is_selected
+                    {
+                        *selected = history;
+                    }
+                }
+            });
+        if let History::EveryNth(k) = selected {
+            ui.add(Slider::new(k, 1..=100).text("Keep every k-th iteration"));
+        }
+    }
+    fn strategy_options(&mut self, ui: &mut Ui) {
+        let strategies = [
+            PheromoneStrategy::AntSystem,
+            PheromoneStrategy::Elitist { elite_weight: 5.0 },
+            PheromoneStrategy::RankBased { w: 6 },
+            PheromoneStrategy::MaxMin {
+                p_best: 0.05,
+                stagnation: 50,
+            },
+            PheromoneStrategy::AntColonySystem { q0: 0.9, xi: 0.1 },
+        ];
+        let selected = &mut self.ant_options.strategy;
+        egui::ComboBox::from_label("Pheromone strategy")
+            .selected_text(selected.to_string())
+            .show_ui(ui, |ui| {
+                for strategy in strategies {
+                    let is_selected =
+                        std::mem::discriminant(selected) == std::mem::discriminant(&strategy);
+                    if ui
+                        .selectable_label(is_selected, strategy.to_string())
+                        .clicked()
+                        && !is_selected
+                    {
+                        *selected = strategy;
+                    }
+                }
+            });
+        match selected {
+            PheromoneStrategy::AntSystem => (),
+            PheromoneStrategy::Elitist { elite_weight } => {
+                ui.add(Slider::new(elite_weight, 0. ..=100.).text("Elite weight"));
+            }
+            PheromoneStrategy::RankBased { w } => {
+                ui.add(Slider::new(w, 1..=64).text("w (ranked ants + 1)"));
+            }
+            PheromoneStrategy::MaxMin { p_best, stagnation } => {
+                ui.add(Slider::new(p_best, 0.001..=1.).text("p best"));
+                ui.add(Slider::new(stagnation, 1..=512).text("Reinit after stagnation"));
+            }
+            PheromoneStrategy::AntColonySystem { q0, xi } => {
+                ui.add(Slider::new(q0, 0. ..=1.).text("q0"));
+                ui.add(Slider::new(xi, 0. ..=1.).text("xi"));
+            }
+        }
+    }
+    fn load_instance(&mut self, instance: &TspInstance) {
+        self.reset_graph();
+        self.g = Graph::from(&StableUnGraph::default());
+        let nodes = instance_locations(instance)
+            .into_iter()
+            .map(|location| self.g.g.add_node(Node::new(location, ())))
+            .collect::<Vec<_>>();
+        for a in 0..nodes.len() {
+            for b in a + 1..nodes.len() {
+                if instance.distances.has_edge(a, b) {
+                    self.g.g.add_edge(
+                        nodes[a],
+                        nodes[b],
+                        Edge::new(instance.distances.get(a, b))
+                            .with_color(Color32::from_rgba_unmultiplied(128, 128, 128, 0)),
+                    );
+                }
+            }
+        }
+        self.ant_options.nodes = nodes.len() as i64;
+        self.instance_name = instance.name.clone();
+        self.optimal_length = None;
+    }
+    fn load_opt_tour(&mut self) -> Result<(), tsplib::TsplibError> {
+        let (distances, _) = DistanceMatrix::from_graph(&self.g.g, |e| *e.data().unwrap());
+        let tour = tsplib::read_tour(&self.opt_tour_path, distances.len())?;
+        self.optimal_length = Some(distances.tour_length(&tour));
+        Ok(())
+    }
+    fn save_best_tour(&self) -> Result<(), tsplib::TsplibError> {
+        let solution = self.solution.as_ref().unwrap();
+        let iteration = &solution.solution[solution.best_iteration];
+        let ant = &iteration.ants[iteration.best_ant_i as usize];
+        let name = if self.instance_name.is_empty() {
+            "ant"
+        } else {
+            &self.instance_name
+        };
+        tsplib::write_tour(&self.tour_path, name, &ant.tabu, ant.distance)
+    }
+    fn tsplib_ui(&mut self, ui: &mut Ui) {
+        ui.label("Instance (.tsp) path");
+        ui.text_edit_singleline(&mut self.tsp_path);
+        if ui.button("Load instance").clicked() {
+            match tsplib::read_tsp(&self.tsp_path) {
+                Ok(instance) => {
+                    self.load_instance(&instance);
+                    self.tsplib_error = None;
+                }
+                Err(e) => self.tsplib_error = Some(e.to_string()),
+            }
+        }
+        ui.label("Optimal tour (.opt.tour) path");
+        ui.text_edit_singleline(&mut self.opt_tour_path);
+        if ui.button("Load optimal tour").clicked() {
+            self.tsplib_error = self.load_opt_tour().err().map(|e| e.to_string());
+        }
+        ui.label("Best tour (.tour) save path");
+        ui.text_edit_singleline(&mut self.tour_path);
+        if ui
+            .add_enabled(
+                self.solution
+                    .as_ref()
+                    .is_some_and(|x| !x.solution.is_empty()),
+                egui::Button::new("Save best tour"),
+            )
+            .clicked()
+        {
+            self.tsplib_error = self.save_best_tour().err().map(|e| e.to_string());
+        }
+        if !self.instance_name.is_empty() {
+            ui.label(format!("Instance: {}", self.instance_name));
+        }
+        if let Some(optimal_length) = self.optimal_length {
+            ui.label(format!("Optimal path: {}", optimal_length));
+            if let Some(solution) = self.solution.as_ref().filter(|x| !x.solution.is_empty()) {
+                let best = solution.solution[solution.best_iteration].best_path_len;
+                ui.label(format!(
+                    "Gap to optimum: {:.2}%",
+                    (best - optimal_length) / optimal_length * 100.0
+                ));
+            }
+        }
+        if let Some(e) = &self.tsplib_error {
+            ui.add(Label::new(RichText::new(e).color(Color32::RED)));
+        }
     }
     fn ui_settings(&mut self, ui: &mut Ui) {
         if ui
@@ -208,90 +536,117 @@ impl AntApp {
         });
         self.g.g.edge_weights_mut().for_each(|x| {
             x.clone_from(
-                &Edge::new(x.data().unwrap().clone())
+                &Edge::new(*x.data().unwrap())
                     .with_color(Color32::from_rgba_unmultiplied(128, 128, 128, 0)),
             );
         });
     }
     fn reset_graph(&mut self) {
         self.solution = None;
-        self.g.g.node_weights_mut().for_each(|x| {
-            x.clone_from(&x.clone().with_color(Color32::from_rgb(200, 200, 200)));
-        });
-        self.g.g.edge_weights_mut().for_each(|x| {
-            let mut new_edge_data = x.data().unwrap().clone();
-            new_edge_data.pheromones = 0.0;
-            new_edge_data.probability_parameters = 0.0;
-            x.clone_from(
-                &Edge::new(new_edge_data)
-                    .with_color(Color32::from_rgba_unmultiplied(128, 128, 128, 0)),
-            );
-        });
+        self.iterations_receiver = None;
+        if let Some(control) = self.control.take() {
+            control.cancel();
+        }
+        self.reset_graph_color();
     }
     fn update_graph(&mut self) {
-        match &self.solution {
-            Some(v) => {
-                let v = &v.solution;
-                let iteration = &v[self.iteration_i as usize];
-                let ant = &iteration.ants[self.ant_i as usize];
-                if self.show_pheromones {
-                    iteration
-                        .old_edges
-                        .iter()
-                        .zip(self.g.g.edge_weights_mut())
-                        .for_each(|(color_edge, x)| {
-                            x.clone_from(
-                                &x.clone().with_color(Color32::from_rgba_unmultiplied(
-                                    0,
-                                    (((0.0 as f32 + 255 as f32 * color_edge.pheromones)
-                                        * self.pheromones_k)
-                                        as u8)
-                                        .clamp(0, 255),
-                                    0,
-                                    (((0.0 as f32 + 255 as f32 * color_edge.pheromones)
-                                        * self.pheromones_k)
-                                        as u8)
-                                        .clamp(0, 255),
-                                )),
-                            );
-                        });
+        if let Some(solution) = self.solution.as_ref().filter(|x| !x.solution.is_empty()) {
+            let v = &solution.solution;
+            let iteration = &v[self.iteration_i as usize];
+            let ant = &iteration.ants[self.ant_i as usize];
+            if let Some(old_edges) = iteration
+                .old_edges
+                .as_ref()
+                .filter(|_| self.show_pheromones)
+            {
+                let edges = self.g.g.edge_indices().collect::<Vec<_>>();
+                let pheromones_of = |g: &StableUnGraph<_, _>, e| {
+                    let (a, b) = g.edge_endpoints(e).unwrap();
+                    old_edges
+                        .edge(solution.positions[&a], solution.positions[&b])
+                        .pheromones
+                };
+                // strategies lay pheromones on different scales, so colors are relative to the max
+                let max_pheromones = edges
+                    .iter()
+                    .map(|e| pheromones_of(&self.g.g, *e))
+                    .fold(f32::EPSILON, f32::max);
+                for e in edges {
+                    let pheromones = pheromones_of(&self.g.g, e) / max_pheromones;
+                    let x = self.g.g.edge_weight_mut(e).unwrap();
+                    x.clone_from(&x.clone().with_color(Color32::from_rgba_unmultiplied(
+                        0,
+                        ((255.0 * pheromones * self.pheromones_k) as u8).clamp(0, 255),
+                        0,
+                        ((255.0 * pheromones * self.pheromones_k) as u8).clamp(0, 255),
+                    )));
                 }
-                for i in 0..=self.edge_i {
-                    let node = self.g.g.node_weight_mut(ant.tabu[i as usize]).unwrap();
-                    node.clone_from(&node.clone().with_color(Color32::from_rgb(85, 24, 93)));
+            }
+            for i in 0..=self.edge_i {
+                let node = self
+                    .g
+                    .g
+                    .node_weight_mut(solution.nodes[ant.tabu[i as usize]])
+                    .unwrap();
+                node.clone_from(&node.clone().with_color(Color32::from_rgb(85, 24, 93)));
+            }
+            for i in 0..=self.edge_i {
+                let edge = &ant.edges[i as usize];
+                let e = self
+                    .g
+                    .g
+                    .find_edge(solution.nodes[edge.source], solution.nodes[edge.target])
+                    .unwrap();
+                let e = self.g.g.edge_weight_mut(e).unwrap();
+                e.clone_from(
+                    &e.clone()
+                        .with_color(Color32::from_rgba_unmultiplied(255, 213, 36, 128)),
+                );
+            }
+        }
+    }
+    fn receive_iterations(&mut self) {
+        let (Some(receiver), Some(solution)) = (&self.iterations_receiver, &mut self.solution)
+        else {
+            return;
+        };
+        let was_empty = solution.solution.is_empty();
+        loop {
+            match receiver.try_recv() {
+                Ok((index, iteration)) => {
+                    solution.iterations_done = index + 1;
+                    if let Some(iteration) = iteration {
+                        if iteration.new_best {
+                            solution.best_iteration = solution.solution.len();
+                        }
+                        solution.solution.push(iteration);
+                    }
                 }
-                for i in 0..=self.edge_i {
-                    let e = self
-                        .g
-                        .g
-                        .edge_weight_mut(ant.edges[i as usize].index)
-                        .unwrap();
-                    e.clone_from(
-                        &e.clone()
-                            .with_color(Color32::from_rgba_unmultiplied(255, 213, 36, 128)),
-                    );
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.iterations_receiver = None;
+                    break;
                 }
             }
-            None => (),
+        }
+        if was_empty && !solution.solution.is_empty() {
+            self.update_graph();
         }
     }
     fn handle_changes(&mut self) {
         let mut node_id: Option<NodeIndex> = None;
         self.changes_receiver.try_iter().for_each(|ch| {
-            if let Change::Node(ChangeNode::Location { id, old, new }) = ch.clone() {
+            if let Change::Node(ChangeNode::Location { id, .. }) = ch.clone() {
                 node_id = Some(id);
             }
         });
-        match node_id {
-            Some(id) => {
-                self.reset_graph();
-                let neighbors = self.g.g.neighbors_undirected(id).collect::<Vec<_>>();
-                neighbors.iter().for_each(|n| {
-                    self.remove_edges(id, *n);
-                });
-                self.connect_node(id)
-            }
-            None => (),
+        if let Some(id) = node_id {
+            self.reset_graph();
+            let neighbors = self.g.g.neighbors_undirected(id).collect::<Vec<_>>();
+            neighbors.iter().for_each(|n| {
+                self.remove_edges(id, *n);
+            });
+            self.connect_node(id)
         }
     }
 }
@@ -317,132 +672,163 @@ impl App for AntApp {
                                 self.iteration_i = 0;
                                 self.ant_i = 0;
                                 self.edge_i = 0;
-                                let res = ant_algo::ant_algo(
-                                    &mut self.g,
-                                    self.ant_options.iterations_amount,
-                                    self.ant_options.ant_amount,
-                                    self.ant_options.alpha,
-                                    self.ant_options.beta,
-                                    self.ant_options.q,
-                                    self.ant_options.p,
-                                );
-                                let best_iteration = res
-                                    .iter()
-                                    .min_by(|x, y| {
-                                        x.ants[x.best_ant_i as usize]
-                                            .distance
-                                            .total_cmp(&y.ants[y.best_ant_i as usize].distance)
-                                    })
-                                    .unwrap()
-                                    .clone();
-                                let solution = SolutionInfo {
-                                    solution: res,
-                                    best_iteration: best_iteration.index,
-                                    best_ant: best_iteration.best_ant_i,
-                                };
-                                self.solution = Some(solution);
-                                self.update_graph();
+                                let (distances, nodes) =
+                                    DistanceMatrix::from_graph(&self.g.g, |e| *e.data().unwrap());
+                                let options = self.ant_options.clone();
+                                let local_search = self.local_search();
+                                let control = self.run_control();
+                                self.control = Some(control.clone());
+                                let (sender, receiver) = unbounded();
+                                // the solver streams the iterations, so the ui shows them while it runs
+                                std::thread::spawn(move || {
+                                    ant_algo::ant_algo(
+                                        &distances,
+                                        &ant_algo::AntOptions {
+                                            iterations_amount: options.iterations_amount,
+                                            ant_amount: options.ant_amount,
+                                            alpha: options.alpha,
+                                            beta: options.beta,
+                                            q: options.q,
+                                            p: options.p,
+                                            strategy: options.strategy,
+                                            candidates: options
+                                                .use_candidates
+                                                .then_some(options.candidates),
+                                            local_search,
+                                            history: History::None,
+                                            snapshots: options.history,
+                                            threads: None,
+                                        },
+                                        |iteration| {
+                                            let kept = options.history.keeps(iteration)
+                                                || iteration.new_best;
+                                            let _ = sender.send((
+                                                iteration.index,
+                                                kept.then(|| iteration.clone()),
+                                            ));
+                                        },
+                                        &control,
+                                        &mut StdRng::seed_from_u64(options.seed),
+                                    );
+                                });
+                                self.solution = Some(SolutionInfo {
+                                    solution: vec![],
+                                    best_iteration: 0,
+                                    iterations_done: 0,
+                                    iterations_amount: self.ant_options.iterations_amount as usize,
+                                    positions: nodes
+                                        .iter()
+                                        .enumerate()
+                                        .map(|(i, x)| (*x, i))
+                                        .collect(),
+                                    nodes,
+                                    seed: self.ant_options.seed,
+                                });
+                                self.iterations_receiver = Some(receiver);
+                            }
+                            self.run_control_ui(ui);
+                            if let Some(solution) = &self.solution {
+                                ui.label(format!(
+                                    "Iterations done: {} / {}",
+                                    solution.iterations_done, solution.iterations_amount
+                                ));
                             }
-                            match &self.solution {
-                                Some(solution) => {
-                                    let v = &solution.solution;
-                                    let iteration_before = self.iteration_i;
-                                    ui.add(
-                                        Slider::new(
-                                            &mut self.iteration_i,
-                                            0..=(v.len() - 1) as i64,
-                                        )
+                            if let Some(solution) =
+                                self.solution.as_ref().filter(|x| !x.solution.is_empty())
+                            {
+                                let v = &solution.solution;
+                                let iteration_before = self.iteration_i;
+                                ui.add(
+                                    Slider::new(&mut self.iteration_i, 0..=(v.len() - 1) as i64)
                                         .text("Iteration"),
-                                    );
-                                    let ant_before = self.ant_i;
-                                    ui.add(
-                                        Slider::new(
-                                            &mut self.ant_i,
-                                            0..=(v.first().unwrap().ants.len() - 1) as i64,
-                                        )
-                                        .text("Ant"),
-                                    );
-                                    let edge_before = self.edge_i;
-                                    ui.add(
-                                        Slider::new(
-                                            &mut self.edge_i,
-                                            0..=(v
-                                                .first()
-                                                .unwrap()
-                                                .ants
-                                                .first()
-                                                .unwrap()
-                                                .edges
-                                                .len()
-                                                - 1)
-                                                as i64,
-                                        )
-                                        .text("Edge"),
-                                    );
-                                    ui.label(format!(
-                                        "Best path: Iteration#{} Ant#{} / {}",
-                                        solution.best_iteration,
-                                        v[solution.best_iteration].best_ant_i,
-                                        v[solution.best_iteration].best_path_len
-                                    ));
-                                    ui.label(format!(
-                                        "Best path for iteration: Ant#{} / {}",
-                                        v[self.iteration_i as usize].best_ant_i,
-                                        v[self.iteration_i as usize].best_path_len
-                                    ));
-                                    ui.label(format!(
-                                        "Current Ant path: {}",
-                                        v[self.iteration_i as usize].ants[self.ant_i as usize]
-                                            .distance
-                                    ));
+                                );
+                                let ant_before = self.ant_i;
+                                ui.add(
+                                    Slider::new(
+                                        &mut self.ant_i,
+                                        0..=(v.first().unwrap().ants.len() - 1) as i64,
+                                    )
+                                    .text("Ant"),
+                                );
+                                let edge_before = self.edge_i;
+                                ui.add(
+                                    Slider::new(
+                                        &mut self.edge_i,
+                                        0..=(v.first().unwrap().ants.first().unwrap().edges.len()
+                                            - 1) as i64,
+                                    )
+                                    .text("Edge"),
+                                );
+                                ui.label(format!("Seed: {}", solution.seed));
+                                ui.label(format!(
+                                    "Best path: Iteration#{} Ant#{} / {}",
+                                    v[solution.best_iteration].index,
+                                    v[solution.best_iteration].best_ant_i,
+                                    v[solution.best_iteration].best_path_len
+                                ));
+                                ui.label(format!(
+                                    "Best path for iteration#{}: Ant#{} / {}",
+                                    v[self.iteration_i as usize].index,
+                                    v[self.iteration_i as usize].best_ant_i,
+                                    v[self.iteration_i as usize].best_path_len
+                                ));
+                                ui.label(format!(
+                                    "Current Ant path: {}",
+                                    v[self.iteration_i as usize].ants[self.ant_i as usize].distance
+                                ));
 
-                                    if ui.button("Show best path").clicked() {
-                                        self.iteration_i = solution.best_iteration as i64;
-                                        self.ant_i = solution.best_ant;
-                                        self.edge_i =
-                                            (v.first().unwrap().ants.first().unwrap().edges.len()
-                                                - 1)
-                                                as i64;
-                                        self.reset_graph_color();
-                                        self.update_graph();
-                                    } else {
-                                        if self.iteration_i != iteration_before
-                                            || self.ant_i != ant_before
-                                            || self.edge_i != edge_before
-                                        {
-                                            self.reset_graph_color();
-                                            if self.iteration_i - iteration_before != 0 {
-                                                self.ant_i = 0;
-                                                self.edge_i = 0;
-                                            } else if self.ant_i - ant_before != 0 {
-                                                self.edge_i = 0;
-                                            }
-                                            self.update_graph();
-                                        }
-                                    }
-                                    if ui
-                                        .checkbox(&mut self.show_pheromones, "Show pheromones")
-                                        .changed()
+                                if ui.button("Show best path").clicked() {
+                                    self.iteration_i = solution.best_iteration as i64;
+                                    self.ant_i = v[solution.best_iteration].best_ant_i;
+                                    self.edge_i =
+                                        (v.first().unwrap().ants.first().unwrap().edges.len() - 1)
+                                            as i64;
+                                    self.reset_graph_color();
+                                    self.update_graph();
+                                } else {
+                                    if self.iteration_i != iteration_before
+                                        || self.ant_i != ant_before
+                                        || self.edge_i != edge_before
                                     {
                                         self.reset_graph_color();
-                                        self.update_graph();
-                                    }
-                                    let old_pheromones_k = self.pheromones_k;
-                                    ui.add_enabled(
-                                        self.show_pheromones,
-                                        Slider::new(&mut self.pheromones_k, 0. ..=1.)
-                                            .text("Pheromones visiability"),
-                                    );
-                                    if old_pheromones_k != self.pheromones_k {
-                                        if self.show_pheromones {
-                                            self.reset_graph_color();
-                                            self.update_graph();
+                                        if self.iteration_i - iteration_before != 0 {
+                                            self.ant_i = 0;
+                                            self.edge_i = 0;
+                                        } else if self.ant_i - ant_before != 0 {
+                                            self.edge_i = 0;
                                         }
+                                        self.update_graph();
                                     }
                                 }
-                                None => (),
+                                if ui
+                                    .checkbox(&mut self.show_pheromones, "Show pheromones")
+                                    .changed()
+                                {
+                                    self.reset_graph_color();
+                                    self.update_graph();
+                                }
+                                let old_pheromones_k = self.pheromones_k;
+                                ui.add_enabled(
+                                    self.show_pheromones,
+                                    Slider::new(&mut self.pheromones_k, 0. ..=1.)
+                                        .text("Pheromones visiability"),
+                                );
+                                if old_pheromones_k != self.pheromones_k && self.show_pheromones {
+                                    self.reset_graph_color();
+                                    self.update_graph();
+                                }
                             }
                         });
+                    CollapsingHeader::new("TSPLIB")
+                        .default_open(false)
+                        .show(ui, |ui| {
+                            ui.add_space(10.0);
+
+                            ui.label("TSPLIB files");
+                            ui.separator();
+
+                            self.tsplib_ui(ui);
+                        });
                     CollapsingHeader::new("Ui")
                         .default_open(true)
                         .show(ui, |ui| {
@@ -476,6 +862,10 @@ impl App for AntApp {
             );
         });
         self.handle_changes();
+        self.receive_iterations();
+        if self.iterations_receiver.is_some() {
+            ctx.request_repaint();
+        }
     }
 }
 
@@ -1,11 +1,8 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // Скрывает консоль на Windows
 
-mod art1;
-
-use art1::Claster;
+use ai_core::art1::{self, Claster};
 use eframe::egui;
-use egui::plot::{Legend, Line, Plot, PlotPoints};
-use rand::Rng;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
 fn main() -> Result<(), eframe::Error> {
     env_logger::init(); // Лог в stderr (`RUST_LOG=debug`).
@@ -14,11 +11,7 @@ fn main() -> Result<(), eframe::Error> {
         initial_window_size: Some(egui::vec2(350.0, 400.0)),
         ..Default::default()
     };
-    eframe::run_native(
-        "ART-1",
-        options,
-        Box::new(|_cc| Box::<MyApp>::default()),
-    )
+    eframe::run_native("ART-1", options, Box::new(|_cc| Box::<MyApp>::default()))
 }
 struct MyApp {
     // Количество векторов-прототипов
@@ -27,6 +20,8 @@ struct MyApp {
     p: String,
     // Бета параметр
     b: String,
+    // Зерно генератора случайных чисел
+    seed: String,
     // Кластеры
     clasters: Option<Vec<Claster>>,
     // Данные
@@ -45,16 +40,19 @@ struct MyApp {
 
 impl Default for MyApp {
     fn default() -> Self {
+        let seed = rand::random::<u32>();
+        let mut rng = StdRng::seed_from_u64(seed as u64);
         let mut data: Vec<bit_vec::BitVec> = vec![];
-        for i in 0..10 {
-            data.push(bit_vec::BitVec::from_bytes(&[rand::random::<u8>()]))
+        for _ in 0..10 {
+            data.push(bit_vec::BitVec::from_bytes(&[rng.gen::<u8>()]))
         }
         Self {
             amount_clasters: "5".into(),
             b: "1.0".into(),
             p: "0.1".into(),
+            seed: seed.to_string(),
             clasters: None,
-            data: data,
+            data,
             colors: vec![
                 egui::Color32::from_rgb(128, 0, 0),
                 egui::Color32::from_rgb(255, 99, 71),
@@ -80,6 +78,19 @@ impl Default for MyApp {
     }
 }
 
+impl MyApp {
+    // Зерно генератора, при ошибке разбора 0
+    fn seed(&mut self) -> u64 {
+        match self.seed.parse() {
+            Ok(v) => v,
+            Err(_) => {
+                self.seed = "0".into();
+                0u64
+            }
+        }
+    }
+}
+
 impl eframe::App for MyApp {
     fn update(&mut self, ctx: &egui::Context, _: &mut eframe::Frame) {
         egui::CentralPanel::default().show(ctx, |ui| {
@@ -100,6 +111,14 @@ impl eframe::App for MyApp {
                             ui.label("Бета параметр");
                             ui.text_edit_singleline(&mut self.b);
 
+                            ui.label("Зерно генератора");
+                            ui.horizontal(|ui| {
+                                ui.text_edit_singleline(&mut self.seed);
+                                if ui.button("Новое зерно").clicked() {
+                                    self.seed = rand::random::<u32>().to_string();
+                                }
+                            });
+
                             if ui.button("Посчитать").clicked() {
                                 let p = match self.p.parse() {
                                     Ok(v) => v,
@@ -122,8 +141,14 @@ impl eframe::App for MyApp {
                                         5usize
                                     }
                                 };
-                                let (clasters, dropped) =
-                                    art1::art1(&self.data, &amount_clasters, &p, &b);
+                                let seed = self.seed();
+                                let (clasters, dropped) = art1::art1(
+                                    &self.data,
+                                    &amount_clasters,
+                                    &p,
+                                    &b,
+                                    &mut StdRng::seed_from_u64(seed),
+                                );
                                 self.clasters = Some(clasters);
                                 self.dropped = dropped;
                                 if self.clasters.as_ref().unwrap().len() > self.colors.len() {
@@ -163,11 +188,13 @@ impl eframe::App for MyApp {
                                         8usize
                                     }
                                 };
+                                // данные строятся из того же зерна, что и кластеризация
+                                let mut rng = StdRng::seed_from_u64(self.seed());
                                 self.data.clear();
                                 for i in 0..amount_of_data {
                                     self.data.push(bit_vec::BitVec::from_elem(features, false));
                                     for j in 0..features {
-                                        if rand::random::<bool>() {
+                                        if rng.gen::<bool>() {
                                             self.data[i].set(j, true);
                                         }
                                     }
@@ -227,7 +254,7 @@ impl eframe::App for MyApp {
                                             }
                                             None => match self.clasters.as_ref() {
                                                 Some(clasters) => {
-                                                    for claster in self.clasters.as_ref().unwrap() {
+                                                    for claster in clasters {
                                                         ui.label(
                                                             egui::RichText::new(format!(
                                                                 "Прототип: {:?}",
@@ -264,7 +291,7 @@ impl eframe::App for MyApp {
                                                 }
                                             },
                                         }
-                                        if self.dropped.len() != 0 {
+                                        if !self.dropped.is_empty() {
                                             ui.label(
                                                 egui::RichText::new("Не вошедшие в кластеры")
                                                     .font(egui::FontId::proportional(25.0))
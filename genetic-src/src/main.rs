@@ -1,27 +1,59 @@
 use crossbeam::channel::{unbounded, Receiver, Sender};
 use eframe::{run_native, App, CreationContext};
-use egui::{CollapsingHeader, Color32, Context, RichText, ScrollArea, Slider, Ui, Vec2};
+use egui::plot::{Legend, Line, Plot, PlotPoints};
+use egui::{CollapsingHeader, Color32, Context, Label, RichText, ScrollArea, Slider, Ui, Vec2};
 use egui_graphs::{Change, ChangeNode, Edge, Graph, GraphView, Node, SettingsInteraction};
 use petgraph::{
     stable_graph::{NodeIndex, StableUnGraph},
-    visit::EdgeRef,
+    visit::{EdgeRef, IntoEdgeReferences, NodeIndexable},
     Undirected,
 };
-use rand::Rng;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
-mod genetic;
 mod settings;
 
-use genetic::*;
+use ai_core::genetic::crossover::PermutationCrossover;
+use ai_core::genetic::genealogy::{Genealogy, Origin};
+use ai_core::genetic::island::{self, IslandSolution, Migration, Topology};
+use ai_core::genetic::mutation::{Adaptation, MutationMix, PermutationMutation, SharedMutationMix};
+use ai_core::genetic::replacement::Replacement;
+use ai_core::genetic::selection::{self, SelectionMethod};
+use ai_core::genetic::stats::GenerationStats;
+use ai_core::genetic::*;
+use ai_core::{
+    local_search::LocalSearch,
+    run_control::RunControl,
+    tsp::DistanceMatrix,
+    tsplib::{self, TspInstance},
+};
 
+#[derive(Clone)]
 pub struct GeneticOptions {
     nodes_amount: usize,
     crossover_p: f32,
     mutation_p: f32,
     population_amount: usize,
     population_size: usize,
+    local_search: LocalSearch,
+    use_local_search_neighbours: bool,
+    local_search_neighbours: usize,
+    seed: u64,
+    selection: SelectionMethod,
+    replacement: Replacement,
+    islands: usize,
+    migration: Migration,
+    crossover: PermutationCrossover,
+    // weight of every `PermutationMutation::ALL`, 0 turns it off
+    mutation_weights: [f32; 5],
+    adaptive_mutation: bool,
+    use_time_limit: bool,
+    // seconds
+    time_limit: f32,
+    use_stagnation: bool,
+    stagnation: usize,
 }
 
 impl Default for GeneticOptions {
@@ -32,6 +64,21 @@ impl Default for GeneticOptions {
             mutation_p: 0.5,
             population_amount: 3,
             population_size: 3,
+            local_search: LocalSearch::default(),
+            use_local_search_neighbours: true,
+            local_search_neighbours: 10,
+            seed: new_seed(),
+            selection: SelectionMethod::default(),
+            replacement: Replacement::default(),
+            islands: 1,
+            migration: Migration::default(),
+            crossover: PermutationCrossover::default(),
+            mutation_weights: [0.0, 0.0, 0.0, 1.0, 0.0],
+            adaptive_mutation: false,
+            use_time_limit: false,
+            time_limit: 60.0,
+            use_stagnation: false,
+            stagnation: 100,
         }
     }
 }
@@ -42,37 +89,107 @@ pub struct GeneticApp {
     settings_style: settings::SettingsStyle,
     settings_navigation: settings::SettingsNavigation,
     solution: Option<TSPSolution>,
+    // Solution of every island, `solution` is the one of `island_i`
+    islands: Vec<TSPSolution>,
+    island_i: usize,
+    // seed of the shown solution
+    solution_seed: u64,
     population_i: usize,
     chromosome_i: usize,
     drag_enabled: bool,
     changes_receiver: Receiver<Change>,
     changes_sender: Sender<Change>,
     show_current: bool,
+    show_stats: bool,
     show_parent_1: bool,
     show_parent_2: bool,
+    tsp_path: String,
+    opt_tour_path: String,
+    tour_path: String,
+    instance_name: String,
+    optimal_length: Option<f32>,
+    tsplib_error: Option<String>,
+    // Places the random nodes, seeded with the seed so the instance can be built again
+    instance_rng: StdRng,
+    genealogy_path: String,
+    // Ancestors of the best path and how many came from every origin
+    best_ancestry: Option<(usize, Vec<(Origin, usize)>)>,
+    genealogy_error: Option<String>,
+    // Mutations of the shown solution, with their success counts
+    mutation_mixes: Vec<SharedMutationMix>,
+    // Solver running in its own thread
+    promise: Option<poll_promise::Promise<IslandSolution<TSPSolution>>>,
+    // Stops and pauses the solver thread
+    control: Option<RunControl>,
+}
+
+// Seeds stay below 2^32, so they are shown and typed in exactly
+fn new_seed() -> u64 {
+    rand::random::<u32>() as u64
 }
 
 fn distance(a: Vec2, b: Vec2) -> f32 {
     ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
 }
 
+// Node locations of a TSPLIB instance scaled to 1000 points wide,
+// nodes without coordinates are placed on a circle
+fn instance_locations(instance: &TspInstance) -> Vec<Vec2> {
+    let n = instance.dimension();
+    let Some(coords) = instance.display_coords() else {
+        return (0..n)
+            .map(|i| {
+                let angle = std::f32::consts::TAU * i as f32 / n as f32;
+                Vec2::new(500.0 * angle.cos(), 500.0 * angle.sin())
+            })
+            .collect();
+    };
+    let min_x = coords.iter().map(|x| x.0).fold(f64::INFINITY, f64::min);
+    let max_x = coords.iter().map(|x| x.0).fold(f64::NEG_INFINITY, f64::max);
+    let min_y = coords.iter().map(|x| x.1).fold(f64::INFINITY, f64::min);
+    let max_y = coords.iter().map(|x| x.1).fold(f64::NEG_INFINITY, f64::max);
+    let scale = 1000.0 / (max_x - min_x).max(max_y - min_y).max(f64::EPSILON);
+    coords
+        .iter()
+        .map(|(x, y)| Vec2::new(((x - min_x) * scale) as f32, ((max_y - y) * scale) as f32))
+        .collect()
+}
+
 impl GeneticApp {
     fn new(_: &CreationContext<'_>) -> Self {
         let (changes_sender, changes_receiver) = unbounded();
+        let genetic_options = GeneticOptions::default();
         let mut app = Self {
             g: Graph::from(&StableUnGraph::default()),
-            genetic_options: GeneticOptions::default(),
+            instance_rng: StdRng::seed_from_u64(genetic_options.seed),
+            genetic_options,
             settings_style: settings::SettingsStyle::default(),
             settings_navigation: settings::SettingsNavigation::default(),
             solution: None,
+            islands: vec![],
+            island_i: 0,
+            solution_seed: 0,
             population_i: 0,
             chromosome_i: 0,
             drag_enabled: false,
-            changes_receiver: changes_receiver,
-            changes_sender: changes_sender,
+            changes_receiver,
+            changes_sender,
             show_current: true,
+            show_stats: false,
             show_parent_1: false,
             show_parent_2: false,
+            tsp_path: "".into(),
+            opt_tour_path: "".into(),
+            tour_path: "".into(),
+            instance_name: "".into(),
+            optimal_length: None,
+            tsplib_error: None,
+            genealogy_path: "".into(),
+            best_ancestry: None,
+            genealogy_error: None,
+            mutation_mixes: vec![],
+            promise: None,
+            control: None,
         };
         for _ in 0..app.genetic_options.nodes_amount {
             app.add_random_node();
@@ -122,13 +239,13 @@ impl GeneticApp {
 
         self.g.g.remove_node(idx).unwrap();
     }
-    fn random_node_idx(&self) -> Option<NodeIndex> {
+    fn random_node_idx(&mut self) -> Option<NodeIndex> {
         let nodes_cnt = self.g.g.node_count();
         if nodes_cnt == 0 {
             return None;
         }
 
-        let random_n_idx = rand::thread_rng().gen_range(0..nodes_cnt);
+        let random_n_idx = self.instance_rng.gen_range(0..nodes_cnt);
         self.g.g.node_indices().nth(random_n_idx)
     }
     fn remove_random_node(&mut self) {
@@ -144,7 +261,7 @@ impl GeneticApp {
 
         let random_n = self.g.g.node_weight(random_n_idx.unwrap()).unwrap();
         // location of new node is in surrounging of random existing node
-        let mut rng = rand::thread_rng();
+        let rng = &mut self.instance_rng;
         let x_sign = (rng.gen_range(0..1) * -2 + 1) as f32;
         let y_sign = (rng.gen_range(0..1) * -2 + 1) as f32;
         let location = Vec2::new(
@@ -157,7 +274,11 @@ impl GeneticApp {
     }
     fn genetic_options_sliders(&mut self, ui: &mut Ui) {
         let nodes_before = self.genetic_options.nodes_amount;
-        ui.add(Slider::new(&mut self.genetic_options.nodes_amount, 3..=200).text("Nodes"));
+        ui.add(
+            Slider::new(&mut self.genetic_options.nodes_amount, 3..=200)
+                .clamp_to_range(false)
+                .text("Nodes"),
+        );
         let delta_nodes = self.genetic_options.nodes_amount.abs_diff(nodes_before);
         if delta_nodes != 0 {
             self.reset_graph();
@@ -177,6 +298,442 @@ impl GeneticApp {
         ui.add(
             Slider::new(&mut self.genetic_options.population_size, 2..=512).text("Population size"),
         );
+        self.selection_options(ui);
+        self.replacement_options(ui);
+        self.island_options(ui);
+        let selected = &mut self.genetic_options.crossover;
+        egui::ComboBox::from_label("Crossover")
+            .selected_text(selected.to_string())
+            .show_ui(ui, |ui| {
+                for crossover in [
+                    PermutationCrossover::ShuffledSegment,
+                    PermutationCrossover::Pmx,
+                    PermutationCrossover::Ox1,
+                    PermutationCrossover::Cx,
+                    PermutationCrossover::Erx,
+                ] {
+                    ui.selectable_value(selected, crossover, crossover.to_string());
+                }
+            });
+        ui.label("Mutations (weights)");
+        for (mutation, weight) in PermutationMutation::ALL
+            .iter()
+            .zip(self.genetic_options.mutation_weights.iter_mut())
+        {
+            ui.add(Slider::new(weight, 0. ..=1.).text(mutation.to_string()));
+        }
+        ui.checkbox(
+            &mut self.genetic_options.adaptive_mutation,
+            "Adapt weights to successful mutations",
+        );
+        ui.label("Local search (memetic step)");
+        ui.checkbox(&mut self.genetic_options.local_search.two_opt, "2-opt");
+        ui.checkbox(&mut self.genetic_options.local_search.or_opt, "Or-opt");
+        ui.checkbox(&mut self.genetic_options.local_search.three_opt, "3-opt");
+        ui.checkbox(
+            &mut self.genetic_options.use_local_search_neighbours,
+            "Local search neighbour lists",
+        );
+        ui.add_enabled(
+            self.genetic_options.use_local_search_neighbours,
+            Slider::new(&mut self.genetic_options.local_search_neighbours, 1..=50)
+                .text("Local search neighbours"),
+        );
+        ui.checkbox(&mut self.genetic_options.use_time_limit, "Time limit");
+        ui.add_enabled(
+            self.genetic_options.use_time_limit,
+            Slider::new(&mut self.genetic_options.time_limit, 1. ..=600.).text("Seconds"),
+        );
+        ui.checkbox(
+            &mut self.genetic_options.use_stagnation,
+            "Stop on stagnation",
+        );
+        ui.add_enabled(
+            self.genetic_options.use_stagnation,
+            Slider::new(&mut self.genetic_options.stagnation, 1..=10000)
+                .text("Populations without a better path"),
+        );
+        ui.horizontal(|ui| {
+            let seed_before = self.genetic_options.seed;
+            ui.add(egui::DragValue::new(&mut self.genetic_options.seed).prefix("Seed: "));
+            if ui.button("New seed").clicked() {
+                self.genetic_options.seed = new_seed();
+            }
+            if self.genetic_options.seed != seed_before {
+                self.instance_rng = StdRng::seed_from_u64(self.genetic_options.seed);
+            }
+        });
+    }
+    fn selection_options(&mut self, ui: &mut Ui) {
+        let methods = [
+            SelectionMethod::Roulette,
+            SelectionMethod::Tournament(selection::Tournament { size: 2 }),
+            SelectionMethod::Rank(selection::Rank { pressure: 1.5 }),
+            SelectionMethod::StochasticUniversalSampling,
+            SelectionMethod::Truncation(selection::Truncation { ratio: 0.5 }),
+            SelectionMethod::Boltzmann(selection::Boltzmann { temperature: 100.0 }),
+        ];
+        let selected = &mut self.genetic_options.selection;
+        egui::ComboBox::from_label("Selection")
+            .selected_text(selected.to_string())
+            .show_ui(ui, |ui| {
+                for method in methods {
+                    let is_selected =
+                        std::mem::discriminant(selected) == std::mem::discriminant(&method);
+                    if ui
+                        .selectable_label(is_selected, method.to_string())
+                        .clicked()
+                        && !is_selected
+                    {
+                        *selected = method;
+                    }
+                }
+            });
+        match selected {
+            SelectionMethod::Roulette | SelectionMethod::StochasticUniversalSampling => (),
+            SelectionMethod::Tournament(x) => {
+                ui.add(Slider::new(&mut x.size, 1..=32).text("Tournament size"));
+            }
+            SelectionMethod::Rank(x) => {
+                ui.add(Slider::new(&mut x.pressure, 1. ..=2.).text("Selection pressure"));
+            }
+            SelectionMethod::Truncation(x) => {
+                ui.add(Slider::new(&mut x.ratio, 0.01..=1.).text("Kept part"));
+            }
+            SelectionMethod::Boltzmann(x) => {
+                ui.add(
+                    Slider::new(&mut x.temperature, 0.1..=10000.)
+                        .logarithmic(true)
+                        .text("Temperature"),
+                );
+            }
+        }
+    }
+    fn replacement_options(&mut self, ui: &mut Ui) {
+        let offspring = self.genetic_options.population_size;
+        let strategies = [
+            Replacement::Generational { elites: 1 },
+            Replacement::MuPlusLambda { offspring },
+            Replacement::MuCommaLambda { offspring },
+            Replacement::SteadyState { offspring: 2 },
+        ];
+        let selected = &mut self.genetic_options.replacement;
+        egui::ComboBox::from_label("Replacement")
+            .selected_text(selected.to_string())
+            .show_ui(ui, |ui| {
+                for strategy in strategies {
+                    let is_selected =
+                        std::mem::discriminant(selected) == std::mem::discriminant(&strategy);
+                    if ui
+                        .selectable_label(is_selected, strategy.to_string())
+                        .clicked()
+                        && !is_selected
+                    {
+                        *selected = strategy;
+                    }
+                }
+            });
+        match selected {
+            Replacement::Generational { elites } => {
+                ui.add(Slider::new(elites, 0..=offspring).text("Elites"));
+            }
+            Replacement::MuPlusLambda { offspring } | Replacement::MuCommaLambda { offspring } => {
+                ui.add(Slider::new(offspring, 1..=1024).text("Offspring"));
+            }
+            Replacement::SteadyState {
+                offspring: replaced,
+            } => {
+                ui.add(Slider::new(replaced, 1..=offspring).text("Replaced per population"));
+            }
+        }
+    }
+    fn island_options(&mut self, ui: &mut Ui) {
+        ui.add(Slider::new(&mut self.genetic_options.islands, 1..=16).text("Islands"));
+        ui.add_enabled_ui(self.genetic_options.islands > 1, |ui| {
+            let migration = &mut self.genetic_options.migration;
+            egui::ComboBox::from_label("Migration topology")
+                .selected_text(migration.topology.to_string())
+                .show_ui(ui, |ui| {
+                    for topology in [Topology::Ring, Topology::FullyConnected] {
+                        ui.selectable_value(
+                            &mut migration.topology,
+                            topology,
+                            topology.to_string(),
+                        );
+                    }
+                });
+            ui.add(Slider::new(&mut migration.interval, 1..=1000).text("Migration interval"));
+            ui.add(
+                Slider::new(
+                    &mut migration.migrants,
+                    0..=self.genetic_options.population_size - 1,
+                )
+                .text("Migrants"),
+            );
+        });
+    }
+    fn mutation_mix(&self) -> MutationMix {
+        let operators = PermutationMutation::ALL
+            .into_iter()
+            .zip(self.genetic_options.mutation_weights)
+            .filter(|x| x.1 > 0.0)
+            .collect::<Vec<_>>();
+        if operators.is_empty() {
+            return MutationMix::default();
+        }
+        let mix = MutationMix::new(&operators);
+        if self.genetic_options.adaptive_mutation {
+            mix.with_adaptation(Adaptation {
+                learning_rate: 0.05,
+                min_probability: 0.05,
+            })
+        } else {
+            mix
+        }
+    }
+    fn run_control(&self) -> RunControl {
+        let mut control = RunControl::new();
+        if self.genetic_options.use_time_limit {
+            control =
+                control.with_time_limit(Duration::from_secs_f32(self.genetic_options.time_limit));
+        }
+        if self.genetic_options.use_stagnation {
+            control = control.with_stagnation(self.genetic_options.stagnation);
+        }
+        control
+    }
+    // Pause and stop buttons while the solver runs, takes its result when it is done
+    fn run_control_ui(&mut self, ui: &mut Ui) {
+        let Some(promise) = self.promise.take() else {
+            return;
+        };
+        match promise.try_take() {
+            Ok(IslandSolution { islands }) => {
+                self.control = None;
+                if !islands[0].iterations.is_empty() {
+                    self.island_i = 0;
+                    self.best_ancestry = None;
+                    self.solution = Some(islands[0].clone());
+                    self.islands = islands;
+                    self.update_graph();
+                }
+            }
+            Err(promise) => {
+                self.promise = Some(promise);
+                let control = self.control.as_ref().unwrap();
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    if control.is_paused() {
+                        if ui.button("Resume").clicked() {
+                            control.resume();
+                        }
+                    } else if ui.button("Pause").clicked() {
+                        control.pause();
+                    }
+                    if ui.button("Stop").clicked() {
+                        control.cancel();
+                    }
+                });
+            }
+        }
+    }
+    fn local_search(&self) -> LocalSearch {
+        LocalSearch {
+            neighbours: self
+                .genetic_options
+                .use_local_search_neighbours
+                .then_some(self.genetic_options.local_search_neighbours),
+            ..self.genetic_options.local_search
+        }
+    }
+    fn load_instance(&mut self, instance: &TspInstance) {
+        self.reset_graph();
+        self.g = Graph::from(&StableUnGraph::default());
+        let nodes = instance_locations(instance)
+            .into_iter()
+            .map(|location| self.g.g.add_node(Node::new(location, ())))
+            .collect::<Vec<_>>();
+        for a in 0..nodes.len() {
+            for b in a + 1..nodes.len() {
+                if instance.distances.has_edge(a, b) {
+                    self.g.g.add_edge(
+                        nodes[a],
+                        nodes[b],
+                        Edge::new(EdgeInfo {
+                            distance: instance.distances.get(a, b),
+                        })
+                        .with_color(Color32::from_rgba_unmultiplied(128, 128, 128, 0)),
+                    );
+                }
+            }
+        }
+        self.genetic_options.nodes_amount = nodes.len();
+        self.instance_name = instance.name.clone();
+        self.optimal_length = None;
+    }
+    fn load_opt_tour(&mut self) -> Result<(), tsplib::TsplibError> {
+        let (distances, _) = DistanceMatrix::from_graph(&self.g.g, |e| e.data().unwrap().distance);
+        let tour = tsplib::read_tour(&self.opt_tour_path, distances.len())?;
+        self.optimal_length = Some(distances.tour_length(&tour));
+        Ok(())
+    }
+    fn save_best_tour(&self) -> Result<(), tsplib::TsplibError> {
+        let solution = self.solution.as_ref().unwrap();
+        let best = &solution.iterations[solution.best_population_i].old[solution.best_chromosome_i];
+        let positions = self
+            .g
+            .g
+            .node_indices()
+            .enumerate()
+            .map(|(i, x)| (x, i))
+            .collect::<HashMap<_, _>>();
+        let tour = best
+            .travel_list
+            .iter()
+            .map(|x| positions[x])
+            .collect::<Vec<_>>();
+        let name = if self.instance_name.is_empty() {
+            "genetic"
+        } else {
+            &self.instance_name
+        };
+        tsplib::write_tour(&self.tour_path, name, &tour, best.path_length)
+    }
+    fn tsplib_ui(&mut self, ui: &mut Ui) {
+        ui.label("Instance (.tsp) path");
+        ui.text_edit_singleline(&mut self.tsp_path);
+        if ui.button("Load instance").clicked() {
+            match tsplib::read_tsp(&self.tsp_path) {
+                Ok(instance) => {
+                    self.load_instance(&instance);
+                    self.tsplib_error = None;
+                }
+                Err(e) => self.tsplib_error = Some(e.to_string()),
+            }
+        }
+        ui.label("Optimal tour (.opt.tour) path");
+        ui.text_edit_singleline(&mut self.opt_tour_path);
+        if ui.button("Load optimal tour").clicked() {
+            self.tsplib_error = self.load_opt_tour().err().map(|e| e.to_string());
+        }
+        ui.label("Best tour (.tour) save path");
+        ui.text_edit_singleline(&mut self.tour_path);
+        if ui
+            .add_enabled(self.solution.is_some(), egui::Button::new("Save best tour"))
+            .clicked()
+        {
+            self.tsplib_error = self.save_best_tour().err().map(|e| e.to_string());
+        }
+        if !self.instance_name.is_empty() {
+            ui.label(format!("Instance: {}", self.instance_name));
+        }
+        if let Some(optimal_length) = self.optimal_length {
+            ui.label(format!("Optimal path: {}", optimal_length));
+            if let Some(solution) = &self.solution {
+                let best = solution.iterations[solution.best_population_i].old
+                    [solution.best_chromosome_i]
+                    .path_length;
+                ui.label(format!(
+                    "Gap to optimum: {:.2}%",
+                    (best - optimal_length) / optimal_length * 100.0
+                ));
+            }
+        }
+        if let Some(e) = &self.tsplib_error {
+            ui.add(Label::new(RichText::new(e).color(Color32::RED)));
+        }
+    }
+    fn export_genealogy(&self, json: bool) -> std::io::Result<()> {
+        let genealogy = Genealogy::new(&self.islands);
+        let data = if json {
+            serde_json::to_string(&genealogy)?
+        } else {
+            let mut highlight = vec![];
+            if let Some(best) = genealogy.best() {
+                highlight = genealogy.ancestry(best);
+                highlight.push(best);
+            }
+            genealogy.to_dot(&highlight)
+        };
+        std::fs::write(&self.genealogy_path, data)
+    }
+    fn genealogy_ui(&mut self, ui: &mut Ui) {
+        let enabled = !self.islands.is_empty();
+        if ui
+            .add_enabled(enabled, egui::Button::new("Best path ancestry"))
+            .clicked()
+        {
+            let genealogy = Genealogy::new(&self.islands);
+            self.best_ancestry = genealogy.best().map(|best| {
+                let ancestry = genealogy.ancestry(best);
+                (ancestry.len(), genealogy.origins(&ancestry))
+            });
+        }
+        if let Some((ancestors, origins)) = &self.best_ancestry {
+            ui.label(format!("Ancestors of the best path: {}", ancestors));
+            for (origin, amount) in origins {
+                ui.label(format!("{}: {}", origin, amount));
+            }
+        }
+        ui.label("Genealogy save path");
+        ui.text_edit_singleline(&mut self.genealogy_path);
+        ui.horizontal(|ui| {
+            for (json, text) in [(false, "Export DOT"), (true, "Export JSON")] {
+                if ui.add_enabled(enabled, egui::Button::new(text)).clicked() {
+                    self.genealogy_error = self.export_genealogy(json).err().map(|e| e.to_string());
+                }
+            }
+        });
+        if let Some(e) = &self.genealogy_error {
+            ui.add(Label::new(RichText::new(e).color(Color32::RED)));
+        }
+    }
+    // Plots of the statistics of every population of the shown island
+    fn stats_window(&mut self, ctx: &Context) {
+        let Some(solution) = &self.solution else {
+            return;
+        };
+        let line = |name: &str, value: fn(&GenerationStats) -> f32| {
+            let points = solution
+                .iterations
+                .iter()
+                .enumerate()
+                .map(|(i, x)| [i as f64, value(&x.stats) as f64])
+                .collect::<Vec<_>>();
+            Line::new(PlotPoints::from(points)).name(name)
+        };
+        egui::Window::new("Statistics")
+            .open(&mut self.show_stats)
+            .show(ctx, |ui| {
+                ui.label("Path length");
+                Plot::new("path_length")
+                    .height(200.0)
+                    .legend(Legend::default())
+                    .show(ui, |plot_ui| {
+                        plot_ui.line(line("Best", |x| x.best));
+                        plot_ui.line(line("Mean", |x| x.mean));
+                        plot_ui.line(line("Median", |x| x.median));
+                        plot_ui.line(line("Worst", |x| x.worst));
+                        plot_ui.line(line("Standard deviation", |x| x.std_dev));
+                    });
+                ui.label("Diversity");
+                Plot::new("diversity")
+                    .height(200.0)
+                    .include_y(0.0)
+                    .legend(Legend::default())
+                    .show(ui, |plot_ui| {
+                        plot_ui.line(line("Unique tours", |x| x.unique as f32));
+                        plot_ui.line(line("Mean different edges", |x| x.mean_distance));
+                    });
+                let stats = &solution.iterations[self.population_i].stats;
+                ui.label(format!("Population #{}", self.population_i));
+                for (origin, uses, shorter) in &stats.operators {
+                    ui.label(format!(
+                        "{}: {} of {} offspring shorter than their parents",
+                        origin, shorter, uses
+                    ));
+                }
+            });
     }
     fn ui_settings(&mut self, ui: &mut Ui) {
         if ui
@@ -197,13 +754,19 @@ impl GeneticApp {
         });
         self.g.g.edge_weights_mut().for_each(|x| {
             x.clone_from(
-                &Edge::new(x.data().unwrap().clone())
+                &Edge::new(*x.data().unwrap())
                     .with_color(Color32::from_rgba_unmultiplied(128, 128, 128, 0)),
             );
         });
     }
     fn reset_graph(&mut self) {
         self.solution = None;
+        self.islands = vec![];
+        self.best_ancestry = None;
+        self.promise = None;
+        if let Some(control) = self.control.take() {
+            control.cancel();
+        }
         self.reset_graph_color();
     }
     fn color_chromosome(&mut self, chromosome: &TSPChromosome, color: Color32) {
@@ -252,10 +815,21 @@ impl GeneticApp {
                     );
                 }
             }
-            TSPChromosomeType::Mutation(parent) => {
-                if self.show_parent_1 {
+            TSPChromosomeType::Mutation(parent, _) | TSPChromosomeType::Survivor(parent)
+                if self.show_parent_1 =>
+            {
+                self.color_chromosome(
+                    &self.get_chromosome(parent.population_index, parent.chromosome_index),
+                    Color32::from_rgba_unmultiplied(126, 238, 198, 128),
+                );
+            }
+            TSPChromosomeType::Migration(island, parent) if self.show_parent_1 => {
+                // the source island can stop before the population of the migrant
+                if let Some(iteration) =
+                    self.islands[island].iterations.get(parent.population_index)
+                {
                     self.color_chromosome(
-                        &self.get_chromosome(parent.population_index, parent.chromosome_index),
+                        &index_to_chromosome(iteration, parent.chromosome_index),
                         Color32::from_rgba_unmultiplied(126, 238, 198, 128),
                     );
                 }
@@ -264,73 +838,53 @@ impl GeneticApp {
         }
     }
     fn update_graph(&mut self) {
-        match &self.solution {
-            Some(v) => {
-                let v = &v.iterations;
-                let iteration = &v[self.population_i];
-                let chromosome = &index_to_chromosome(iteration, self.chromosome_i);
+        if let Some(v) = &self.solution {
+            let v = &v.iterations;
+            let iteration = &v[self.population_i];
+            let chromosome = &index_to_chromosome(iteration, self.chromosome_i);
 
-                for i in self.g.g.node_indices().collect::<Vec<_>>() {
-                    let node = self.g.g.node_weight_mut(i).unwrap();
-                    node.clone_from(&node.clone().with_color(Color32::from_rgb(85, 24, 93)));
-                }
-                if self.show_parent_1 || self.show_parent_2 {
-                    self.update_parents();
-                }
-                if self.show_current {
-                    self.color_chromosome(
-                        chromosome,
-                        Color32::from_rgba_unmultiplied(255, 213, 36, 128),
-                    );
-                }
+            for i in self.g.g.node_indices().collect::<Vec<_>>() {
+                let node = self.g.g.node_weight_mut(i).unwrap();
+                node.clone_from(&node.clone().with_color(Color32::from_rgb(85, 24, 93)));
+            }
+            if self.show_parent_1 || self.show_parent_2 {
+                self.update_parents();
+            }
+            if self.show_current {
+                self.color_chromosome(
+                    chromosome,
+                    Color32::from_rgba_unmultiplied(255, 213, 36, 128),
+                );
             }
-            None => (),
         }
     }
     fn handle_changes(&mut self) {
         let mut node_id: Option<NodeIndex> = None;
         self.changes_receiver.try_iter().for_each(|ch| {
-            if let Change::Node(ChangeNode::Location { id, old, new }) = ch.clone() {
+            if let Change::Node(ChangeNode::Location { id, .. }) = ch.clone() {
                 node_id = Some(id);
             }
         });
-        match node_id {
-            Some(id) => {
-                self.reset_graph();
-                let neighbors = self.g.g.neighbors_undirected(id).collect::<Vec<_>>();
-                neighbors.iter().for_each(|n| {
-                    self.remove_edges(id, *n);
-                });
-                self.connect_node(id)
-            }
-            None => (),
+        if let Some(id) = node_id {
+            self.reset_graph();
+            let neighbors = self.g.g.neighbors_undirected(id).collect::<Vec<_>>();
+            neighbors.iter().for_each(|n| {
+                self.remove_edges(id, *n);
+            });
+            self.connect_node(id)
         }
     }
-    fn build_edges_dist(&self) -> Arc<HashMap<NodeIndex, HashMap<NodeIndex, f32>>> {
-        let mut dict: HashMap<NodeIndex, HashMap<NodeIndex, f32>> = HashMap::default();
-        let indeces = self.g.g.node_indices().collect::<Vec<_>>();
-        for i in &indeces {
-            dict.insert(*i, HashMap::default());
+    // Distances indexed by `NodeIndex::index`, removed nodes leave unused positions
+    fn build_distances(&self) -> Arc<DistanceMatrix> {
+        let mut distances = DistanceMatrix::new(self.g.g.node_bound());
+        for e in self.g.g.edge_references() {
+            distances.set(
+                e.source().index(),
+                e.target().index(),
+                e.weight().data().unwrap().distance,
+            );
         }
-        indeces
-            .iter()
-            .take(indeces.len() - 1)
-            .enumerate()
-            .for_each(|(i, x)| {
-                for j in &indeces[i + 1..indeces.len()] {
-                    let e = self
-                        .g
-                        .g
-                        .edges_connecting(*x, *j)
-                        .map(|x| x.id())
-                        .collect::<Vec<_>>()[0];
-                    let e = self.g.g.edge_weight(e).unwrap();
-                    let distance = e.data().unwrap().distance;
-                    dict.get_mut(x).unwrap().insert(*j, distance);
-                    dict.get_mut(j).unwrap().insert(*x, distance);
-                }
-            });
-        Arc::new(dict)
+        Arc::new(distances)
     }
 }
 
@@ -338,7 +892,7 @@ fn index_to_chromosome(iteration: &TSPIteration, index: usize) -> TSPChromosome
     if index < iteration.old.len() {
         iteration.old[index].clone()
     } else {
-        iteration.new[index % iteration.old.len()].clone()
+        iteration.new[index - iteration.old.len()].clone()
     }
 }
 
@@ -364,131 +918,237 @@ impl App for GeneticApp {
                                 self.chromosome_i = 0;
                                 self.genetic_options.mutation_p =
                                     1.0 - self.genetic_options.crossover_p;
-                                let res: TSPSolution = solve(
-                                    self.genetic_options.population_amount,
-                                    TSPChromosome::generate_random_population(
-                                        self.g.g.node_indices().collect(),
-                                        self.genetic_options.population_size,
-                                        self.build_edges_dist(),
-                                    ),
-                                    self.genetic_options.crossover_p,
-                                    self.genetic_options.mutation_p,
+                                let distances = self.build_distances();
+                                let local_search = self.local_search();
+                                let neighbour_lists = local_search
+                                    .neighbours
+                                    .map(|k| distances.neighbour_lists(k));
+                                let nodes = self.g.g.node_indices().collect::<Vec<_>>();
+                                let options = self.genetic_options.clone();
+                                let mutation_mixes = (0..self.genetic_options.islands)
+                                    .map(|_| SharedMutationMix::new(self.mutation_mix()))
+                                    .collect::<Vec<_>>();
+                                self.mutation_mixes = mutation_mixes.clone();
+                                let control = self.run_control();
+                                self.control = Some(control.clone());
+                                self.promise = Some(poll_promise::Promise::spawn_thread(
+                                    "Genetic algorithm calculation",
+                                    move || {
+                                        let mut rng = StdRng::seed_from_u64(options.seed);
+                                        let islands = mutation_mixes
+                                            .iter()
+                                            .map(|mutation_mix| {
+                                                TSPChromosome::generate_random_population(
+                                                    nodes.clone(),
+                                                    options.population_size,
+                                                    distances.clone(),
+                                                    &mut rng,
+                                                )
+                                                .into_iter()
+                                                .map(|x| {
+                                                    x.with_crossover(options.crossover)
+                                                        .with_mutation(mutation_mix.clone())
+                                                })
+                                                .collect()
+                                            })
+                                            .collect();
+                                        island::solve(
+                                            options.population_amount,
+                                            islands,
+                                            options.crossover_p,
+                                            options.mutation_p,
+                                            &options.selection,
+                                            options.replacement,
+                                            options.migration,
+                                            |chromosome: &mut TSPChromosome| {
+                                                if local_search.is_enabled() {
+                                                    chromosome.improve(
+                                                        &local_search,
+                                                        neighbour_lists.as_deref(),
+                                                    );
+                                                }
+                                            },
+                                            &control,
+                                            &mut rng,
+                                        )
+                                    },
+                                ));
+                                self.solution_seed = self.genetic_options.seed;
+                            }
+                            self.run_control_ui(ui);
+                            if self.islands.len() > 1 {
+                                let island_before = self.island_i;
+                                ui.add(
+                                    Slider::new(&mut self.island_i, 0..=(self.islands.len() - 1))
+                                        .text("Island"),
                                 );
-                                self.solution = Some(res);
-                                self.update_graph();
+                                if self.island_i != island_before {
+                                    self.solution = Some(self.islands[self.island_i].clone());
+                                    self.population_i = 0;
+                                    self.chromosome_i = 0;
+                                    self.reset_graph_color();
+                                    self.update_graph();
+                                }
                             }
-                            match &self.solution {
-                                Some(solution) => {
-                                    let v = &solution.iterations;
-                                    let iteration_before = self.population_i;
-                                    let chromosome = index_to_chromosome(
-                                        &v[self.population_i],
-                                        self.chromosome_i,
-                                    );
-                                    ui.add(
-                                        Slider::new(&mut self.population_i, 0..=(v.len() - 1))
-                                            .text("Population(Iteration)"),
-                                    );
-                                    let chromosome_before = self.chromosome_i;
-                                    ui.add(
-                                        Slider::new(
-                                            &mut self.chromosome_i,
-                                            0..=(v.first().unwrap().old.len() * 2 - 1),
-                                        )
-                                        .text("Chromosome"),
-                                    );
-
-                                    ui.label(format!(
-                                        "Best path: Population#{} Chromosome#{} / {}",
-                                        solution.best_population_i,
-                                        solution.best_chromosome_i,
-                                        v[solution.best_population_i].old
-                                            [solution.best_chromosome_i]
-                                            .path_length,
-                                    ));
-                                    ui.label(format!(
-                                        "Best path for Population: Chromosome#{} / {}",
-                                        v[self.population_i].best_chromosome_i,
-                                        index_to_chromosome(
-                                            &v[self.population_i],
-                                            v[self.population_i].best_chromosome_i
-                                        )
-                                        .path_length
-                                    ));
-                                    ui.label(format!(
-                                        "Current Chromosome path: {}",
-                                        chromosome.path_length
-                                    ));
+                            if let Some(solution) = &self.solution {
+                                let v = &solution.iterations;
+                                let iteration_before = self.population_i;
+                                let chromosome =
+                                    index_to_chromosome(&v[self.population_i], self.chromosome_i);
+                                ui.add(
+                                    Slider::new(&mut self.population_i, 0..=(v.len() - 1))
+                                        .text("Population(Iteration)"),
+                                );
+                                let chromosome_before = self.chromosome_i;
+                                ui.add(
+                                    Slider::new(
+                                        &mut self.chromosome_i,
+                                        0..=(v[self.population_i].old.len()
+                                            + v[self.population_i].new.len()
+                                            - 1),
+                                    )
+                                    .text("Chromosome"),
+                                );
 
-                                    match chromosome.chromosome_type {
-                                        TSPChromosomeType::NoHistory => {
-                                            ui.label(RichText::new("No parents(init)").strong());
-                                        }
-                                        TSPChromosomeType::Crossover(parent_1, parent_2) => {
-                                            ui.label(
-                                                RichText::new(format!(
-                                                    "Crossover: C#{}(P#{}) / C#{}(P#{})",
-                                                    parent_1.chromosome_index,
-                                                    parent_1.population_index,
-                                                    parent_2.chromosome_index,
-                                                    parent_2.population_index,
-                                                ))
-                                                .strong(),
-                                            );
-                                        }
-                                        TSPChromosomeType::Mutation(parent) => {
-                                            ui.label(
-                                                RichText::new(format!(
-                                                    "Mutation: C#{}(P#{})",
-                                                    parent.chromosome_index,
-                                                    parent.population_index
-                                                ))
-                                                .strong(),
-                                            );
-                                        }
+                                ui.label(format!("Seed: {}", self.solution_seed));
+                                ui.label(format!(
+                                    "Best path: Population#{} Chromosome#{} / {}",
+                                    solution.best_population_i,
+                                    solution.best_chromosome_i,
+                                    v[solution.best_population_i].old[solution.best_chromosome_i]
+                                        .path_length,
+                                ));
+                                ui.label(format!(
+                                    "Best path for Population: Chromosome#{} / {}",
+                                    v[self.population_i].best_chromosome_i,
+                                    index_to_chromosome(
+                                        &v[self.population_i],
+                                        v[self.population_i].best_chromosome_i
+                                    )
+                                    .path_length
+                                ));
+                                ui.label(format!(
+                                    "Current Chromosome path: {}",
+                                    chromosome.path_length
+                                ));
+                                if let Some(mix) = self.mutation_mixes.get(self.island_i) {
+                                    for (mutation, uses, successes) in mix.lock().success_rates() {
+                                        ui.label(format!(
+                                            "{}: {} of {} mutants better",
+                                            mutation, successes, uses
+                                        ));
                                     }
+                                }
 
-                                    if ui.button("Show best path").clicked() {
-                                        self.population_i = solution.best_population_i;
-                                        self.chromosome_i = solution.best_chromosome_i;
-                                        self.reset_graph_color();
-                                        self.update_graph();
-                                    } else {
-                                        if self.population_i != iteration_before
-                                            || self.chromosome_i != chromosome_before
-                                        {
-                                            self.reset_graph_color();
-                                            if self.population_i.abs_diff(iteration_before) != 0 {
-                                                self.chromosome_i = 0;
-                                            }
-                                            self.update_graph();
-                                        }
+                                match chromosome.chromosome_type {
+                                    TSPChromosomeType::NoHistory => {
+                                        ui.label(RichText::new("No parents(init)").strong());
                                     }
-                                    if ui
-                                        .checkbox(&mut self.show_parent_1, "Show parent 1")
-                                        .changed()
-                                    {
-                                        self.reset_graph_color();
-                                        self.update_graph();
+                                    TSPChromosomeType::Crossover(parent_1, parent_2) => {
+                                        ui.label(
+                                            RichText::new(format!(
+                                                "Crossover: C#{}(P#{}) / C#{}(P#{})",
+                                                parent_1.chromosome_index,
+                                                parent_1.population_index,
+                                                parent_2.chromosome_index,
+                                                parent_2.population_index,
+                                            ))
+                                            .strong(),
+                                        );
                                     }
-                                    if ui
-                                        .checkbox(&mut self.show_parent_2, "Show parent 2")
-                                        .changed()
-                                    {
-                                        self.reset_graph_color();
-                                        self.update_graph();
+                                    TSPChromosomeType::Mutation(parent, operator) => {
+                                        ui.label(
+                                            RichText::new(format!(
+                                                "Mutation ({}): C#{}(P#{})",
+                                                operator,
+                                                parent.chromosome_index,
+                                                parent.population_index
+                                            ))
+                                            .strong(),
+                                        );
                                     }
-                                    if ui
-                                        .checkbox(&mut self.show_current, "Show current")
-                                        .changed()
+                                    TSPChromosomeType::Survivor(parent) => {
+                                        ui.label(
+                                            RichText::new(format!(
+                                                "Survivor: C#{}(P#{})",
+                                                parent.chromosome_index, parent.population_index
+                                            ))
+                                            .strong(),
+                                        );
+                                    }
+                                    TSPChromosomeType::Migration(island, parent) => {
+                                        ui.label(
+                                            RichText::new(format!(
+                                                "Migrant: C#{}(P#{}) of island #{}",
+                                                parent.chromosome_index,
+                                                parent.population_index,
+                                                island
+                                            ))
+                                            .strong(),
+                                        );
+                                    }
+                                }
+
+                                if ui.button("Show best path").clicked() {
+                                    self.population_i = solution.best_population_i;
+                                    self.chromosome_i = solution.best_chromosome_i;
+                                    self.reset_graph_color();
+                                    self.update_graph();
+                                } else {
+                                    if self.population_i != iteration_before
+                                        || self.chromosome_i != chromosome_before
                                     {
                                         self.reset_graph_color();
+                                        if self.population_i.abs_diff(iteration_before) != 0 {
+                                            self.chromosome_i = 0;
+                                        }
                                         self.update_graph();
                                     }
                                 }
-                                None => (),
+                                if ui
+                                    .checkbox(&mut self.show_parent_1, "Show parent 1")
+                                    .changed()
+                                {
+                                    self.reset_graph_color();
+                                    self.update_graph();
+                                }
+                                if ui
+                                    .checkbox(&mut self.show_parent_2, "Show parent 2")
+                                    .changed()
+                                {
+                                    self.reset_graph_color();
+                                    self.update_graph();
+                                }
+                                if ui
+                                    .checkbox(&mut self.show_current, "Show current")
+                                    .changed()
+                                {
+                                    self.reset_graph_color();
+                                    self.update_graph();
+                                }
+                                ui.checkbox(&mut self.show_stats, "Show statistics");
                             }
                         });
+                    CollapsingHeader::new("TSPLIB")
+                        .default_open(false)
+                        .show(ui, |ui| {
+                            ui.add_space(10.0);
+
+                            ui.label("TSPLIB files");
+                            ui.separator();
+
+                            self.tsplib_ui(ui);
+                        });
+                    CollapsingHeader::new("Genealogy")
+                        .default_open(false)
+                        .show(ui, |ui| {
+                            ui.add_space(10.0);
+
+                            ui.label("Parents of every chromosome");
+                            ui.separator();
+
+                            self.genealogy_ui(ui);
+                        });
                     CollapsingHeader::new("Ui")
                         .default_open(true)
                         .show(ui, |ui| {
@@ -501,6 +1161,9 @@ impl App for GeneticApp {
                         });
                 });
             });
+        if self.show_stats {
+            self.stats_window(ctx);
+        }
         egui::CentralPanel::default().show(ctx, |ui| {
             let settings_navigation = &egui_graphs::SettingsNavigation::new()
                 .with_fit_to_screen_enabled(self.settings_navigation.fit_to_screen_enabled)
@@ -30,4 +30,4 @@ impl Default for SettingsStyle {
             labels_always: false,
         }
     }
-}
\ No newline at end of file
+}
@@ -1,23 +1,8 @@
-use crossbeam::channel::{unbounded, Receiver};
+use ai_core::kosko;
 use eframe::{
-    egui::{
-        Align, CentralPanel, CollapsingHeader, Color32, Context, Grid, Label, Layout, RichText,
-        ScrollArea, SidePanel, Slider, Ui, Vec2,
-    },
+    egui::{Align, CentralPanel, CollapsingHeader, Context, Layout, ScrollArea, SidePanel, Slider},
     run_native, App, CreationContext,
 };
-use image;
-use image::GenericImageView;
-use notify::{Error, Event, RecommendedWatcher, RecursiveMode, Watcher};
-
-use std::time::Duration;
-
-mod kosko;
-
-#[derive(Debug, Clone)]
-struct LayerOptions {
-    neurons: usize,
-}
 
 pub struct NeuroApp {
     network: Option<kosko::Network>,
@@ -28,35 +13,11 @@ pub struct NeuroApp {
     input: Vec<i32>,
     samples: Vec<Vec<i32>>,
     answers: Vec<Vec<i32>>,
-    toasts: egui_notify::Toasts,
 }
 
 impl NeuroApp {
     fn new(_: &CreationContext<'_>) -> Self {
-        let (changes_sender, changes_receiver) = unbounded();
-        let mut now = std::time::SystemTime::now();
-        let duration = std::time::Duration::from_millis(500);
-        let mut watcher: RecommendedWatcher = Watcher::new(
-            move |result: Result<Event, Error>| match result {
-                Ok(event) => {
-                    if event.kind.is_modify() {
-                        if now.elapsed().unwrap() > duration {
-                            now = std::time::SystemTime::now();
-                            changes_sender.send(event);
-                        }
-                    }
-                }
-                Err(e) => (),
-            },
-            notify::Config::default()
-                .with_compare_contents(true)
-                .with_poll_interval(Duration::from_millis(500)),
-        )
-        .unwrap();
-        let mut layers_options = vec![LayerOptions { neurons: 1 }; 16];
-        layers_options[0].neurons = 784;
-        layers_options[1].neurons = 2;
-        let app = Self {
+        Self {
             network: None,
             sample_amount: 1,
             answer_size: 1,
@@ -65,9 +26,7 @@ impl NeuroApp {
             samples: vec![vec![1]],
             answers: vec![vec![1]],
             solution: None,
-            toasts: egui_notify::Toasts::default(),
-        };
-        app
+        }
     }
 }
 
@@ -191,28 +150,22 @@ impl App for NeuroApp {
                             ui.add_space(12.0);
                             for j in 0..self.answers[i].len() {
                                 if ui.button(format!("{}", self.answers[i][j])).clicked() {
-                                    self.answers[i][j] = if self.answers[i][j] == 1 {
-                                        -1
-                                    } else {
-                                        1
-                                    }
+                                    self.answers[i][j] =
+                                        if self.answers[i][j] == 1 { -1 } else { 1 }
                                 }
                             }
                         });
                     }
-                    match &self.network {
-                        Some(n) => {
-                            ui.label("Weights:");
-                            let shape = n.w.shape();
-                            for i in 0..shape[0] {
-                                ui.horizontal(|ui| {
-                                    for j in 0..shape[1] {
-                                        ui.button(format!("{}", n.w.get((i, j)).unwrap()));
-                                    }
-                                });
-                            }
+                    if let Some(n) = &self.network {
+                        ui.label("Weights:");
+                        let shape = n.w.shape();
+                        for i in 0..shape[0] {
+                            ui.horizontal(|ui| {
+                                for j in 0..shape[1] {
+                                    let _ = ui.button(format!("{}", n.w.get((i, j)).unwrap()));
+                                }
+                            });
                         }
-                        None => (),
                     }
                 });
                 ui.add_space(12.0);
@@ -222,31 +175,25 @@ impl App for NeuroApp {
                         for i in 0..self.input.len() {
                             if ui.button(format!("{}", self.input[i])).clicked() {
                                 self.input[i] = if self.input[i] == 1 { -1 } else { 1 };
-                                match &self.network {
-                                    Some(n) => {
-                                        self.solution = Some(n.predict(&ndarray::Array1::from_vec(
-                                            self.input.clone(),
-                                        )))
-                                    }
-                                    None => (),
+                                if let Some(n) = &self.network {
+                                    self.solution = Some(
+                                        n.predict(&ndarray::Array1::from_vec(self.input.clone())),
+                                    )
                                 }
                             }
                         }
                     });
                 });
-                match self.solution.clone() {
-                    Some(output) => {
-                        ui.vertical(|ui| {
-                            ui.add_space(20.0);
-                            ui.label("Solution:");
-                            ui.horizontal(|ui| {
-                                for i in 0..output.len() {
-                                    ui.button(format!("{}", output[i]));
-                                }
-                            });
+                if let Some(output) = self.solution.clone() {
+                    ui.vertical(|ui| {
+                        ui.add_space(20.0);
+                        ui.label("Solution:");
+                        ui.horizontal(|ui| {
+                            for value in &output {
+                                let _ = ui.button(format!("{}", value));
+                            }
                         });
-                    }
-                    None => (),
+                    });
                 }
             });
         });
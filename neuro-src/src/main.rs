@@ -1,23 +1,20 @@
 use crossbeam::channel::{unbounded, Receiver};
 use eframe::{
     egui::{
-        Align, CentralPanel, CollapsingHeader, Color32, Context, Grid, Label, Layout, RichText,
-        ScrollArea, SidePanel, Slider, Ui, Vec2,
+        Align, CentralPanel, CollapsingHeader, Color32, Context, DragValue, Grid, Label, Layout,
+        RichText, ScrollArea, SidePanel, Slider, Ui, Vec2,
     },
     run_native, App, CreationContext,
 };
-use image;
 use image::GenericImageView;
 use notify::{Error, Event, RecommendedWatcher, RecursiveMode, Watcher};
+use rand::{rngs::StdRng, SeedableRng};
 
 use std::time::Duration;
 
-mod activation;
-mod error_func;
-mod neuro;
-
-use activation::*;
-use neuro::*;
+use ai_core::activation::*;
+use ai_core::neuro::{self, *};
+use ai_core::run_control::RunControl;
 
 #[derive(Debug, Clone)]
 struct LayerOptions {
@@ -32,6 +29,8 @@ pub struct NeuroApp {
     learning_norm: f32,
     amount_epoch: usize,
     batch_size: usize,
+    // Seed of the initial weights
+    seed: u64,
     // Path to input file
     input_file_path: String,
     // Path to training data or json-network
@@ -45,6 +44,8 @@ pub struct NeuroApp {
     watching: Option<String>,
     // Promise for training function
     promise: Option<poll_promise::Promise<NeuralNetwork>>,
+    // Stops and pauses the training
+    control: RunControl,
     // Notifications
     toasts: egui_notify::Toasts,
     // Layers Options
@@ -57,17 +58,14 @@ impl NeuroApp {
         let (changes_sender, changes_receiver) = unbounded();
         let mut now = std::time::SystemTime::now();
         let duration = std::time::Duration::from_millis(500);
-        let mut watcher: RecommendedWatcher = Watcher::new(
-            move |result: Result<Event, Error>| match result {
-                Ok(event) => {
-                    if event.kind.is_modify() {
-                        if now.elapsed().unwrap() > duration {
-                            now = std::time::SystemTime::now();
-                            changes_sender.send(event);
-                        }
+        let watcher: RecommendedWatcher = Watcher::new(
+            move |result: Result<Event, Error>| {
+                if let Ok(event) = result {
+                    if event.kind.is_modify() && now.elapsed().unwrap() > duration {
+                        now = std::time::SystemTime::now();
+                        let _ = changes_sender.send(event);
                     }
                 }
-                Err(e) => (),
             },
             notify::Config::default()
                 .with_compare_contents(true)
@@ -77,26 +75,28 @@ impl NeuroApp {
         let mut layers_options = vec![LayerOptions { neurons: 1 }; 16];
         layers_options[0].neurons = 784;
         layers_options[1].neurons = 2;
-        let mut app = Self {
-            changes_receiver: changes_receiver,
+
+        Self {
+            changes_receiver,
             layers_activation: ActivationFunc::Sigmoid,
             final_activation: ActivationFunc::Sigmoid,
             learning_norm: 0.5,
             batch_size: 1,
             amount_epoch: 1000,
+            seed: rand::random::<u32>() as u64,
             input_file_path: "".into(),
             train_data_folder_path: "".into(),
             network: None,
-            watcher: watcher,
+            watcher,
             watching: None,
             promise: None,
+            control: RunControl::new(),
             toasts: egui_notify::Toasts::default(),
-            layers_options: layers_options,
+            layers_options,
             picked_layers: 2,
             solution: None,
             best_solution: None,
-        };
-        app
+        }
     }
     fn handle_changes(&mut self) {
         if self.network.is_none() || self.watching.is_none() {
@@ -119,7 +119,7 @@ impl NeuroApp {
 }
 
 fn get_file_data(file_path: String) -> Vec<f32> {
-    let mut img = image::open(file_path).unwrap();
+    let img = image::open(file_path).unwrap();
     let mut input: Vec<f32> = vec![0.0; 784];
     for pixel in img.pixels() {
         input[pixel.0 as usize * 28 + pixel.1 as usize] = 1.0 - pixel.2 .0[0] as f32 / 255.0;
@@ -127,6 +127,7 @@ fn get_file_data(file_path: String) -> Vec<f32> {
     input
 }
 
+#[allow(clippy::too_many_arguments)]
 fn train(
     path: &std::path::Path,
     layers_activation: ActivationFunc,
@@ -135,6 +136,8 @@ fn train(
     epoch: usize,
     batch_size: usize,
     learning_rate: f32,
+    seed: u64,
+    control: RunControl,
 ) -> NeuralNetwork {
     let file = std::fs::File::open(path.join(std::path::Path::new("train.json"))).unwrap();
     let reader = std::io::BufReader::new(file);
@@ -158,13 +161,14 @@ fn train(
             }
         })
         .collect();
-    let mut net = neuro::NeuralNetwork::new(layers_options, labels)
-        .with_activation(layers_activation)
-        .with_last_activation(final_activation)
-        .with_epoch(epoch)
-        .with_batch_size(batch_size);
+    let mut net =
+        neuro::NeuralNetwork::new(layers_options, labels, &mut StdRng::seed_from_u64(seed))
+            .with_activation(layers_activation)
+            .with_last_activation(final_activation)
+            .with_epoch(epoch)
+            .with_batch_size(batch_size);
 
-    net.train(samples, learning_rate);
+    net.train(samples, learning_rate, &control);
     net
 }
 
@@ -266,6 +270,17 @@ impl App for NeuroApp {
                                 ui.separator();
                                 let r = ui.add(Slider::new(&mut self.amount_epoch, 1..=50000));
 
+                                ui.add_space(10.0);
+
+                                ui.label("Seed");
+                                ui.separator();
+                                ui.horizontal(|ui| {
+                                    ui.add(DragValue::new(&mut self.seed));
+                                    if ui.button("New seed").clicked() {
+                                        self.seed = rand::random::<u32>() as u64;
+                                    }
+                                });
+
                                 ui.add_space(10.0);
                                 r
                             });
@@ -273,103 +288,107 @@ impl App for NeuroApp {
                             ui.label("Actions");
                             ui.separator();
                             ui.horizontal(|ui| {
-                                if ui.button("Learn").clicked() {
-                                    if self.promise.is_none() {
-                                        let path_str = self.train_data_folder_path.clone();
-                                        if std::path::Path::new(&path_str).exists() {
-                                            if std::path::Path::new(&path_str).is_dir() {
-                                                self.network = None;
-                                                self.best_solution = None;
-                                                self.solution = None;
-                                                let layers_activation =
-                                                    self.layers_activation.clone();
-                                                let final_activation =
-                                                    self.final_activation.clone();
-                                                let layers_options = self
-                                                    .layers_options
-                                                    .clone()
-                                                    .into_iter()
-                                                    .take(self.picked_layers)
-                                                    .map(|x| x.neurons)
-                                                    .collect();
-                                                let amount_epoch = self.amount_epoch.clone();
-                                                let batch_size = self.batch_size.clone();
-                                                let learning_norm = self.learning_norm.clone();
-                                                self.promise = Some(poll_promise::Promise::<
-                                                    NeuralNetwork,
-                                                >::spawn_thread(
-                                                    "Neural network training",
-                                                    move || {
-                                                        train(
-                                                            std::path::Path::new(&path_str),
-                                                            layers_activation,
-                                                            final_activation,
-                                                            layers_options,
-                                                            amount_epoch,
-                                                            batch_size,
-                                                            learning_norm,
-                                                        )
-                                                    },
-                                                ));
-                                            } else if std::path::Path::new(&path_str).is_file() {
-                                                self.network = None;
-                                                self.best_solution = None;
-                                                self.solution = None;
-                                                let file = std::fs::File::open(
-                                                    std::path::Path::new(&path_str),
-                                                )
-                                                .unwrap();
-                                                let reader = std::io::BufReader::new(file);
-                                                let net: NeuralNetworkJson =
-                                                    serde_json::from_reader(reader).unwrap();
-                                                let net = json_to_network(net);
-                                                self.picked_layers = net.layers.len();
-                                                self.layers_options
-                                                    .iter_mut()
-                                                    .take(self.picked_layers)
-                                                    .zip(net.layers.iter().take(self.picked_layers))
-                                                    .for_each(|(opt, layer)| {
-                                                        opt.neurons = layer.neurons();
-                                                    });
-                                                self.amount_epoch = net.epoch();
-                                                self.batch_size = net.batch_size();
-                                                self.learning_norm = net.learning_rate();
-                                                self.layers_activation = net.activation();
-                                                self.final_activation = net.final_activation();
-                                                self.network = Some(net);
-                                            }
+                                if ui.button("Learn").clicked() && self.promise.is_none() {
+                                    let path_str = self.train_data_folder_path.clone();
+                                    if std::path::Path::new(&path_str).exists() {
+                                        if std::path::Path::new(&path_str).is_dir() {
+                                            self.network = None;
+                                            self.best_solution = None;
+                                            self.solution = None;
+                                            let layers_activation = self.layers_activation.clone();
+                                            let final_activation = self.final_activation.clone();
+                                            let layers_options = self
+                                                .layers_options
+                                                .clone()
+                                                .into_iter()
+                                                .take(self.picked_layers)
+                                                .map(|x| x.neurons)
+                                                .collect();
+                                            let amount_epoch = self.amount_epoch;
+                                            let batch_size = self.batch_size;
+                                            let learning_norm = self.learning_norm;
+                                            let seed = self.seed;
+                                            self.control = RunControl::new();
+                                            let control = self.control.clone();
+                                            self.promise = Some(poll_promise::Promise::<
+                                                NeuralNetwork,
+                                            >::spawn_thread(
+                                                "Neural network training",
+                                                move || {
+                                                    train(
+                                                        std::path::Path::new(&path_str),
+                                                        layers_activation,
+                                                        final_activation,
+                                                        layers_options,
+                                                        amount_epoch,
+                                                        batch_size,
+                                                        learning_norm,
+                                                        seed,
+                                                        control,
+                                                    )
+                                                },
+                                            ));
+                                        } else if std::path::Path::new(&path_str).is_file() {
+                                            self.best_solution = None;
+                                            self.solution = None;
+                                            let file = std::fs::File::open(std::path::Path::new(
+                                                &path_str,
+                                            ))
+                                            .unwrap();
+                                            let reader = std::io::BufReader::new(file);
+                                            let net: NeuralNetworkJson =
+                                                serde_json::from_reader(reader).unwrap();
+                                            let net = json_to_network(net);
+                                            self.picked_layers = net.layers.len();
+                                            self.layers_options
+                                                .iter_mut()
+                                                .take(self.picked_layers)
+                                                .zip(net.layers.iter().take(self.picked_layers))
+                                                .for_each(|(opt, layer)| {
+                                                    opt.neurons = layer.neurons();
+                                                });
+                                            self.amount_epoch = net.epoch();
+                                            self.batch_size = net.batch_size();
+                                            self.learning_norm = net.learning_rate();
+                                            self.layers_activation = net.activation();
+                                            self.final_activation = net.final_activation();
+                                            self.network = Some(net);
                                         }
                                     }
                                 }
-                                if ui.button("Drop").clicked() {
-                                    if self.promise.is_none() {
+                                if ui.button("Drop").clicked() && self.promise.is_none() {
+                                    self.train_data_folder_path = "".into();
+                                    self.network = None;
+                                    self.best_solution = None;
+                                    self.solution = None;
+                                }
+                                if ui.button("Save").clicked() && self.promise.is_none() {
+                                    let path = std::path::Path::new(&self.train_data_folder_path);
+                                    if !path.exists() || !path.is_dir() {
+                                        let j = neural_to_json(self.network.as_ref().unwrap());
+                                        std::fs::write(path, serde_json::to_string(&j).unwrap())
+                                            .unwrap();
                                         self.train_data_folder_path = "".into();
                                         self.network = None;
                                         self.best_solution = None;
                                         self.solution = None;
                                     }
                                 }
-                                if ui.button("Save").clicked() {
-                                    if self.promise.is_none() {
-                                        let path =
-                                            std::path::Path::new(&self.train_data_folder_path);
-                                        if !path.exists() || !path.is_dir() {
-                                            let j = neural_to_json(self.network.as_ref().unwrap());
-                                            std::fs::write(
-                                                path,
-                                                serde_json::to_string(&j).unwrap(),
-                                            )
-                                            .unwrap();
-                                            self.train_data_folder_path = "".into();
-                                            self.network = None;
-                                            self.best_solution = None;
-                                            self.solution = None;
-                                        }
-                                    }
-                                }
                             });
                             if self.promise.is_some() {
-                                ui.spinner();
+                                ui.horizontal(|ui| {
+                                    ui.spinner();
+                                    if self.control.is_paused() {
+                                        if ui.button("Resume").clicked() {
+                                            self.control.resume();
+                                        }
+                                    } else if ui.button("Pause").clicked() {
+                                        self.control.pause();
+                                    }
+                                    if ui.button("Stop").clicked() {
+                                        self.control.cancel();
+                                    }
+                                });
                             }
                         });
                     CollapsingHeader::new("Layers settings")
@@ -387,7 +406,7 @@ impl App for NeuroApp {
                                 for i in 1..self.picked_layers {
                                     ui.add_space(10.0);
 
-                                    let r = ui.label(format!("Layer {}", i + 1));
+                                    ui.label(format!("Layer {}", i + 1));
                                     ui.separator();
                                     ui.add(Slider::new(
                                         &mut self.layers_options[i].neurons,
@@ -439,26 +458,23 @@ impl App for NeuroApp {
                     ui.text_edit_singleline(&mut self.train_data_folder_path);
                 });
                 ui.add_space(12.0);
-                match self.solution.clone() {
-                    Some(output) => {
-                        let labels = &self.network.as_ref().unwrap().labels();
-                        Grid::new("unique_id_2")
-                            .spacing(Vec2::new(1., 1.))
-                            .show(ui, |ui| {
-                                for i in 0..output.len() {
-                                    if i == self.best_solution.unwrap() {
-                                        ui.label(
-                                            RichText::new(labels[i].clone()).color(Color32::GREEN),
-                                        );
-                                    } else {
-                                        ui.label(RichText::new(labels[i].clone()));
-                                    }
-                                    ui.label(format!("{}", output[i]));
-                                    ui.end_row();
+                if let Some(output) = self.solution.clone() {
+                    let labels = &self.network.as_ref().unwrap().labels();
+                    Grid::new("unique_id_2")
+                        .spacing(Vec2::new(1., 1.))
+                        .show(ui, |ui| {
+                            for i in 0..output.len() {
+                                if i == self.best_solution.unwrap() {
+                                    ui.label(
+                                        RichText::new(labels[i].clone()).color(Color32::GREEN),
+                                    );
+                                } else {
+                                    ui.label(RichText::new(labels[i].clone()));
                                 }
-                            });
-                    }
-                    None => (),
+                                ui.label(format!("{}", output[i]));
+                                ui.end_row();
+                            }
+                        });
                 }
             });
             self.toasts.show(ctx);
@@ -1,10 +1,26 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // Скрывает консоль на Windows
 
-mod simulated_annealing;
+use std::sync::Arc;
 
+use ai_core::{
+    run_control::RunControl,
+    simulated_annealing::{
+        self,
+        bin_packing::BinPackingState,
+        calibration::{Frozen, InitialTemperature},
+        colouring::ColouringState,
+        cooling::{CoolingSchedule, Reheat},
+        function::{Function, FunctionState},
+        queens::QueenState,
+        sudoku::SudokuState,
+        tour::TourState,
+        State,
+    },
+    tsp::DistanceMatrix,
+};
 use eframe::egui;
 use egui::plot::{Legend, Line, Plot, PlotPoints};
-use rand::Rng;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
 fn main() -> Result<(), eframe::Error> {
     env_logger::init(); // Лог в stderr (`RUST_LOG=debug`).
@@ -20,66 +36,145 @@ fn main() -> Result<(), eframe::Error> {
     )
 }
 
-// Состояние задачи о королевах
-#[derive(Debug, Clone)]
-pub struct QueenState {
-    positions: Vec<usize>,
-    n: usize,
+// Задача для решения
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Problem {
+    Queens,
+    Tour,
+    Colouring,
+    BinPacking,
+    Sudoku,
+    Function(Function),
 }
 
-// Реализация состояния задачи о королевах
-impl QueenState {
-    fn new(n: usize) -> Self {
-        Self {
-            positions: vec![],
-            n: n,
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Queens => write!(f, "N queens"),
+            Self::Tour => write!(f, "Travelling salesman"),
+            Self::Colouring => write!(f, "Graph colouring"),
+            Self::BinPacking => write!(f, "Bin packing"),
+            Self::Sudoku => write!(f, "Sudoku"),
+            Self::Function(x) => write!(f, "{} function", x),
         }
     }
 }
 
-// Реализуем свойство State для нашего состояния задачи о королевах
-impl simulated_annealing::State for QueenState {
-    // Переставляем двух королев местами
-    fn changed_state(&self) -> Self {
-        let mut new_positions = self.positions.clone();
-        let first_index = rand::thread_rng().gen_range(0..new_positions.len());
-        let second_index = rand::thread_rng().gen_range(0..new_positions.len());
-        new_positions.swap(first_index, second_index);
+// Параметры отжига, общие для всех задач
+#[derive(Debug, Clone, Copy)]
+struct Annealing {
+    min_temperature: f64,
+    initial_temperature: InitialTemperature,
+    schedule: CoolingSchedule,
+    reheat: Option<Reheat>,
+    frozen: Option<Frozen>,
+    return_to_best: bool,
+    n_steps: i64,
+}
+
+// Отжиг с параметрами из интерфейса, возвращает лучшее состояние
+fn anneal<T: State + Clone + 'static>(
+    state: T,
+    annealing: Annealing,
+    control: &RunControl,
+    rng: &mut impl Rng,
+) -> (T, simulated_annealing::SolutionInfo<T>) {
+    let (_, info) = simulated_annealing::sim_ang(
+        state,
+        annealing.min_temperature,
+        annealing.initial_temperature,
+        annealing.schedule,
+        annealing.reheat,
+        annealing.frozen,
+        annealing.return_to_best,
+        annealing.n_steps,
+        control,
+        rng,
+    );
+    (info.best_state.clone(), info)
+}
+
+// Решение любой задачи, состояние - лучшее за все решение
+struct Solved {
+    // Доска, если решалась задача о королевах
+    queens: Option<QueenState>,
+    // Лучшее состояние текстом
+    text: String,
+    energy: f64,
+    // Температура, кол-во плохих решений и энергия каждой температуры
+    steps: Vec<[f64; 3]>,
+    // Подобранная макс температура
+    calibrated_temperature: Option<f64>,
+    // Номер температуры, на которой найдено лучшее состояние
+    best_step: usize,
+}
+
+impl Solved {
+    fn new<T: State + Clone>(
+        state: &T,
+        info: &simulated_annealing::SolutionInfo<T>,
+        text: String,
+    ) -> Self {
         Self {
-            positions: new_positions,
-            n: self.n,
-        }
-    }
-    // Расчет энергии. Считаем только пересечения по диагонали
-    fn energy(&self) -> f64 {
-        let mut energy = 0.0f64;
-        for i in 0..self.positions.len() {
-            for j in i + 1..self.positions.len() {
-                if i.abs_diff(j) == self.positions[i].abs_diff(self.positions[j]) {
-                    energy += 1.0f64;
-                    break;
-                }
-            }
+            queens: None,
+            text,
+            energy: state.energy(),
+            steps: info
+                .steps
+                .iter()
+                .map(|x| [x.temperature, x.bad_decisions as f64, x.final_energy])
+                .collect(),
+            best_step: info.best_step,
+            calibrated_temperature: match info.initial_temperature {
+                InitialTemperature::Calibrated { .. } => Some(info.max_temperature),
+                InitialTemperature::Fixed(_) => None,
+            },
         }
-        energy
-    }
-    // Такая генерация гарантирует отсутствие повторений в столбцах
-    fn setup(&mut self) {
-        self.positions = (0..self.n).collect();
     }
 }
 
 struct MyApp {
     // Макс температура
     max_temperature_str: String,
+    // Подбор макс температуры по доле принятых ухудшений
+    calibrate: bool,
+    calibration_acceptance: f64,
+    calibration_samples: usize,
+    // Остановка при замерзании
+    frozen_enabled: bool,
+    frozen: Frozen,
+    // Возврат к лучшему состоянию в конце температуры
+    return_to_best: bool,
     // Мин температура
     min_temperature_str: String,
-    // Коэф. температуры
-    temperature_alpha: String,
+    // Закон понижения температуры
+    schedule: CoolingSchedule,
+    // Повторный нагрев
+    reheat_enabled: bool,
+    reheat: Reheat,
+    // Задача
+    problem: Problem,
     // Кол-во королев
     queens_amount: String,
+    // Кол-во случайных городов
+    cities: usize,
+    // Случайный граф и кол-во цветов
+    vertices: usize,
+    edge_probability: f64,
+    colours: usize,
+    // Кол-во случайных предметов и их макс размер при вместимости 1
+    items: usize,
+    max_item_size: f64,
+    // Судоку, пустые клетки - 0 или '.'
+    sudoku: String,
+    sudoku_error: bool,
+    // Размерность и шаг для функций
+    dimensions: usize,
+    function_step: f64,
     // Шагов без изменения температуры
     steps_n: String,
+    // Зерно генератора случайных чисел
+    seed: String,
     // График
     plot: CustomPlot,
     // Картинка белой клетки
@@ -90,18 +185,17 @@ struct MyApp {
     chess_queen_white: egui_extras::RetainedImage,
     // Картинка черной клетки с королевой клетки
     chess_queen_black: egui_extras::RetainedImage,
-    // Конечное состояние решения
-    state: QueenState,
-    // Информация о решении
-    solution: simulated_annealing::SolutionInfo<QueenState>,
+    // Решение
+    solved: Option<Solved>,
     // Promise функции решения
-    promise:
-        Option<poll_promise::Promise<(QueenState, simulated_annealing::SolutionInfo<QueenState>)>>,
+    promise: Option<poll_promise::Promise<Solved>>,
+    // Остановка и пауза решения
+    control: RunControl,
 }
 
 impl MyApp {
     // Показ шахматной доски
-    fn show_chess_board(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+    fn show_chess_board(&self, state: &QueenState, ui: &mut egui::Ui, ctx: &egui::Context) {
         egui::ScrollArea::new([true, true])
             .min_scrolled_height(400.0)
             .auto_shrink([true, true])
@@ -112,10 +206,10 @@ impl MyApp {
                     .min_col_width(32.0)
                     .spacing([0.0, 0.0])
                     .show(ui, |ui| {
-                        for i in 0..self.state.n {
-                            for j in 0..self.state.n {
+                        for i in 0..state.n {
+                            for j in 0..state.n {
                                 if i % 2 == j % 2 {
-                                    if self.state.positions[i] == j {
+                                    if state.positions()[i] == j {
                                         ui.add(egui::Image::new(
                                             self.chess_queen_white.texture_id(ctx),
                                             self.chess_queen_white.size_vec2(),
@@ -127,7 +221,7 @@ impl MyApp {
                                         ));
                                     }
                                 } else {
-                                    if self.state.positions[i] == j {
+                                    if state.positions()[i] == j {
                                         ui.add(egui::Image::new(
                                             self.chess_queen_black.texture_id(ctx),
                                             self.chess_queen_black.size_vec2(),
@@ -145,32 +239,294 @@ impl MyApp {
                     });
             });
     }
+    // Макс температура, ее подбор и остановка при замерзании
+    fn temperature_options(&mut self, ui: &mut egui::Ui) {
+        ui.label("Max temperature");
+        ui.add_enabled(
+            !self.calibrate,
+            egui::TextEdit::singleline(&mut self.max_temperature_str),
+        );
+        ui.checkbox(&mut self.calibrate, "Calibrate max temperature");
+        ui.add_enabled_ui(self.calibrate, |ui| {
+            ui.add(
+                egui::Slider::new(&mut self.calibration_acceptance, 0.01..=0.99)
+                    .text("Initial acceptance ratio of worse states"),
+            );
+            ui.add(
+                egui::Slider::new(&mut self.calibration_samples, 10..=100000)
+                    .logarithmic(true)
+                    .text("Sampled moves"),
+            );
+        });
+        if let Some(temperature) = self.solved.as_ref().and_then(|x| x.calibrated_temperature) {
+            ui.label(format!("Calibrated max temperature: {:.4}", temperature));
+        }
+
+        ui.checkbox(&mut self.frozen_enabled, "Stop when frozen");
+        ui.add_enabled_ui(self.frozen_enabled, |ui| {
+            ui.add(
+                egui::Slider::new(&mut self.frozen.acceptance, 0.0..=0.5)
+                    .text("Max acceptance ratio of worse states"),
+            );
+            ui.add(
+                egui::Slider::new(&mut self.frozen.temperatures, 1..=100)
+                    .text("Temperatures in a row without better energy"),
+            );
+        });
+    }
+    // Выбор закона понижения температуры и повторного нагрева
+    fn cooling_options(&mut self, ui: &mut egui::Ui) {
+        let schedules = [
+            CoolingSchedule::Geometric { alpha: 0.98 },
+            CoolingSchedule::Linear { step: 0.1 },
+            CoolingSchedule::Logarithmic { c: 10.0 },
+            CoolingSchedule::LundyMees { beta: 0.01 },
+            CoolingSchedule::ExponentialAdditive { cycles: 200 },
+            CoolingSchedule::Adaptive {
+                alpha: 0.98,
+                target: 0.5,
+            },
+        ];
+        let selected = &mut self.schedule;
+        egui::ComboBox::from_label("Cooling schedule")
+            .selected_text(selected.to_string())
+            .show_ui(ui, |ui| {
+                for schedule in schedules {
+                    let is_selected =
+                        std::mem::discriminant(selected) == std::mem::discriminant(&schedule);
+                    if ui
+                        .selectable_label(is_selected, schedule.to_string())
+                        .clicked()
+                        && !is_selected
+                    {
+                        *selected = schedule;
+                    }
+                }
+            });
+        match selected {
+            CoolingSchedule::Geometric { alpha } => {
+                ui.add(egui::Slider::new(alpha, 0.5..=0.9999).text("Temperature coefficient"));
+            }
+            CoolingSchedule::Linear { step } => {
+                ui.add(
+                    egui::DragValue::new(step)
+                        .speed(0.01)
+                        .clamp_range(0.001..=f64::MAX)
+                        .prefix("Step: "),
+                );
+            }
+            CoolingSchedule::Logarithmic { c } => {
+                ui.add(
+                    egui::DragValue::new(c)
+                        .speed(0.1)
+                        .clamp_range(0.001..=f64::MAX)
+                        .prefix("c: "),
+                );
+            }
+            CoolingSchedule::LundyMees { beta } => {
+                ui.add(
+                    egui::DragValue::new(beta)
+                        .speed(0.001)
+                        .clamp_range(0.000_001..=f64::MAX)
+                        .prefix("β: "),
+                );
+            }
+            CoolingSchedule::ExponentialAdditive { cycles } => {
+                ui.add(egui::Slider::new(cycles, 1..=10000).text("Temperatures"));
+            }
+            CoolingSchedule::Adaptive { alpha, target } => {
+                ui.add(egui::Slider::new(alpha, 0.5..=0.9999).text("Temperature coefficient"));
+                ui.add(egui::Slider::new(target, 0.01..=1.0).text("Target acceptance ratio"));
+            }
+        }
+
+        ui.checkbox(&mut self.reheat_enabled, "Reheat");
+        ui.add_enabled_ui(self.reheat_enabled, |ui| {
+            ui.add(
+                egui::Slider::new(&mut self.reheat.after, 0..=1000)
+                    .text("Temperatures without better energy (0 - at min temperature)"),
+            );
+            ui.add(
+                egui::Slider::new(&mut self.reheat.fraction, 0.01..=1.0)
+                    .text("Fraction of max temperature"),
+            );
+            ui.add(egui::Slider::new(&mut self.reheat.times, 1..=100).text("Reheats"));
+        });
+    }
+    // Выбор задачи и ее параметров
+    fn problem_options(&mut self, ui: &mut egui::Ui) {
+        egui::ComboBox::from_label("Problem")
+            .selected_text(self.problem.to_string())
+            .show_ui(ui, |ui| {
+                for problem in [
+                    Problem::Queens,
+                    Problem::Tour,
+                    Problem::Colouring,
+                    Problem::BinPacking,
+                    Problem::Sudoku,
+                    Problem::Function(Function::Rastrigin),
+                    Problem::Function(Function::Rosenbrock),
+                ] {
+                    ui.selectable_value(&mut self.problem, problem, problem.to_string());
+                }
+            });
+        match self.problem {
+            Problem::Queens => {
+                ui.label("Queens amount");
+                ui.text_edit_singleline(&mut self.queens_amount);
+            }
+            Problem::Tour => {
+                ui.add(egui::Slider::new(&mut self.cities, 3..=1000).text("Random cities"));
+            }
+            Problem::Colouring => {
+                ui.add(egui::Slider::new(&mut self.vertices, 2..=1000).text("Random vertices"));
+                ui.add(
+                    egui::Slider::new(&mut self.edge_probability, 0.0..=1.0)
+                        .text("Edge probability"),
+                );
+                ui.add(egui::Slider::new(&mut self.colours, 1..=20).text("Colours"));
+            }
+            Problem::BinPacking => {
+                ui.add(egui::Slider::new(&mut self.items, 1..=1000).text("Random items"));
+                ui.add(
+                    egui::Slider::new(&mut self.max_item_size, 0.01..=1.0)
+                        .text("Max item size (bin capacity is 1)"),
+                );
+            }
+            Problem::Sudoku => {
+                ui.label("Sudoku (81 digits by rows, 0 or . for an empty cell)");
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.sudoku)
+                        .font(egui::TextStyle::Monospace)
+                        .desired_rows(9),
+                );
+                if self.sudoku_error {
+                    ui.colored_label(egui::Color32::RED, "Sudoku needs 81 cells");
+                }
+            }
+            Problem::Function(_) => {
+                ui.add(egui::Slider::new(&mut self.dimensions, 1..=100).text("Dimensions"));
+                ui.add(
+                    egui::Slider::new(&mut self.function_step, 0.001..=2.0)
+                        .logarithmic(true)
+                        .text("Max coordinate step"),
+                );
+            }
+        }
+    }
+    // Решение выбранной задачи на случайном экземпляре из `rng`
+    fn solve(
+        &self,
+        annealing: Annealing,
+        queens_amount: usize,
+        sudoku: Option<SudokuState>,
+        control: RunControl,
+        mut rng: StdRng,
+    ) -> impl FnOnce() -> Solved + Send + 'static {
+        let (problem, cities, vertices, edge_probability, colours, items, max_item_size) = (
+            self.problem,
+            self.cities,
+            self.vertices,
+            self.edge_probability,
+            self.colours,
+            self.items,
+            self.max_item_size,
+        );
+        let (dimensions, function_step) = (self.dimensions, self.function_step);
+        move || match problem {
+            Problem::Queens => {
+                let (state, info) = anneal(
+                    QueenState::new(queens_amount),
+                    annealing,
+                    &control,
+                    &mut rng,
+                );
+                let mut solved = Solved::new(&state, &info, String::new());
+                solved.queens = Some(state);
+                solved
+            }
+            Problem::Tour => {
+                let points = (0..cities)
+                    .map(|_| (rng.gen_range(0.0..1000.0), rng.gen_range(0.0..1000.0)))
+                    .collect::<Vec<(f32, f32)>>();
+                let distances = DistanceMatrix::from_fn(cities, |a, b| {
+                    ((points[a].0 - points[b].0).powi(2) + (points[a].1 - points[b].1).powi(2))
+                        .sqrt()
+                });
+                let (state, info) = anneal(
+                    TourState::new(Arc::new(distances)),
+                    annealing,
+                    &control,
+                    &mut rng,
+                );
+                Solved::new(&state, &info, state.to_string())
+            }
+            Problem::Colouring => {
+                let mut edges = vec![];
+                for a in 0..vertices {
+                    for b in a + 1..vertices {
+                        if rng.gen::<f64>() < edge_probability {
+                            edges.push((a, b));
+                        }
+                    }
+                }
+                let neighbours = ColouringState::neighbour_lists(vertices, &edges);
+                let (state, info) = anneal(
+                    ColouringState::new(Arc::new(neighbours), colours),
+                    annealing,
+                    &control,
+                    &mut rng,
+                );
+                Solved::new(&state, &info, state.to_string())
+            }
+            Problem::BinPacking => {
+                let sizes = (0..items)
+                    .map(|_| rng.gen_range(0.0..=max_item_size))
+                    .collect::<Vec<_>>();
+                let (state, info) = anneal(
+                    BinPackingState::new(Arc::new(sizes), 1.0),
+                    annealing,
+                    &control,
+                    &mut rng,
+                );
+                Solved::new(&state, &info, state.to_string())
+            }
+            Problem::Sudoku => {
+                let (state, info) = anneal(sudoku.unwrap(), annealing, &control, &mut rng);
+                Solved::new(&state, &info, state.to_string())
+            }
+            Problem::Function(function) => {
+                let (state, info) = anneal(
+                    FunctionState::new(function, dimensions, function_step),
+                    annealing,
+                    &control,
+                    &mut rng,
+                );
+                Solved::new(&state, &info, state.to_string())
+            }
+        }
+    }
     // Добавление линий на график
     fn add_lines(&mut self) {
-        self.plot.add_line(
-            self.solution
-                .steps
-                .iter()
-                .map(|x| [x.index as f64, x.temperature])
-                .collect(),
+        let Some(solved) = &self.solved else {
+            return;
+        };
+        for (i, name) in [
             "Температура",
-        );
-        self.plot.add_line(
-            self.solution
-                .steps
-                .iter()
-                .map(|x| [x.index as f64, x.bad_decisions as f64])
-                .collect(),
             "Amount of bad decisions",
-        );
-        self.plot.add_line(
-            self.solution
+            "Energy of the best solution",
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let line = solved
                 .steps
                 .iter()
-                .map(|x| [x.index as f64, x.final_energy])
-                .collect(),
-            "Energy of the best solution",
-        );
+                .enumerate()
+                .map(|(index, x)| [index as f64, x[i]])
+                .collect();
+            self.plot.add_line(line, name);
+        }
     }
 }
 
@@ -179,9 +535,40 @@ impl Default for MyApp {
         Self {
             min_temperature_str: "0.1".into(),
             max_temperature_str: "25".into(),
-            temperature_alpha: "0.98".into(),
+            calibrate: false,
+            calibration_acceptance: 0.8,
+            calibration_samples: 1000,
+            frozen_enabled: false,
+            frozen: Frozen::default(),
+            return_to_best: false,
+            schedule: CoolingSchedule::default(),
+            reheat_enabled: false,
+            reheat: Reheat::default(),
+            problem: Problem::Queens,
             queens_amount: "5".into(),
+            cities: 50,
+            vertices: 50,
+            edge_probability: 0.1,
+            colours: 4,
+            items: 50,
+            max_item_size: 0.6,
+            sudoku: [
+                "53..7....",
+                "6..195...",
+                ".98....6.",
+                "8...6...3",
+                "4..8.3..1",
+                "7...2...6",
+                ".6....28.",
+                "...419..5",
+                "....8..79",
+            ]
+            .join("\n"),
+            sudoku_error: false,
+            dimensions: 10,
+            function_step: 0.5,
             steps_n: "10".into(),
+            seed: rand::random::<u32>().to_string(),
             plot: CustomPlot::new("plot_1", 800.0, 400.0, "Parameters change"),
             chess_white: egui_extras::RetainedImage::from_image_bytes(
                 "chess_white.png",
@@ -203,14 +590,9 @@ impl Default for MyApp {
                 include_bytes!("chess_queen_black.png"),
             )
             .unwrap(),
-            state: QueenState::new(0),
-            solution: simulated_annealing::SolutionInfo {
-                min_temperature: 0.0,
-                max_temperature: 0.0,
-                n_steps: 0,
-                steps: vec![],
-            },
+            solved: None,
             promise: Option::None,
+            control: RunControl::new(),
         }
     }
 }
@@ -233,98 +615,156 @@ impl eframe::App for MyApp {
                                 ui.label("Min temperature");
                                 ui.text_edit_singleline(&mut self.min_temperature_str);
 
-                                ui.label("Max temperature");
-                                ui.text_edit_singleline(&mut self.max_temperature_str);
+                                self.temperature_options(ui);
+
+                                self.cooling_options(ui);
 
-                                ui.label("Temperature coefficient");
-                                ui.text_edit_singleline(&mut self.temperature_alpha);
+                                ui.checkbox(
+                                    &mut self.return_to_best,
+                                    "Return to the best state after a worse temperature",
+                                );
 
-                                ui.label("Queens amount");
-                                ui.text_edit_singleline(&mut self.queens_amount);
+                                self.problem_options(ui);
 
                                 ui.label("Number of steps at constant temperature");
                                 ui.text_edit_singleline(&mut self.steps_n);
 
-                                if ui.button("Calculate").clicked() {
-                                    if self.promise.is_none() {
-                                        self.plot.clear_lines();
-                                        // Достаем параметры из интерфейса
-                                        let min_temperature_str = match
-                                            self.min_temperature_str.parse() {
-                                                Ok(v) => v,
-                                                Err(_) => {
-                                                    self.min_temperature_str = "0".into();
-                                                    0f64
-                                                }
-                                            };
-                                        let max_temperature_str = match
-                                            self.max_temperature_str.parse() {
-                                                Ok(v) => v,
-                                                Err(_) => {
-                                                    self.max_temperature_str = "0".into();
-                                                    0f64
-                                                }
-                                            };
-                                        let queens_amount = match self.queens_amount.parse::<usize>() {
-                                            Ok(v) => v,
-                                            Err(_) => {
-                                                self.queens_amount = "0".into();
-                                                0usize
+                                ui.label("Seed");
+                                ui.horizontal(|ui| {
+                                    ui.text_edit_singleline(&mut self.seed);
+                                    if ui.button("New seed").clicked() {
+                                        self.seed = rand::random::<u32>().to_string();
+                                    }
+                                });
+
+                                if ui.button("Calculate").clicked() && self.promise.is_none() {
+                                    // Судоку проверяем до запуска
+                                    let sudoku = SudokuState::parse(&self.sudoku);
+                                    self.sudoku_error =
+                                        self.problem == Problem::Sudoku && sudoku.is_none();
+                                    if self.sudoku_error {
+                                        return;
+                                    }
+                                    self.plot.clear_lines();
+                                    // Достаем параметры из интерфейса
+                                    let min_temperature_str = match self.min_temperature_str.parse()
+                                    {
+                                        Ok(v) => v,
+                                        Err(_) => {
+                                            self.min_temperature_str = "0".into();
+                                            0f64
+                                        }
+                                    };
+                                    let max_temperature_str = match self.max_temperature_str.parse()
+                                    {
+                                        Ok(v) => v,
+                                        Err(_) => {
+                                            self.max_temperature_str = "0".into();
+                                            0f64
+                                        }
+                                    };
+                                    let queens_amount = match self.queens_amount.parse::<usize>() {
+                                        Ok(v) => v,
+                                        Err(_) => {
+                                            self.queens_amount = "0".into();
+                                            0usize
+                                        }
+                                    };
+                                    let steps_n = match self.steps_n.parse::<i64>() {
+                                        Ok(v) => v,
+                                        Err(_) => {
+                                            self.steps_n = "0".into();
+                                            0i64
+                                        }
+                                    };
+                                    let seed = match self.seed.parse::<u64>() {
+                                        Ok(v) => v,
+                                        Err(_) => {
+                                            self.seed = "0".into();
+                                            0u64
+                                        }
+                                    };
+
+                                    let annealing = Annealing {
+                                        min_temperature: min_temperature_str,
+                                        initial_temperature: if self.calibrate {
+                                            InitialTemperature::Calibrated {
+                                                acceptance: self.calibration_acceptance,
+                                                samples: self.calibration_samples,
                                             }
-                                        };
-                                        let temperature_alpha = match
-                                            self.temperature_alpha.parse::<f64>() {
-                                                Ok(v) => v,
-                                                Err(_) => {
-                                                    self.temperature_alpha = "0".into();
-                                                    0f64
-                                                }
-                                            };
-                                        let steps_n = match
-                                            self.steps_n.parse::<i64>() {
-                                                Ok(v) => v,
-                                                Err(_) => {
-                                                    self.steps_n = "0".into();
-                                                    0i64
-                                                }
-                                            };
-
-                                        self.promise = Some(poll_promise::Promise::<(
-                                            QueenState,
-                                            simulated_annealing::SolutionInfo<QueenState>,
-                                        )>::spawn_thread(
+                                        } else {
+                                            InitialTemperature::Fixed(max_temperature_str)
+                                        },
+                                        schedule: self.schedule,
+                                        reheat: self.reheat_enabled.then_some(self.reheat),
+                                        frozen: self.frozen_enabled.then_some(self.frozen),
+                                        return_to_best: self.return_to_best,
+                                        n_steps: steps_n,
+                                    };
+                                    self.control = RunControl::new();
+                                    self.promise =
+                                        Some(poll_promise::Promise::<Solved>::spawn_thread(
                                             "Simulated annealing calculation",
-                                            move || {
-                                                simulated_annealing::sim_ang(
-                                                    QueenState::new(queens_amount),
-                                                    min_temperature_str,
-                                                    max_temperature_str,
-                                                    |x| x * temperature_alpha,
-                                                    steps_n as i64,
-                                                )
-                                            },
+                                            self.solve(
+                                                annealing,
+                                                queens_amount,
+                                                sudoku,
+                                                self.control.clone(),
+                                                StdRng::seed_from_u64(seed),
+                                            ),
                                         ));
-                                    }
                                 }
                             });
 
                             ui.add(&mut self.plot);
                         });
-                        ui.heading("Chess board");
+                        ui.heading("Solution");
 
                         match &self.promise {
                             Some(p) => {
-                                if let Some(value) = p.ready() {
-                                    self.state = value.0.clone();
-                                    self.solution = value.1.clone();
+                                if p.ready().is_some() {
+                                    self.solved = self.promise.take().map(|x| x.block_and_take());
                                     self.add_lines();
-                                    self.promise = Option::None;
                                 } else {
-                                    ui.spinner();
+                                    ui.horizontal(|ui| {
+                                        ui.spinner();
+                                        if self.control.is_paused() {
+                                            if ui.button("Resume").clicked() {
+                                                self.control.resume();
+                                            }
+                                        } else if ui.button("Pause").clicked() {
+                                            self.control.pause();
+                                        }
+                                        if ui.button("Stop").clicked() {
+                                            self.control.cancel();
+                                        }
+                                    });
                                 }
                             }
                             None => {
-                                self.show_chess_board(ui, ctx);
+                                if let Some(solved) = &self.solved {
+                                    ui.label(format!(
+                                        "Best energy: {} (temperature #{})",
+                                        solved.energy, solved.best_step
+                                    ));
+                                    match &solved.queens {
+                                        // Большую доску не рисуем
+                                        Some(state) if state.n <= 100 => {
+                                            self.show_chess_board(state, ui, ctx)
+                                        }
+                                        Some(_) => {
+                                            ui.label("The board is too large to show");
+                                        }
+                                        None => {
+                                            egui::ScrollArea::new([true, true])
+                                                .id_source("solution_text")
+                                                .max_height(400.0)
+                                                .show(ui, |ui| {
+                                                    ui.monospace(&solved.text);
+                                                });
+                                        }
+                                    }
+                                }
                             }
                         };
                     });
@@ -351,7 +791,7 @@ pub struct CustomLine {
 impl CustomLine {
     fn new(data: Vec<[f64; 2]>, name: impl Into<String>) -> Self {
         Self {
-            data: data,
+            data,
             name: name.into(),
         }
     }
@@ -361,8 +801,8 @@ impl CustomPlot {
     fn new(plot_id: impl Into<String>, width: f32, height: f32, title: impl Into<String>) -> Self {
         Self {
             plot_id: plot_id.into(),
-            width: width,
-            height: height,
+            width,
+            height,
             title: title.into(),
             lines: Default::default(),
         }